@@ -100,7 +100,7 @@ fn main() {
             // combinator if the start element is 0
             SliceMethod::Red => triangles.slice(..3), // TessSlice::one_slice(&triangles, 0, 3),
             // the blue triangle is at slice [3..]
-            SliceMethod::Blue => triangles.slice(3..), // TessSlice::one_slice(&triangles, 3, 6),
+            SliceMethod::Blue => triangles.slice(3..), // TessSlice::one_slice(&triangles, 3, 3),
             // both triangles are at slice [0..6] or [..], but we’ll use the faster
             // TessSlice::one_whole combinator; this combinator is also if you invoke the From or
             // Into method on (&triangles) (we did that in 02-render-state)
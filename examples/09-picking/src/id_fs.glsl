@@ -0,0 +1,7 @@
+out uint frag;
+
+uniform uint id;
+
+void main() {
+  frag = id;
+}
@@ -0,0 +1,138 @@
+//! This program shows how to perform *GPU picking*: each object is rendered with a unique
+//! identifier into an offscreen framebuffer with an unsigned integral color buffer, and the
+//! identifier under the cursor is read back when you click.
+//!
+//! Click on a triangle to get its identifier printed.
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::Framebuffer;
+use luminance::pixel::R32UI;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess, TessSlice, TessSliceIndex};
+use luminance::texture::{Dim2, Flat, Texture};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, MouseButton, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+// fragment shader outputting the identifier of the object
+const ID_FS: &'static str = include_str!("id_fs.glsl");
+
+type Vertex = ([f32; 2], [f32; 3]);
+
+const TRI_VERTICES: [Vertex; 6] = [
+  // first triangle – an RGB one
+  ([ 0.5, -0.5], [0., 1., 0.]),
+  ([ 0.0,  0.5], [0., 0., 1.]),
+  ([-0.5, -0.5], [1., 0., 0.]),
+  // second triangle, a purple one, positioned differently
+  ([-0.5,  0.5], [1., 0.2, 1.]),
+  ([ 0.0, -0.5], [0.2, 1., 1.]),
+  ([ 0.5,  0.5], [0.2, 0.2, 1.]),
+];
+
+uniform_interface! {
+  struct IdInterface {
+    // identifier of the object being rendered; 0 is reserved for “nothing”
+    id: u32
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (program, _) = Program::<Vertex, (), ()>::from_strings(None, VS, None, FS).expect("program creation");
+  let (id_program, _) = Program::<Vertex, (), IdInterface>::from_strings(None, VS, None, ID_FS).expect("id program creation");
+
+  let triangles = Tess::new(&mut surface, Mode::Triangle, &TRI_VERTICES[..], None);
+
+  let mut surf_size = surface.size();
+  let mut back_buffer = Framebuffer::back_buffer(surf_size);
+  // offscreen buffer holding the identifiers of the objects
+  let mut id_buffer =
+    Framebuffer::<Flat, Dim2, Texture<Flat, Dim2, R32UI>, ()>::new(&mut surface, surf_size, 0).expect("framebuffer creation");
+
+  let mut cursor = [0., 0.];
+  let mut pick = false;
+  let mut update_buffers = None;
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::CursorPos(x, y) => {
+          cursor = [x, y];
+        }
+
+        WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
+          pick = true;
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          update_buffers = Some([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    if let Some(size) = update_buffers {
+      surf_size = size;
+      back_buffer = Framebuffer::back_buffer(size);
+      id_buffer = Framebuffer::new(&mut surface, size, 0).expect("framebuffer recreation");
+
+      update_buffers = None;
+    }
+
+    let builder = surface.pipeline_builder();
+
+    // render the identifiers of the triangles; the clear color gets converted to 0
+    builder.pipeline(&id_buffer, [0., 0., 0., 0.], |_, shd_gate| {
+      shd_gate.shade(&id_program, |rdr_gate, iface| {
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          iface.id.update(1);
+          tess_gate.render(&mut surface, TessSlice::one_slice(&triangles, 0, 3));
+
+          iface.id.update(2);
+          tess_gate.render(&mut surface, TessSlice::one_slice(&triangles, 3, 3));
+        });
+      });
+    });
+
+    if pick {
+      // the cursor origin is the upper-left corner while the framebuffer’s is the lower-left one
+      let x = cursor[0] as u32;
+      let y = surf_size[1].saturating_sub(cursor[1] as u32 + 1);
+
+      match id_buffer.read_pixel_u32(&mut surface, x, y) {
+        0 => println!("picked nothing"),
+        id => println!("picked triangle {}", id)
+      }
+
+      pick = false;
+    }
+
+    // render the triangles on the screen
+    builder.pipeline(&back_buffer, [0., 0., 0., 0.], |_, shd_gate| {
+      shd_gate.shade(&program, |rdr_gate, _| {
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, triangles.slice(..));
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
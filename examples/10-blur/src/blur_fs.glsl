@@ -0,0 +1,22 @@
+in vec2 v_uv;
+
+out vec4 frag;
+
+uniform sampler2D source_texture;
+uniform vec2 direction;
+
+// 9-tap gaussian kernel, using linear filtering to fetch two texels at once
+const float OFFSETS[3] = float[](0., 1.3846153846, 3.2307692308);
+const float WEIGHTS[3] = float[](0.2270270270, 0.3162162162, 0.0702702703);
+
+void main() {
+  vec2 texel = direction / vec2(textureSize(source_texture, 0));
+  vec3 color = texture(source_texture, v_uv).rgb * WEIGHTS[0];
+
+  for (int i = 1; i < 3; ++i) {
+    color += texture(source_texture, v_uv + texel * OFFSETS[i]).rgb * WEIGHTS[i];
+    color += texture(source_texture, v_uv - texel * OFFSETS[i]).rgb * WEIGHTS[i];
+  }
+
+  frag = vec4(color, 1.);
+}
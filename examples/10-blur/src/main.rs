@@ -0,0 +1,157 @@
+//! This program shows how to blur an offscreen render by alternating between two framebuffers with
+//! a `PingPong`. Each blur iteration is made of two passes – an horizontal one and a vertical
+//! one – each reading the result of the previous pass.
+//!
+//! Press <up> or <down> to change the number of blur iterations.
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::Framebuffer;
+use luminance::pipeline::BoundTexture;
+use luminance::ping_pong::PingPong;
+use luminance::pixel::RGBA32F;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess, TessSliceIndex};
+use luminance::texture::{Dim2, Flat, Texture};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+// fullscreen quad shaders
+const QUAD_VS: &'static str = include_str!("quad_vs.glsl");
+const BLUR_FS: &'static str = include_str!("blur_fs.glsl");
+const COPY_FS: &'static str = include_str!("copy_fs.glsl");
+
+type Vertex = ([f32; 2], [f32; 3]);
+
+const TRI_VERTICES: [Vertex; 3] = [
+  ([ 0.5, -0.5], [0., 1., 0.]),
+  ([ 0.0,  0.5], [0., 0., 1.]),
+  ([-0.5, -0.5], [1., 0., 0.]),
+];
+
+// both framebuffers of the ping-pong have a single color texture
+type BlurBuffers = PingPong<Flat, Dim2, Texture<Flat, Dim2, RGBA32F>, ()>;
+
+uniform_interface! {
+  struct BlurInterface {
+    #[unbound, as("source_texture")]
+    texture: &'static BoundTexture<'static, Flat, Dim2, RGBA32F>,
+    // direction of the blur pass: [1, 0] for horizontal and [0, 1] for vertical
+    direction: [f32; 2]
+  }
+}
+
+uniform_interface! {
+  struct CopyInterface {
+    #[unbound, as("source_texture")]
+    texture: &'static BoundTexture<'static, Flat, Dim2, RGBA32F>
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (program, _) = Program::<Vertex, (), ()>::from_strings(None, VS, None, FS).expect("program creation");
+  let (blur_program, _) = Program::<(), (), BlurInterface>::from_strings(None, QUAD_VS, None, BLUR_FS).expect("blur program creation");
+  let (copy_program, _) = Program::<(), (), CopyInterface>::from_strings(None, QUAD_VS, None, COPY_FS).expect("copy program creation");
+
+  let triangle = Tess::new(&mut surface, Mode::Triangle, &TRI_VERTICES[..], None);
+  let quad = Tess::attributeless(&mut surface, Mode::TriangleFan, 4);
+
+  let surf_size = surface.size();
+  let mut back_buffer = Framebuffer::back_buffer(surf_size);
+  let mut ping_pong = BlurBuffers::new(&mut surface, surf_size, 0).expect("ping-pong creation");
+  let mut update_buffers = None;
+  let mut iterations = 4;
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::Key(Key::Up, _, Action::Release, _) => {
+          iterations += 1;
+          println!("blur iterations: {}", iterations);
+        }
+
+        WindowEvent::Key(Key::Down, _, Action::Release, _) => {
+          iterations = if iterations > 0 { iterations - 1 } else { 0 };
+          println!("blur iterations: {}", iterations);
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          update_buffers = Some([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    if let Some(size) = update_buffers {
+      back_buffer = Framebuffer::back_buffer(size);
+      ping_pong = BlurBuffers::new(&mut surface, size, 0).expect("ping-pong recreation");
+
+      update_buffers = None;
+    }
+
+    let builder = surface.pipeline_builder();
+
+    // render the triangle; it then becomes the framebuffer to read from
+    builder.pipeline(ping_pong.write(), [0., 0., 0., 0.], |_, shd_gate| {
+      shd_gate.shade(&program, |rdr_gate, _| {
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, triangle.slice(..));
+        });
+      });
+    });
+
+    ping_pong.swap();
+
+    // each iteration is an horizontal pass followed by a vertical pass
+    for _ in 0..iterations {
+      for &direction in &[[1., 0.], [0., 1.]] {
+        builder.pipeline(ping_pong.write(), [0., 0., 0., 0.], |pipeline, shd_gate| {
+          let source = pipeline.bind_texture(ping_pong.read().color_slot());
+
+          shd_gate.shade(&blur_program, |rdr_gate, iface| {
+            iface.texture.update(&source);
+            iface.direction.update(direction);
+
+            rdr_gate.render(RenderState::default(), |tess_gate| {
+              tess_gate.render(&mut surface, quad.slice(..));
+            });
+          });
+        });
+
+        ping_pong.swap();
+      }
+    }
+
+    // output the result of the last pass to the screen
+    builder.pipeline(&back_buffer, [0., 0., 0., 0.], |pipeline, shd_gate| {
+      let source = pipeline.bind_texture(ping_pong.read().color_slot());
+
+      shd_gate.shade(&copy_program, |rdr_gate, iface| {
+        iface.texture.update(&source);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, quad.slice(..));
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
@@ -0,0 +1,80 @@
+//! This program shows how to render many copies of the same tessellation with a single draw
+//! command. 10,000 small triangles are rendered, each instance reading its own offset from a
+//! per-instance vertex attribute.
+//!
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::Framebuffer;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess, TessSliceIndex};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+type Vertex = ([f32; 2], [f32; 3]);
+
+// per-instance attribute
+type Instance = [f32; 2];
+
+const TRI_VERTICES: [Vertex; 3] = [
+  ([ 0.005, -0.005], [0., 1., 0.]),
+  ([ 0.0,    0.005], [0., 0., 1.]),
+  ([-0.005, -0.005], [1., 0., 0.]),
+];
+
+// number of instances along each axis
+const GRID_SIZE: usize = 100;
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (program, _) = Program::<Vertex, (), ()>::from_strings(None, VS, None, FS).expect("program creation");
+
+  // lay the instances out on a grid covering the screen
+  let step = 2. / GRID_SIZE as f32;
+  let instances: Vec<Instance> = (0..GRID_SIZE * GRID_SIZE).map(|i| {
+    let (x, y) = (i % GRID_SIZE, i / GRID_SIZE);
+    [-1. + step * (x as f32 + 0.5), -1. + step * (y as f32 + 0.5)]
+  }).collect();
+
+  let mut triangle = Tess::new(&mut surface, Mode::Triangle, &TRI_VERTICES[..], None);
+  triangle.set_instances(&mut surface, &instances);
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    surface.pipeline_builder().pipeline(&back_buffer, [0., 0., 0., 0.], |_, shd_gate| {
+      shd_gate.shade(&program, |rdr_gate, _| {
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          // a single draw command for all the triangles
+          tess_gate.render_instanced(&mut surface, triangle.slice(..), instances.len());
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
@@ -0,0 +1,11 @@
+layout (location = 0) in vec2 co;
+layout (location = 1) in vec3 color;
+// per-instance attribute: one offset per triangle
+layout (location = 2) in vec2 offset;
+
+out vec3 v_color;
+
+void main() {
+  gl_Position = vec4(co + offset, 0., 1.);
+  v_color = color;
+}
@@ -0,0 +1,13 @@
+in vec3 g_color;
+in vec2 g_uv;
+
+out vec4 frag;
+
+void main() {
+  // round sprites: discard the corners of the quad
+  if (dot(g_uv, g_uv) > 1.) {
+    discard;
+  }
+
+  frag = vec4(g_color, 1.);
+}
@@ -0,0 +1,28 @@
+layout (points) in;
+layout (triangle_strip, max_vertices = 4) out;
+
+in vec3 v_color[];
+
+out vec3 g_color;
+out vec2 g_uv;
+
+uniform float size;
+uniform float aspect_ratio;
+
+// emit a corner of the quad; the quad is built in clip space so that it always faces the camera
+void emit_corner(vec2 corner) {
+  vec2 offset = corner * size * vec2(1. / aspect_ratio, 1.);
+
+  gl_Position = gl_in[0].gl_Position + vec4(offset, 0., 0.);
+  g_color = v_color[0];
+  g_uv = corner;
+  EmitVertex();
+}
+
+void main() {
+  emit_corner(vec2(-1., -1.));
+  emit_corner(vec2( 1., -1.));
+  emit_corner(vec2(-1.,  1.));
+  emit_corner(vec2( 1.,  1.));
+  EndPrimitive();
+}
@@ -0,0 +1,105 @@
+//! This program shows how to use a geometry shader to expand points into quads – often called
+//! *point sprites*. Only one vertex per sprite is sent to the GPU; the geometry shader emits the
+//! four corners of a quad facing the camera around it.
+//!
+//! Press <up> or <down> to change the size of the sprites.
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::Framebuffer;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess, TessSliceIndex};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+use std::f32::consts::PI;
+use std::time::Instant;
+
+const VS: &'static str = include_str!("vs.glsl");
+const GS: &'static str = include_str!("gs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+type Vertex = ([f32; 2], [f32; 3]);
+
+// number of sprites
+const POINT_NB: usize = 64;
+
+uniform_interface! {
+  struct ShaderInterface {
+    t: f32,
+    // half the height of a sprite, in clip space
+    size: f32,
+    aspect_ratio: f32
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  // the geometry shader sits between the vertex and the fragment shaders
+  let (program, _) = Program::<Vertex, (), ShaderInterface>::from_strings(None, VS, GS, FS).expect("program creation");
+
+  // lay the points out on a spiral
+  let points: Vec<Vertex> = (0..POINT_NB).map(|i| {
+    let k = i as f32 / POINT_NB as f32;
+    let angle = k * 6. * PI;
+    let radius = 0.1 + 0.8 * k;
+
+    ([radius * angle.cos(), radius * angle.sin()], [k, 1. - k, 0.5])
+  }).collect();
+
+  let sprites = Tess::new(&mut surface, Mode::Point, &points[..], None);
+
+  let mut size = surface.size();
+  let mut back_buffer = Framebuffer::back_buffer(size);
+  let mut sprite_size = 0.03;
+  let start_t = Instant::now();
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::Key(Key::Up, _, Action::Release, _) => {
+          sprite_size *= 1.25;
+        }
+
+        WindowEvent::Key(Key::Down, _, Action::Release, _) => {
+          sprite_size /= 1.25;
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          size = [width as u32, height as u32];
+          back_buffer = Framebuffer::back_buffer(size);
+        }
+
+        _ => ()
+      }
+    }
+
+    let elapsed = start_t.elapsed();
+    let t = elapsed.as_secs() as f32 + elapsed.subsec_millis() as f32 * 1e-3;
+
+    surface.pipeline_builder().pipeline(&back_buffer, [0., 0., 0., 0.], |_, shd_gate| {
+      shd_gate.shade(&program, |rdr_gate, iface| {
+        iface.t.update(t);
+        iface.size.update(sprite_size);
+        iface.aspect_ratio.update(size[0] as f32 / size[1] as f32);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, sprites.slice(..));
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
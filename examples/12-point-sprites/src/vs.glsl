@@ -0,0 +1,15 @@
+layout (location = 0) in vec2 co;
+layout (location = 1) in vec3 color;
+
+out vec3 v_color;
+
+uniform float t;
+
+void main() {
+  // slowly spin the points around the center of the screen
+  float c = cos(t * .2);
+  float s = sin(t * .2);
+
+  gl_Position = vec4(mat2(c, s, -s, c) * co, 0., 1.);
+  v_color = color;
+}
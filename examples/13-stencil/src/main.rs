@@ -0,0 +1,108 @@
+//! This program shows how to use the stencil test to mask renders. A first pass renders a triangle
+//! and writes `1` into the stencil buffer wherever it’s rendered; a second pass renders a quad
+//! covering the whole screen but only where the stencil buffer holds – or doesn’t hold – `1`.
+//!
+//! Press <space> to switch between rendering the quad inside or outside of the triangle.
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::Framebuffer;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess};
+use luminance::render_state::RenderState;
+use luminance::stencil::{StencilComparison, StencilOperation, StencilTest};
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::depth_test::DepthTest;
+use luminance::context::GraphicsContext;
+
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+type Vertex = ([f32; 2], [f32; 3]);
+
+// the mask – a gray triangle
+const MASK_VERTICES: [Vertex; 3] = [
+  ([ 0.5, -0.5], [0.2, 0.2, 0.2]),
+  ([ 0.0,  0.5], [0.2, 0.2, 0.2]),
+  ([-0.5, -0.5], [0.2, 0.2, 0.2]),
+];
+
+// the masked quad, covering the whole screen
+const QUAD_VERTICES: [Vertex; 4] = [
+  ([-1., -1.], [1., 0., 0.]),
+  ([ 1., -1.], [0., 1., 0.]),
+  ([ 1.,  1.], [0., 0., 1.]),
+  ([-1.,  1.], [1., 1., 0.]),
+];
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (program, _) = Program::<Vertex, (), ()>::from_strings(None, VS, None, FS).expect("program creation");
+
+  let mask = Tess::new(&mut surface, Mode::Triangle, &MASK_VERTICES[..], None);
+  let quad = Tess::new(&mut surface, Mode::TriangleFan, &QUAD_VERTICES[..], None);
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+
+  let mut comparison = StencilComparison::Equal;
+  println!("now rendering the quad where the stencil comparison is {:?}", comparison);
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::Key(Key::Space, _, Action::Release, _) => {
+          comparison = match comparison {
+            StencilComparison::Equal => StencilComparison::NotEqual,
+            _ => StencilComparison::Equal
+          };
+
+          println!("now rendering the quad where the stencil comparison is {:?}", comparison);
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    // the stencil buffer is cleared to 0 when the pipeline starts
+    surface.pipeline_builder().pipeline(&back_buffer, [0., 0., 0., 0.], |_, shd_gate| {
+      shd_gate.shade(&program, |rdr_gate, _| {
+        // first pass: the test always passes and the reference value – 1 – replaces the stored one
+        let write_state = RenderState::default()
+          .set_depth_test(DepthTest::Disabled)
+          .set_stencil_test(
+            StencilTest::new(StencilComparison::Always, 1)
+              .set_operations(StencilOperation::Keep, StencilOperation::Keep, StencilOperation::Replace)
+          );
+
+        rdr_gate.render(write_state, |tess_gate| {
+          tess_gate.render(&mut surface, (&mask).into());
+        });
+
+        // second pass: only render where the stored value compares to 1 and leave it untouched
+        let test_state = RenderState::default()
+          .set_depth_test(DepthTest::Disabled)
+          .set_stencil_test(StencilTest::new(comparison, 1));
+
+        rdr_gate.render(test_state, |tess_gate| {
+          tess_gate.render(&mut surface, (&quad).into());
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
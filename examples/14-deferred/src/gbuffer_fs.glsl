@@ -0,0 +1,14 @@
+in vec3 v_co;
+in vec3 v_normal;
+in vec3 v_color;
+
+// each output is written to the color buffer of the same index
+layout (location = 0) out vec4 position;
+layout (location = 1) out vec4 normal;
+layout (location = 2) out vec4 albedo;
+
+void main() {
+  position = vec4(v_co, 1.);
+  normal = vec4(normalize(v_normal), 1.);
+  albedo = vec4(v_color, 1.);
+}
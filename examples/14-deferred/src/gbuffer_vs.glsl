@@ -0,0 +1,14 @@
+layout (location = 0) in vec3 co;
+layout (location = 1) in vec3 normal;
+layout (location = 2) in vec3 color;
+
+out vec3 v_co;
+out vec3 v_normal;
+out vec3 v_color;
+
+void main() {
+  gl_Position = vec4(co, 1.);
+  v_co = co;
+  v_normal = normal;
+  v_color = color;
+}
@@ -0,0 +1,31 @@
+in vec2 v_uv;
+
+out vec4 frag;
+
+uniform sampler2D position_texture;
+uniform sampler2D normal_texture;
+uniform sampler2D albedo_texture;
+uniform vec3 light_position;
+// 0: lit, 1: position, 2: normal, 3: albedo
+uniform int view;
+
+void main() {
+  vec4 position = texture(position_texture, v_uv);
+  vec3 normal = texture(normal_texture, v_uv).xyz;
+  vec3 albedo = texture(albedo_texture, v_uv).rgb;
+
+  vec3 color;
+  if (view == 1) {
+    color = position.xyz * .5 + .5;
+  } else if (view == 2) {
+    color = normal * .5 + .5;
+  } else if (view == 3) {
+    color = albedo;
+  } else {
+    // the alpha channel of the position is 0 where nothing was rendered
+    float diffuse = max(0., dot(normal, normalize(light_position - position.xyz)));
+    color = albedo * diffuse * position.a;
+  }
+
+  frag = vec4(pow(color, vec3(1./2.2)), 1.);
+}
@@ -0,0 +1,145 @@
+//! This program shows how to render into several color buffers at once – also known as *multiple
+//! render targets*. A first pass renders the position, normal and albedo of two triangles into the
+//! three color buffers of a *G-buffer*; a second pass reads them back to light the scene with a
+//! rotating light. This technique is called *deferred shading*.
+//!
+//! Press <space> to cycle through the lit scene, the positions, the normals and the albedos.
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::Framebuffer;
+use luminance::pipeline::BoundTexture;
+use luminance::pixel::{Depth32F, RGBA32F};
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess, TessSliceIndex};
+use luminance::texture::{Dim2, Flat, Texture};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+use std::time::Instant;
+
+const GBUFFER_VS: &'static str = include_str!("gbuffer_vs.glsl");
+const GBUFFER_FS: &'static str = include_str!("gbuffer_fs.glsl");
+
+// fullscreen quad shaders
+const QUAD_VS: &'static str = include_str!("quad_vs.glsl");
+const LIGHTING_FS: &'static str = include_str!("lighting_fs.glsl");
+
+// position, normal and color
+type Vertex = ([f32; 3], [f32; 3], [f32; 3]);
+
+const TRI_VERTICES: [Vertex; 6] = [
+  // a red triangle facing the camera
+  ([-0.8, -0.5, 0.], [0., 0., -1.], [1., 0.2, 0.2]),
+  ([ 0.2, -0.5, 0.], [0., 0., -1.], [1., 0.2, 0.2]),
+  ([-0.3,  0.5, 0.], [0., 0., -1.], [1., 0.2, 0.2]),
+  // a blue triangle facing the top-left corner
+  ([-0.2, -0.5, 0.], [-0.5, 0.5, -0.7], [0.2, 0.2, 1.]),
+  ([ 0.8, -0.5, 0.], [-0.5, 0.5, -0.7], [0.2, 0.2, 1.]),
+  ([ 0.3,  0.5, 0.], [-0.5, 0.5, -0.7], [0.2, 0.2, 1.]),
+];
+
+// the G-buffer: three color buffers – position, normal and albedo – and a depth buffer
+type GBufferTexture = Texture<Flat, Dim2, RGBA32F>;
+type GBuffer = Framebuffer<Flat, Dim2, (GBufferTexture, GBufferTexture, GBufferTexture), Texture<Flat, Dim2, Depth32F>>;
+
+uniform_interface! {
+  struct LightingInterface {
+    #[unbound]
+    position_texture: &'static BoundTexture<'static, Flat, Dim2, RGBA32F>,
+    #[unbound]
+    normal_texture: &'static BoundTexture<'static, Flat, Dim2, RGBA32F>,
+    #[unbound]
+    albedo_texture: &'static BoundTexture<'static, Flat, Dim2, RGBA32F>,
+    light_position: [f32; 3],
+    view: i32
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (gbuffer_program, _) = Program::<Vertex, (), ()>::from_strings(None, GBUFFER_VS, None, GBUFFER_FS).expect("G-buffer program creation");
+  let (lighting_program, _) = Program::<(), (), LightingInterface>::from_strings(None, QUAD_VS, None, LIGHTING_FS).expect("lighting program creation");
+
+  let triangles = Tess::new(&mut surface, Mode::Triangle, &TRI_VERTICES[..], None);
+  let quad = Tess::attributeless(&mut surface, Mode::TriangleFan, 4);
+
+  let surf_size = surface.size();
+  let mut back_buffer = Framebuffer::back_buffer(surf_size);
+  let mut gbuffer = GBuffer::new(&mut surface, surf_size, 0).expect("G-buffer creation");
+  let mut update_buffers = None;
+  let mut view = 0;
+
+  let start = Instant::now();
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::Key(Key::Space, _, Action::Release, _) => {
+          view = (view + 1) % 4;
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          update_buffers = Some([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    if let Some(size) = update_buffers {
+      back_buffer = Framebuffer::back_buffer(size);
+      gbuffer = GBuffer::new(&mut surface, size, 0).expect("G-buffer recreation");
+
+      update_buffers = None;
+    }
+
+    let t = start.elapsed();
+    let t = t.as_secs() as f32 + t.subsec_nanos() as f32 * 1e-9;
+    let light_position = [t.cos(), t.sin(), -1.];
+
+    let builder = surface.pipeline_builder();
+
+    // geometry pass: write the three outputs of the fragment shader to the three color buffers
+    builder.pipeline(&gbuffer, [0., 0., 0., 0.], |_, shd_gate| {
+      shd_gate.shade(&gbuffer_program, |rdr_gate, _| {
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, triangles.slice(..));
+        });
+      });
+    });
+
+    // lighting pass: read the G-buffer back and light the scene
+    builder.pipeline(&back_buffer, [0., 0., 0., 0.], |pipeline, shd_gate| {
+      let (ref positions, ref normals, ref albedos) = *gbuffer.color_slot();
+      let positions = pipeline.bind_texture(positions);
+      let normals = pipeline.bind_texture(normals);
+      let albedos = pipeline.bind_texture(albedos);
+
+      shd_gate.shade(&lighting_program, |rdr_gate, iface| {
+        iface.position_texture.update(&positions);
+        iface.normal_texture.update(&normals);
+        iface.albedo_texture.update(&albedos);
+        iface.light_position.update(light_position);
+        iface.view.update(view);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, quad.slice(..));
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
@@ -0,0 +1,9 @@
+layout (location = 0) in vec3 co;
+
+// horizontal offset of the light per unit of height
+uniform vec2 light_dir;
+
+void main() {
+  // oblique orthographic projection from the light, looking down
+  gl_Position = vec4((co.xz - co.y * light_dir) * .8, -co.y, 1.);
+}
@@ -0,0 +1,18 @@
+in vec3 v_color;
+in vec3 v_light_co;
+
+out vec4 frag;
+
+uniform sampler2DShadow shadow_map;
+
+const float BIAS = .005;
+
+void main() {
+  // from normalized device coordinates to texture coordinates and window-space depth
+  vec3 light_co = v_light_co * .5 + .5;
+  float lit = texture(shadow_map, vec3(light_co.xy, light_co.z - BIAS));
+  float lighting = mix(.3, 1., lit);
+
+  frag = vec4(v_color * lighting, 1.);
+  frag = pow(frag, vec4(1./2.2));
+}
@@ -0,0 +1,132 @@
+//! This program shows how to sample the depth buffer of a framebuffer by implementing *shadow
+//! mapping*. A first pass renders the scene from the light into a depth-only framebuffer – the
+//! *shadow map*. A second pass renders the scene from the camera and darkens the fragments which
+//! depth, seen from the light, is behind the one stored in the shadow map. The shadow map is
+//! sampled with a depth comparison, which the GPU filters to soften the edges of the shadows.
+//!
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::Framebuffer;
+use luminance::pipeline::BoundTexture;
+use luminance::pixel::Depth32F;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess, TessSliceIndex};
+use luminance::texture::{DepthComparison, Dim2, Flat, Texture};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+use std::time::Instant;
+
+const DEPTH_VS: &'static str = include_str!("depth_vs.glsl");
+const DEPTH_FS: &'static str = include_str!("depth_fs.glsl");
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+// position and color
+type Vertex = ([f32; 3], [f32; 3]);
+
+const VERTICES: [Vertex; 9] = [
+  // the ground, a gray square
+  ([-1., 0., -1.], [0.8, 0.8, 0.8]),
+  ([ 1., 0., -1.], [0.8, 0.8, 0.8]),
+  ([ 1., 0.,  1.], [0.8, 0.8, 0.8]),
+  ([-1., 0., -1.], [0.8, 0.8, 0.8]),
+  ([ 1., 0.,  1.], [0.8, 0.8, 0.8]),
+  ([-1., 0.,  1.], [0.8, 0.8, 0.8]),
+  // a red triangle floating above the ground
+  ([-0.3, 0.5, -0.2], [1., 0.2, 0.2]),
+  ([ 0.3, 0.5, -0.2], [1., 0.2, 0.2]),
+  ([ 0.0, 0.5,  0.3], [1., 0.2, 0.2]),
+];
+
+// the shadow map only has a depth buffer, which is a texture we can sample afterwards
+type ShadowMap = Framebuffer<Flat, Dim2, (), Texture<Flat, Dim2, Depth32F>>;
+
+uniform_interface! {
+  struct DepthInterface {
+    light_dir: [f32; 2]
+  }
+}
+
+uniform_interface! {
+  struct ShaderInterface {
+    #[unbound]
+    shadow_map: &'static BoundTexture<'static, Flat, Dim2, Depth32F>,
+    light_dir: [f32; 2]
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (depth_program, _) = Program::<Vertex, (), DepthInterface>::from_strings(None, DEPTH_VS, None, DEPTH_FS).expect("depth program creation");
+  let (program, _) = Program::<Vertex, (), ShaderInterface>::from_strings(None, VS, None, FS).expect("program creation");
+
+  let scene = Tess::new(&mut surface, Mode::Triangle, &VERTICES[..], None);
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+  let shadow_map = ShadowMap::new(&mut surface, [1024, 1024], 0).expect("shadow map creation");
+
+  // sampling the shadow map returns whether the reference depth is in front of the stored one
+  shadow_map.depth_slot().set_depth_comparison(Some(DepthComparison::LessOrEqual));
+
+  let start = Instant::now();
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    // make the light turn around the scene
+    let t = start.elapsed();
+    let t = t.as_secs() as f32 + t.subsec_nanos() as f32 * 1e-9;
+    let light_dir = [t.cos() * 0.5, t.sin() * 0.5];
+
+    let builder = surface.pipeline_builder();
+
+    // render the depth of the scene, seen from the light, into the shadow map; the clear color is
+    // not used as there’s no color buffer
+    builder.pipeline(&shadow_map, [0., 0., 0., 0.], |_, shd_gate| {
+      shd_gate.shade(&depth_program, |rdr_gate, iface| {
+        iface.light_dir.update(light_dir);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, scene.slice(..));
+        });
+      });
+    });
+
+    // render the scene from the camera, sampling the shadow map
+    builder.pipeline(&back_buffer, [0.1, 0.1, 0.2, 1.], |pipeline, shd_gate| {
+      let depths = pipeline.bind_texture(shadow_map.depth_slot());
+
+      shd_gate.shade(&program, |rdr_gate, iface| {
+        iface.shadow_map.update(&depths);
+        iface.light_dir.update(light_dir);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, scene.slice(..));
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
@@ -0,0 +1,20 @@
+layout (location = 0) in vec3 co;
+layout (location = 1) in vec3 color;
+
+out vec3 v_color;
+out vec3 v_light_co;
+
+uniform vec2 light_dir;
+
+const float ANGLE = .6;
+
+void main() {
+  // tilt the scene towards the camera and project it orthographically
+  float y = co.y * cos(ANGLE) - co.z * sin(ANGLE);
+  float z = co.y * sin(ANGLE) + co.z * cos(ANGLE);
+  gl_Position = vec4(co.x * .7, y * .7, -z * .5, 1.);
+
+  v_color = color;
+  // same projection as in the depth pass
+  v_light_co = vec3((co.xz - co.y * light_dir) * .8, -co.y);
+}
@@ -0,0 +1,206 @@
+//! This program shows how to use *cubemaps*. A skybox is generated on the CPU and uploaded to the
+//! six faces of a cubemap. Every frame, the sky and a moving sun are rendered into a second cubemap
+//! – the *environment map* – face by face, through a cubemap framebuffer. The scene is then
+//! rendered from an orbiting camera: the sky, and a sphere reflecting the environment map.
+//!
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::Framebuffer;
+use luminance::pipeline::BoundTexture;
+use luminance::pixel::RGBA32F;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess};
+use luminance::texture::{CubeFace, Cubemap, Flat, Sampler, Texture};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+use std::time::Instant;
+
+const VS: &'static str = include_str!("vs.glsl");
+const SKY_FS: &'static str = include_str!("sky_fs.glsl");
+const SPHERE_FS: &'static str = include_str!("sphere_fs.glsl");
+
+const SKY_SIZE: u32 = 64;
+const ENV_MAP_SIZE: u32 = 256;
+
+// forward, right and up vectors of a camera looking at each face of a cubemap, in the order of
+// CubeFace::ALL; they follow the OpenGL cubemap conventions
+const FACE_BASES: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+  ([ 1.,  0.,  0.], [ 0.,  0., -1.], [0., -1.,  0.]),
+  ([-1.,  0.,  0.], [ 0.,  0.,  1.], [0., -1.,  0.]),
+  ([ 0.,  1.,  0.], [ 1.,  0.,  0.], [0.,  0.,  1.]),
+  ([ 0., -1.,  0.], [ 1.,  0.,  0.], [0.,  0., -1.]),
+  ([ 0.,  0.,  1.], [ 1.,  0.,  0.], [0., -1.,  0.]),
+  ([ 0.,  0., -1.], [-1.,  0.,  0.], [0., -1.,  0.]),
+];
+
+// the environment map is rendered face by face
+type EnvMap = Framebuffer<Flat, Cubemap, Texture<Flat, Cubemap, RGBA32F>, ()>;
+
+uniform_interface! {
+  struct SkyInterface {
+    #[unbound]
+    sky: &'static BoundTexture<'static, Flat, Cubemap, RGBA32F>,
+    forward: [f32; 3],
+    right: [f32; 3],
+    up: [f32; 3],
+    aspect: f32,
+    sun_dir: [f32; 3]
+  }
+}
+
+uniform_interface! {
+  struct SphereInterface {
+    #[unbound]
+    env_map: &'static BoundTexture<'static, Flat, Cubemap, RGBA32F>,
+    forward: [f32; 3],
+    right: [f32; 3],
+    up: [f32; 3],
+    aspect: f32
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  // both programs render a fullscreen triangle and cast a view ray per fragment
+  let (sky_program, _) = Program::<(), (), SkyInterface>::from_strings(None, VS, None, SKY_FS).expect("sky program creation");
+  let (sphere_program, _) = Program::<(), (), SphereInterface>::from_strings(None, VS, None, SPHERE_FS).expect("sphere program creation");
+
+  let fullscreen = Tess::attributeless(&mut surface, Mode::Triangle, 3);
+
+  let sky = Texture::<Flat, Cubemap, RGBA32F>::new(&mut surface, SKY_SIZE, 0, &Sampler::default()).expect("sky creation");
+  let faces: Vec<Vec<f32>> = FACE_BASES.iter().map(|basis| sky_face(*basis)).collect();
+  sky.upload_faces_raw(false, [&faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5]]).expect("sky upload");
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+  let env_map = EnvMap::new(&mut surface, ENV_MAP_SIZE, 0).expect("environment map creation");
+
+  let start = Instant::now();
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    let t = start.elapsed();
+    let t = t.as_secs() as f32 + t.subsec_nanos() as f32 * 1e-9;
+    let sun_dir = normalize([t.cos(), 0.4, t.sin()]);
+
+    let builder = surface.pipeline_builder();
+
+    // render the sky into each face of the environment map
+    for (face, &(forward, right, up)) in CubeFace::ALL.iter().zip(&FACE_BASES) {
+      env_map.attach_face(&mut surface, *face);
+
+      builder.pipeline(&env_map, [0., 0., 0., 1.], |pipeline, shd_gate| {
+        let sky = pipeline.bind_texture(&sky);
+
+        shd_gate.shade(&sky_program, |rdr_gate, iface| {
+          iface.sky.update(&sky);
+          iface.forward.update(forward);
+          iface.right.update(right);
+          iface.up.update(up);
+          iface.aspect.update(1.);
+          iface.sun_dir.update(sun_dir);
+
+          rdr_gate.render(RenderState::default(), |tess_gate| {
+            tess_gate.render(&mut surface, (&fullscreen).into());
+          });
+        });
+      });
+    }
+
+    // an orbiting camera looking at the sphere, at the origin
+    let yaw = t * 0.2;
+    let forward = [yaw.sin(), 0., -yaw.cos()];
+    let right = [yaw.cos(), 0., yaw.sin()];
+    let up = [0., 1., 0.];
+    let aspect = back_buffer.width() as f32 / back_buffer.height() as f32;
+
+    builder.pipeline(&back_buffer, [0., 0., 0., 1.], |pipeline, shd_gate| {
+      let sky = pipeline.bind_texture(&sky);
+      let env = pipeline.bind_texture(env_map.color_slot());
+
+      shd_gate.shade(&sky_program, |rdr_gate, iface| {
+        iface.sky.update(&sky);
+        iface.forward.update(forward);
+        iface.right.update(right);
+        iface.up.update(up);
+        iface.aspect.update(aspect);
+        iface.sun_dir.update(sun_dir);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, (&fullscreen).into());
+        });
+      });
+
+      // the sphere is ray-traced in the fragment shader, which discards the fragments missing it
+      shd_gate.shade(&sphere_program, |rdr_gate, iface| {
+        iface.env_map.update(&env);
+        iface.forward.update(forward);
+        iface.right.update(right);
+        iface.up.update(up);
+        iface.aspect.update(aspect);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, (&fullscreen).into());
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
+
+// Generate the texels of a face of the skybox: a gradient from the horizon to the zenith, above a
+// dark ground.
+fn sky_face((forward, right, up): ([f32; 3], [f32; 3], [f32; 3])) -> Vec<f32> {
+  let mut texels = Vec::with_capacity((SKY_SIZE * SKY_SIZE * 4) as usize);
+
+  for y in 0..SKY_SIZE {
+    for x in 0..SKY_SIZE {
+      // texel center, in [-1;1]
+      let s = (x as f32 + 0.5) / SKY_SIZE as f32 * 2. - 1.;
+      let t = (y as f32 + 0.5) / SKY_SIZE as f32 * 2. - 1.;
+      let dir = normalize([
+        forward[0] + s * right[0] + t * up[0],
+        forward[1] + s * right[1] + t * up[1],
+        forward[2] + s * right[2] + t * up[2],
+      ]);
+
+      let color = if dir[1] >= 0. {
+        let k = dir[1];
+        [0.8 - 0.6 * k, 0.85 - 0.45 * k, 0.95 - 0.15 * k]
+      } else {
+        [0.2, 0.18, 0.15]
+      };
+
+      texels.extend_from_slice(&[color[0], color[1], color[2], 1.]);
+    }
+  }
+
+  texels
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+  let l = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+  [v[0] / l, v[1] / l, v[2] / l]
+}
@@ -0,0 +1,21 @@
+in vec2 v_pos;
+
+out vec4 frag;
+
+uniform samplerCube sky;
+uniform vec3 forward;
+uniform vec3 right;
+uniform vec3 up;
+uniform float aspect;
+uniform vec3 sun_dir;
+
+void main() {
+  // direction of the view ray going through the fragment, with a 90° field of view
+  vec3 dir = normalize(forward + v_pos.x * aspect * right + v_pos.y * up);
+  vec3 color = texture(sky, dir).rgb;
+
+  // the sun isn’t part of the skybox so that it can move
+  color += vec3(1., .9, .6) * smoothstep(.995, .998, dot(dir, sun_dir));
+
+  frag = vec4(color, 1.);
+}
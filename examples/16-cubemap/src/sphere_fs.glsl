@@ -0,0 +1,29 @@
+in vec2 v_pos;
+
+out vec4 frag;
+
+uniform samplerCube env_map;
+uniform vec3 forward;
+uniform vec3 right;
+uniform vec3 up;
+uniform float aspect;
+
+const float RADIUS = 1.;
+const float DISTANCE = 3.;
+
+void main() {
+  vec3 dir = normalize(forward + v_pos.x * aspect * right + v_pos.y * up);
+  vec3 eye = -DISTANCE * forward;
+
+  // intersect the view ray with the sphere, centered at the origin
+  float b = dot(eye, dir);
+  float h = b * b - dot(eye, eye) + RADIUS * RADIUS;
+
+  if (h < 0.) {
+    discard;
+  }
+
+  vec3 normal = (eye + (-b - sqrt(h)) * dir) / RADIUS;
+
+  frag = vec4(texture(env_map, reflect(dir, normal)).rgb * .9, 1.);
+}
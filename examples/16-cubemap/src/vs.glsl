@@ -0,0 +1,13 @@
+out vec2 v_pos;
+
+// a triangle covering the whole screen
+const vec2[3] POSITIONS = vec2[](
+  vec2(-1., -1.),
+  vec2( 3., -1.),
+  vec2(-1.,  3.)
+);
+
+void main() {
+  v_pos = POSITIONS[gl_VertexID];
+  gl_Position = vec4(v_pos, 0., 1.);
+}
@@ -0,0 +1,51 @@
+in vec2 v_pos;
+
+out vec4 frag;
+
+uniform sampler3D volume;
+uniform vec3 forward;
+uniform vec3 right;
+uniform vec3 up;
+uniform float aspect;
+uniform float time;
+
+const float DISTANCE = 3.;
+const int STEPS = 64;
+const vec3 BACKGROUND = vec3(.05, .05, .1);
+
+void main() {
+  vec3 dir = normalize(forward + v_pos.x * aspect * right + v_pos.y * up);
+  vec3 eye = -DISTANCE * forward;
+
+  // intersect the view ray with the [-1;1]³ box holding the volume
+  vec3 t0 = (-1. - eye) / dir;
+  vec3 t1 = (1. - eye) / dir;
+  vec3 t_min = min(t0, t1);
+  vec3 t_max = max(t0, t1);
+  float near = max(max(t_min.x, t_min.y), max(t_min.z, 0.));
+  float far = min(min(t_max.x, t_max.y), t_max.z);
+
+  if (near >= far) {
+    frag = vec4(BACKGROUND, 1.);
+    return;
+  }
+
+  // march along the ray, accumulating the light scattered by the volume
+  float step_len = (far - near) / float(STEPS);
+  float transmittance = 1.;
+  vec3 color = vec3(0.);
+
+  for (int i = 0; i < STEPS; ++i) {
+    vec3 p = eye + (near + (float(i) + .5) * step_len) * dir;
+
+    // the volume scrolls upwards; the repeat wrapping makes it seamless
+    float noise = texture(volume, p * .5 + .5 - vec3(0., time * .05, 0.)).r;
+    float density = max(noise - .4, 0.) * 4.;
+    float absorption = exp(-density * step_len);
+
+    color += transmittance * (1. - absorption) * mix(vec3(.9, .5, .2), vec3(1.), density);
+    transmittance *= absorption;
+  }
+
+  frag = vec4(color + transmittance * BACKGROUND, 1.);
+}
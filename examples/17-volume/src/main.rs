@@ -0,0 +1,129 @@
+//! This program shows how to use *3D textures* to render volumetric data. A noise volume is
+//! generated on the CPU and uploaded to a 3D texture, which is then *ray-marched* in the fragment
+//! shader: the density of the volume is accumulated along the view ray of every fragment.
+//!
+//! The volume is small, but trilinear filtering – the `Linear` filters of the sampler – smoothly
+//! interpolates between its texels, turning random values into *value noise*.
+//!
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::Framebuffer;
+use luminance::pipeline::BoundTexture;
+use luminance::pixel::R32F;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess};
+use luminance::texture::{Dim3, Flat, MagFilter, MinFilter, Sampler, Texture, Wrap};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+use std::time::Instant;
+
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+const VOLUME_SIZE: u32 = 16;
+
+uniform_interface! {
+  struct ShaderInterface {
+    #[unbound]
+    volume: &'static BoundTexture<'static, Flat, Dim3, R32F>,
+    forward: [f32; 3],
+    right: [f32; 3],
+    up: [f32; 3],
+    aspect: f32,
+    time: f32
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (program, _) = Program::<(), (), ShaderInterface>::from_strings(None, VS, None, FS).expect("program creation");
+
+  // a fullscreen triangle; the fragment shader casts a view ray per fragment
+  let fullscreen = Tess::attributeless(&mut surface, Mode::Triangle, 3);
+
+  // trilinear filtering, and a volume repeating along the three axes
+  let sampler = Sampler {
+    wrap_r: Wrap::Repeat,
+    wrap_s: Wrap::Repeat,
+    wrap_t: Wrap::Repeat,
+    min_filter: MinFilter::Linear,
+    mag_filter: MagFilter::Linear,
+    ..Sampler::default()
+  };
+  let size = [VOLUME_SIZE, VOLUME_SIZE, VOLUME_SIZE];
+  let volume = Texture::<Flat, Dim3, R32F>::new(&mut surface, size, 0, &sampler).expect("volume creation");
+
+  // the texels are laid out row after row, slice after slice
+  volume.upload(false, &random_texels((VOLUME_SIZE * VOLUME_SIZE * VOLUME_SIZE) as usize));
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+
+  let start = Instant::now();
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    let t = start.elapsed();
+    let t = t.as_secs() as f32 + t.subsec_nanos() as f32 * 1e-9;
+
+    // an orbiting camera looking at the volume, at the origin
+    let yaw = t * 0.2;
+    let forward = [yaw.sin(), 0., -yaw.cos()];
+    let right = [yaw.cos(), 0., yaw.sin()];
+    let up = [0., 1., 0.];
+    let aspect = back_buffer.width() as f32 / back_buffer.height() as f32;
+
+    surface.pipeline_builder().pipeline(&back_buffer, [0., 0., 0., 1.], |pipeline, shd_gate| {
+      let volume = pipeline.bind_texture(&volume);
+
+      shd_gate.shade(&program, |rdr_gate, iface| {
+        iface.volume.update(&volume);
+        iface.forward.update(forward);
+        iface.right.update(right);
+        iface.up.update(up);
+        iface.aspect.update(aspect);
+        iface.time.update(t);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, (&fullscreen).into());
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
+
+// Generate random values in [0;1] with a xorshift generator.
+fn random_texels(n: usize) -> Vec<f32> {
+  let mut state = 0x2545_f491u32;
+
+  (0..n).map(|_| {
+    state ^= state << 13;
+    state ^= state >> 17;
+    state ^= state << 5;
+
+    state as f32 / u32::max_value() as f32
+  }).collect()
+}
@@ -0,0 +1,32 @@
+in vec2 v_pos;
+
+out vec4 frag;
+
+const int LIGHT_NB = 8;
+
+// must match the layout of the Light type declared with uniform_block!
+struct Light {
+  vec2 position;
+  float radius;
+  vec4 color;
+};
+
+layout (std140) uniform Lights {
+  Light lights[LIGHT_NB];
+};
+
+uniform float aspect;
+
+void main() {
+  vec2 p = vec2(v_pos.x * aspect, v_pos.y);
+  vec3 color = vec3(0.);
+
+  for (int i = 0; i < LIGHT_NB; ++i) {
+    float d = distance(p, lights[i].position);
+    float attenuation = max(1. - d / lights[i].radius, 0.);
+
+    color += lights[i].color.rgb * attenuation * attenuation;
+  }
+
+  frag = vec4(color, 1.);
+}
@@ -0,0 +1,128 @@
+//! This program shows how to pass structured data to shaders with a *uniform buffer*. An array of
+//! lights is declared as a std140 uniform block, stored in a GPU buffer and bound to the shader
+//! program in a single call, instead of updating each light with its own uniforms. The lights are
+//! animated by rewriting the buffer every frame.
+//!
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::buffer::Buffer;
+use luminance::framebuffer::Framebuffer;
+use luminance::pipeline::BoundBuffer;
+use luminance::shader::program::Program;
+use luminance::std140::Std140Array;
+use luminance::tess::{Mode, Tess};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+use std::time::Instant;
+
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+// must match LIGHT_NB in the fragment shader
+const LIGHT_NB: usize = 8;
+
+// the layout of both types is checked at compile-time against the std140 rules; without the
+// padding field, color would sit at offset 12 instead of 16
+uniform_block! {
+  #[derive(Clone, Copy, Default)]
+  struct Light {
+    position: [f32; 2],
+    radius: f32,
+    _pad: f32,
+    color: [f32; 4]
+  }
+}
+
+uniform_block! {
+  #[derive(Clone, Copy, Default)]
+  struct Lights {
+    lights: Std140Array<Light, LIGHT_NB>
+  }
+}
+
+uniform_interface! {
+  struct ShaderInterface {
+    // uniform blocks are mapped by their block name
+    #[as("Lights")]
+    lights: &'static BoundBuffer<'static, Buffer<Lights>>,
+    aspect: f32
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (program, _) = Program::<(), (), ShaderInterface>::from_strings(None, VS, None, FS).expect("program creation");
+
+  let fullscreen = Tess::attributeless(&mut surface, Mode::Triangle, 3);
+
+  // a buffer holding a single uniform block
+  let lights_buffer = Buffer::from_slice(&mut surface, &[Lights::default()]);
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+
+  let start = Instant::now();
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    let t = start.elapsed();
+    let t = t.as_secs() as f32 + t.subsec_nanos() as f32 * 1e-9;
+
+    // make the lights dance around the center of the screen
+    let mut lights = Lights::default();
+
+    for i in 0..LIGHT_NB {
+      let k = i as f32 / LIGHT_NB as f32;
+      let angle = t * (0.3 + k) + k * 2. * std::f32::consts::PI;
+
+      lights.lights[i] = Light {
+        position: [angle.cos() * (0.3 + 0.5 * k), angle.sin() * 0.6],
+        radius: 0.8,
+        _pad: 0.,
+        color: [1. - k, 0.3 + 0.5 * k, k, 1.]
+      };
+    }
+
+    lights_buffer.write_whole(&[lights]).expect("lights update");
+
+    let aspect = back_buffer.width() as f32 / back_buffer.height() as f32;
+
+    surface.pipeline_builder().pipeline(&back_buffer, [0., 0., 0., 1.], |pipeline, shd_gate| {
+      // bind the buffer to a free uniform buffer binding index
+      let lights = pipeline.bind_uniform_buffer(&lights_buffer);
+
+      shd_gate.shade(&program, |rdr_gate, iface| {
+        // point the uniform block to the binding index of the buffer
+        iface.lights.update(&lights);
+        iface.aspect.update(aspect);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, (&fullscreen).into());
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
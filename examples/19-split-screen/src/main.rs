@@ -0,0 +1,87 @@
+//! This program shows how to render into parts of a framebuffer by changing the *viewport* of a
+//! pipeline. The same triangle is rendered into the left and the right halves of the screen – a
+//! split-screen render – the right one being rendered upside down.
+//!
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::framebuffer::{Framebuffer, Region};
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+type Vertex = ([f32; 2], [f32; 3]);
+
+const TRI_VERTICES: [Vertex; 3] = [
+  ([ 0.5, -0.5], [0., 1., 0.]),
+  ([ 0.0,  0.5], [0., 0., 1.]),
+  ([-0.5, -0.5], [1., 0., 0.]),
+];
+
+uniform_interface! {
+  struct ShaderInterface {
+    flip: f32
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (program, _) = Program::<Vertex, (), ShaderInterface>::from_strings(None, VS, None, FS).expect("program creation");
+
+  let triangle = Tess::new(&mut surface, Mode::Triangle, &TRI_VERTICES[..], None);
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    // each pane covers half of the back buffer
+    let half_width = back_buffer.width() / 2;
+    let height = back_buffer.height();
+    let panes = [
+      (Region::new(0, 0, half_width, height), 1.),
+      (Region::new(half_width, 0, half_width, height), -1.)
+    ];
+
+    surface.pipeline_builder().pipeline(&back_buffer, [0., 0., 0., 0.], |pipeline, shd_gate| {
+      for &(viewport, flip) in &panes {
+        // the viewport is restored to the whole back buffer when the closure returns
+        pipeline.with_viewport(viewport, || {
+          shd_gate.shade(&program, |rdr_gate, iface| {
+            iface.flip.update(flip);
+
+            rdr_gate.render(RenderState::default(), |tess_gate| {
+              tess_gate.render(&mut surface, (&triangle).into());
+            });
+          });
+        });
+      }
+    });
+
+    surface.swap_buffers();
+  }
+}
@@ -0,0 +1,22 @@
+in vec2 v_pos;
+
+out vec4 frag;
+
+uniform int layer;
+
+void main() {
+  vec3 color;
+
+  if (layer == 0) {
+    // vertical stripes
+    float k = step(0.5, fract(v_pos.x * 4.));
+    color = mix(vec3(0.9, 0.4, 0.1), vec3(0.1, 0.1, 0.2), k);
+  } else {
+    // concentric rings
+    float k = step(0.5, fract(length(v_pos) * 4.));
+    color = mix(vec3(0.1, 0.6, 0.9), vec3(0.9, 0.9, 0.8), k);
+  }
+
+  frag = vec4(color, 1.);
+  frag = pow(frag, vec4(1./2.2));
+}
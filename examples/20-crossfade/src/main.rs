@@ -0,0 +1,91 @@
+//! This program shows how to use the *blending constant*. Two layers – stripes and rings – are
+//! rendered on top of each other, the second one being blended with the constant alpha of the
+//! render state instead of its own alpha. Changing the constant every frame crossfades the layers
+//! without touching the shader.
+//!
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::blending::{Equation, Factor};
+use luminance::depth_test::DepthTest;
+use luminance::framebuffer::Framebuffer;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+use std::time::Instant;
+
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+uniform_interface! {
+  struct ShaderInterface {
+    layer: i32
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (program, _) = Program::<(), (), ShaderInterface>::from_strings(None, VS, None, FS).expect("program creation");
+
+  let fullscreen = Tess::attributeless(&mut surface, Mode::Triangle, 3);
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+
+  let start = Instant::now();
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    let t = start.elapsed();
+    let t = t.as_secs() as f32 + t.subsec_nanos() as f32 * 1e-9;
+
+    // the weight of the second layer, going back and forth between 0 and 1
+    let fade = (t.sin() + 1.) * 0.5;
+
+    // the second layer is blended with the constant alpha: fade * rings + (1 - fade) * stripes; the
+    // depth test would discard it, as both layers lie at the same depth
+    let fade_state = RenderState::default()
+      .set_depth_test(DepthTest::Disabled)
+      .set_blending((Equation::Additive, Factor::ConstantAlpha, Factor::ConstantAlphaComplement))
+      .set_blending_constant([0., 0., 0., fade]);
+
+    surface.pipeline_builder().pipeline(&back_buffer, [0., 0., 0., 1.], |_, shd_gate| {
+      shd_gate.shade(&program, |rdr_gate, iface| {
+        iface.layer.update(0);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, (&fullscreen).into());
+        });
+
+        iface.layer.update(1);
+
+        rdr_gate.render(fade_state, |tess_gate| {
+          tess_gate.render(&mut surface, (&fullscreen).into());
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
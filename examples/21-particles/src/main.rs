@@ -0,0 +1,127 @@
+//! This program shows how to use *transform feedback* to simulate particles entirely on the GPU.
+//! The particles – a position and a velocity – are stored in two transform feedback objects. Every
+//! frame, a vertex shader reads the particles of one of them, moves them and writes them to the
+//! other one, which is then rendered. The two objects swap their roles on the next frame.
+//!
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::blending::{Equation, Factor};
+use luminance::framebuffer::Framebuffer;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess};
+use luminance::transform_feedback::TransformFeedback;
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+use std::time::Instant;
+
+const UPDATE_VS: &'static str = include_str!("update_vs.glsl");
+const UPDATE_FS: &'static str = include_str!("update_fs.glsl");
+const RENDER_VS: &'static str = include_str!("render_vs.glsl");
+const RENDER_FS: &'static str = include_str!("render_fs.glsl");
+
+const PARTICLE_NB: usize = 20_000;
+
+// position and velocity
+type Particle = ([f32; 2], [f32; 2]);
+
+uniform_interface! {
+  struct UpdateInterface {
+    dt: f32,
+    time: f32
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  // the outputs of the update shader are captured in the order of the attributes of Particle
+  let (update_program, _) = Program::<Particle, (), UpdateInterface>::from_strings_with_feedback(
+    None,
+    UPDATE_VS,
+    None,
+    UPDATE_FS,
+    &["out_position", "out_velocity"]
+  ).expect("update program creation");
+  let (render_program, _) = Program::<Particle, (), ()>::from_strings(None, RENDER_VS, None, RENDER_FS).expect("render program creation");
+
+  // the particles start below the screen, so that they are shot by the fountain at once
+  let initial = Tess::new(&mut surface, Mode::Point, &vec![([0., -2.], [0., 0.]); PARTICLE_NB][..], None);
+
+  let feedbacks = [
+    TransformFeedback::<Particle>::new(&mut surface, PARTICLE_NB),
+    TransformFeedback::<Particle>::new(&mut surface, PARTICLE_NB)
+  ];
+  // index of the transform feedback object holding the latest particles; none before the first
+  // update, which reads the initial particles
+  let mut current: Option<usize> = None;
+
+  // overlapping particles add up
+  let render_state = RenderState::default()
+    .set_blending((Equation::Additive, Factor::One, Factor::One));
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+
+  let start = Instant::now();
+  let mut last_frame = start;
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+        }
+
+        _ => ()
+      }
+    }
+
+    let now = Instant::now();
+    let dt = now - last_frame;
+    let dt = (dt.as_secs() as f32 + dt.subsec_nanos() as f32 * 1e-9).min(0.1);
+    let t = now - start;
+    let t = t.as_secs() as f32 + t.subsec_nanos() as f32 * 1e-9;
+    last_frame = now;
+
+    let next = current.map_or(0, |c| 1 - c);
+
+    surface.pipeline_builder().pipeline(&back_buffer, [0., 0., 0., 1.], |_, shd_gate| {
+      // update the particles into the next transform feedback object
+      shd_gate.shade(&update_program, |rdr_gate, iface| {
+        iface.dt.update(dt);
+        iface.time.update(t);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          feedbacks[next].capture_only(Mode::Point, || {
+            match current {
+              Some(c) => tess_gate.render_transform_feedback(&mut surface, &feedbacks[c], Mode::Point),
+              None => tess_gate.render(&mut surface, (&initial).into())
+            }
+          });
+        });
+      });
+
+      // and render them
+      shd_gate.shade(&render_program, |rdr_gate, _| {
+        rdr_gate.render(render_state, |tess_gate| {
+          tess_gate.render_transform_feedback(&mut surface, &feedbacks[next], Mode::Point);
+        });
+      });
+    });
+
+    current = Some(next);
+
+    surface.swap_buffers();
+  }
+}
@@ -0,0 +1,11 @@
+in float v_speed;
+
+out vec4 frag;
+
+void main() {
+  // slow particles are red, fast ones yellow
+  vec3 color = mix(vec3(0.8, 0.1, 0.05), vec3(1., 0.85, 0.3), clamp(v_speed * 0.5, 0., 1.));
+
+  frag = vec4(color * 0.3, 1.);
+  frag = pow(frag, vec4(1./2.2));
+}
@@ -0,0 +1,9 @@
+layout (location = 0) in vec2 position;
+layout (location = 1) in vec2 velocity;
+
+out float v_speed;
+
+void main() {
+  gl_Position = vec4(position, 0., 1.);
+  v_speed = length(velocity);
+}
@@ -0,0 +1,6 @@
+out vec4 frag;
+
+// never run: the particles are not rasterized while being updated
+void main() {
+  frag = vec4(0.);
+}
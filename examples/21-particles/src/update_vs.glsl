@@ -0,0 +1,30 @@
+layout (location = 0) in vec2 position;
+layout (location = 1) in vec2 velocity;
+
+// captured by transform feedback
+out vec2 out_position;
+out vec2 out_velocity;
+
+uniform float dt;
+uniform float time;
+
+const vec2 GRAVITY = vec2(0., -1.5);
+
+float hash(float x) {
+  return fract(sin(x * 12.9898) * 43758.5453);
+}
+
+void main() {
+  vec2 v = velocity + GRAVITY * dt;
+  vec2 p = position + v * dt;
+
+  // particles falling off the bottom of the screen are shot again from the fountain
+  if (p.y < -1.) {
+    float seed = float(gl_VertexID) + time;
+    p = vec2(0., -0.9);
+    v = vec2((hash(seed) - 0.5) * 0.8, 1.6 + hash(seed + 1.) * 0.6);
+  }
+
+  out_position = p;
+  out_velocity = v;
+}
@@ -0,0 +1,158 @@
+//! This program shows how to drive a `Camera` with the mouse. The camera orbits around a cube:
+//! dragging with the left button rotates it around the cube and scrolling moves it closer or
+//! farther. The camera needs the `camera` feature of luminance.
+//!
+//! The cursor position, mouse button and scroll events are plain window events; the camera only
+//! takes angles and distances, so the program turns the cursor motion into rotation angles itself.
+//!
+//! Press <escape> to quit or close the window.
+//!
+//! https://docs.rs/luminance
+
+#[macro_use]
+extern crate luminance;
+extern crate luminance_glfw;
+
+use luminance::camera::Camera;
+use luminance::framebuffer::Framebuffer;
+use luminance::linear::M44;
+use luminance::shader::program::Program;
+use luminance::tess::{Mode, Tess};
+use luminance::render_state::RenderState;
+use luminance_glfw::event::{Action, Key, MouseButton, WindowEvent};
+use luminance_glfw::surface::{GlfwSurface, Surface, WindowDim, WindowOpt};
+use luminance::context::GraphicsContext;
+
+const VS: &'static str = include_str!("vs.glsl");
+const FS: &'static str = include_str!("fs.glsl");
+
+// radians per pixel of cursor motion
+const ROTATE_SPEED: f32 = 0.005;
+// distance per scroll step
+const ZOOM_SPEED: f32 = 0.5;
+const MIN_DISTANCE: f32 = 2.;
+const MAX_DISTANCE: f32 = 20.;
+
+// position and color
+type Vertex = ([f32; 3], [f32; 3]);
+
+uniform_interface! {
+  struct ShaderInterface {
+    view_projection: M44
+  }
+}
+
+fn main() {
+  let mut surface = GlfwSurface::new(WindowDim::Windowed(960, 540), "Hello, world!", WindowOpt::default()).expect("GLFW surface creation");
+
+  let (program, _) = Program::<Vertex, (), ShaderInterface>::from_strings(None, VS, None, FS).expect("program creation");
+
+  let (vertices, indices) = cube();
+  let cube = Tess::new(&mut surface, Mode::Triangle, &vertices[..], &indices[..]);
+
+  let mut back_buffer = Framebuffer::back_buffer(surface.size());
+
+  // the camera looks at the cube, at the origin, from a bit above
+  let mut distance = 5.;
+  let aspect = back_buffer.width() as f32 / back_buffer.height() as f32;
+  let mut camera = Camera::new([0., 0., distance], 60f32.to_radians(), aspect);
+  camera.rotate(0.6, -0.4);
+  orbit(&mut camera, distance);
+
+  let mut cursor = [0., 0.];
+  let mut dragging = false;
+
+  'app: loop {
+    for event in surface.poll_events() {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+          break 'app
+        }
+
+        WindowEvent::MouseButton(MouseButton::Button1, action, _) => {
+          dragging = action != Action::Release;
+        }
+
+        WindowEvent::CursorPos(x, y) => {
+          if dragging {
+            // dragging right turns the camera right, around the cube; dragging down looks down
+            let (dx, dy) = ((x - cursor[0]) as f32, (y - cursor[1]) as f32);
+            camera.rotate(-dx * ROTATE_SPEED, -dy * ROTATE_SPEED);
+            orbit(&mut camera, distance);
+          }
+
+          cursor = [x, y];
+        }
+
+        WindowEvent::Scroll(_, dy) => {
+          // moving along the view direction keeps the cube at the center
+          let new_distance = distance - dy as f32 * ZOOM_SPEED;
+          let new_distance = new_distance.max(MIN_DISTANCE).min(MAX_DISTANCE);
+          camera.move_local(0., 0., new_distance - distance);
+          distance = new_distance;
+        }
+
+        WindowEvent::FramebufferSize(width, height) => {
+          back_buffer = Framebuffer::back_buffer([width as u32, height as u32]);
+          camera.aspect = width as f32 / height as f32;
+        }
+
+        _ => ()
+      }
+    }
+
+    let view_projection = camera.view_projection();
+
+    surface.pipeline_builder().pipeline(&back_buffer, [0.1, 0.1, 0.12, 1.], |_, shd_gate| {
+      shd_gate.shade(&program, |rdr_gate, iface| {
+        iface.view_projection.update(view_projection);
+
+        rdr_gate.render(RenderState::default(), |tess_gate| {
+          tess_gate.render(&mut surface, (&cube).into());
+        });
+      });
+    });
+
+    surface.swap_buffers();
+  }
+}
+
+// Place the camera at `distance` from the origin, looking at it.
+fn orbit(camera: &mut Camera, distance: f32) {
+  let [fx, fy, fz] = camera.forward();
+  camera.position = [-fx * distance, -fy * distance, -fz * distance];
+}
+
+// A unit cube which faces have different colors.
+fn cube() -> (Vec<Vertex>, Vec<u16>) {
+  // normal, then the two axes spanning the face
+  let faces = [
+    ([ 1.,  0.,  0.], [0., 1., 0.], [0., 0., 1.], [0.9, 0.3, 0.3]),
+    ([-1.,  0.,  0.], [0., 0., 1.], [0., 1., 0.], [0.3, 0.9, 0.9]),
+    ([ 0.,  1.,  0.], [0., 0., 1.], [1., 0., 0.], [0.3, 0.9, 0.3]),
+    ([ 0., -1.,  0.], [1., 0., 0.], [0., 0., 1.], [0.9, 0.3, 0.9]),
+    ([ 0.,  0.,  1.], [1., 0., 0.], [0., 1., 0.], [0.3, 0.3, 0.9]),
+    ([ 0.,  0., -1.], [0., 1., 0.], [1., 0., 0.], [0.9, 0.9, 0.3]),
+  ];
+
+  let mut vertices = Vec::with_capacity(24);
+  let mut indices = Vec::with_capacity(36);
+
+  for &(n, u, v, color) in &faces {
+    let first = vertices.len() as u16;
+
+    for &(a, b) in &[(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)] {
+      let co = [
+        (n[0] + a * u[0] + b * v[0]) * 0.5,
+        (n[1] + a * u[1] + b * v[1]) * 0.5,
+        (n[2] + a * u[2] + b * v[2]) * 0.5,
+      ];
+
+      vertices.push((co, color));
+    }
+
+    indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+  }
+
+  (vertices, indices)
+}
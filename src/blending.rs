@@ -8,6 +8,10 @@
 //! details.
 //!
 //! The factors are encoded with `Factor`.
+//!
+//! Besides the pixels themselves, factors can refer to a *blending constant* – a color set with
+//! `RenderState::set_blending_constant`. That’s handy to fade a whole layer in or out without
+//! touching its shader, for instance.
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub(crate) enum BlendingState {
@@ -65,5 +69,23 @@ pub enum Factor {
   DstAlpha,
   /// `(1 - dstA) * color`
   DstAlphaComplement,
-  SrcAlphaSaturate
+  SrcAlphaSaturate,
+  /// `constant * color`
+  ConstantColor,
+  /// `(1 - constant) * color`
+  ConstantColorComplement,
+  /// `constantA * color`
+  ConstantAlpha,
+  /// `(1 - constantA) * color`
+  ConstantAlphaComplement
+}
+
+impl Factor {
+  // Whether the factor refers to the blending constant.
+  pub(crate) fn is_constant(self) -> bool {
+    matches!(
+      self,
+      Factor::ConstantColor | Factor::ConstantColorComplement | Factor::ConstantAlpha | Factor::ConstantAlphaComplement
+    )
+  }
 }
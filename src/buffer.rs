@@ -9,6 +9,15 @@
 //! let buffer: Buffer<f32> = Buffer::new(&mut ctx, 5);
 //! ```
 //!
+//! Buffers created with `new` are optimized for content rewritten about every time it’s used. If
+//! your content changes less often, pass a `BufferUsage` hint to `new_with_usage`.
+//!
+//! If you already have the values, use `from_slice` to upload them along with the allocation:
+//!
+//! ```ignore
+//! let buffer = Buffer::from_slice(&mut ctx, &[1., 2., 3., 4., 5.]);
+//! ```
+//!
 //! Once the buffer is created, you can perform several operations on them:
 //!
 //! - Writing to them.
@@ -40,37 +49,92 @@
 //! buffer.set(3, 3.14);
 //! ```
 //!
+//! To change several contiguous values at once, use `write_slice_at`:
+//!
+//! ```ignore
+//! buffer.write_slice_at(1, &[9., 9.]);
+//! ```
+//!
 //! # Reading from the buffer
 //!
 //! You can either retrieve the `whole` content of the `Buffer` or `get` a value with an index.
 //!
 //! ```ignore
 //! // get the whole content
-//! let all_elems = buffer.whole();
+//! let all_elems = buffer.whole().unwrap();
 //! assert_eq!(all_elems, vec![1., 2., 3., 3.14, 5.]); // admit floating equalities
 //!
 //! // get the element at index 3
 //! assert_eq!(buffer.at(3), Some(3.14));
 //! ```
 //!
+//! Both functions copy the data out of the buffer and hence require `T: Copy`. If you only need
+//! to read the data, you can map the buffer with `as_slice` and iterate over references into the
+//! GPU memory instead. The type of the elements must then implement `Pod`.
+//!
+//! ```ignore
+//! let slice = buffer.as_slice().unwrap();
+//!
+//! for x in &slice {
+//!   // x is a &T living in GPU memory
+//! }
+//! ```
+//!
+//! # Immutable buffers
+//!
+//! If the content of a buffer never changes – static meshes, for instance – you can create it
+//! with `new_immutable`, passing the values and the operations the buffer must allow after
+//! creation. Operations not allowed by the `StorageFlags` fail with
+//! `BufferError::ImmutableStorage`.
+//!
+//! ```ignore
+//! let buffer = Buffer::new_immutable(&mut ctx, &[1., 2., 3.], StorageFlags::default());
+//! assert!(buffer.fill(&[4., 5., 6.]).is_err());
+//! ```
+//!
+//! # Growable buffers
+//!
+//! `Buffer`s have a static size. If you need to build up a buffer incrementally – per-frame
+//! debug geometry, for instance – you can use a `GrowableBuffer`, which behaves like a `Vec`: it
+//! has a length and a capacity, and doubles its capacity when you push past it.
+//!
+//! ```ignore
+//! let mut buffer = GrowableBuffer::new(&mut ctx, 16);
+//!
+//! buffer.push(1.);
+//! buffer.extend(&[2., 3.]);
+//! assert_eq!(buffer.len(), 3);
+//!
+//! buffer.clear(); // keeps the GPU memory around
+//! ```
+//!
 //! # Uniform buffer
 //!
 //! It’s possible to use buffers as *uniform buffers*. That is, buffers that will be in bound at
 //! rendering time and which content will be available for a shader to read (no write).
 //!
 //! In order to use your buffers in a uniform context, the inner type has to implement
-//! `UniformBlock`. Keep in mind alignment must be respected and is a bit peculiar. TODO: explain
-//! std140 here.
+//! `UniformBlock`. Keep in mind alignment must be respected and is a bit peculiar: uniform blocks
+//! follow the *std140* layout, in which – among other things – `vec3` are aligned to 16 bytes and
+//! array elements have a 16-byte stride. Declare your types with the `uniform_block!` macro to get
+//! that layout checked at compile-time; the rules are detailed in the `std140` module.
+//!
+//! # Shader storage buffer
+//!
+//! Buffers can also be bound as *shader storage buffers* with `Pipeline::bind_storage_buffer`.
+//! Shaders can both read and write them, and they can be far larger than uniform buffers. They
+//! typically hold per-object data arrays – declared as `buffer Objects { Object objects[]; };` –
+//! or the data processed by compute-like passes.
 
 use gl;
 use gl::types::*;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
 use std::error::Error;
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Index, IndexMut, Range};
 use std::os::raw::c_void;
 use std::ptr;
 use std::rc::Rc;
@@ -78,15 +142,18 @@ use std::slice;
 use std::vec::Vec;
 
 use context::GraphicsContext;
+use gtup::GTup;
 use linear::{M22, M33, M44};
-use state::GraphicsState;
+use state::{GraphicsState, gl_version};
 
 /// Buffer errors.
 #[derive(Debug, Eq, PartialEq)]
 pub enum BufferError {
-  /// Overflow when setting a value with a specific index.
+  /// Overflow when setting a value with a specific index, or when copying a range of bytes past
+  /// the end of a buffer.
   ///
-  /// Contains the index and the size of the buffer.
+  /// Contains the index and the size of the buffer – or, for copies, the end of the range and the
+  /// size of the buffer, in bytes.
   Overflow(usize, usize),
   /// Too few values were passed to fill a buffer.
   ///
@@ -97,7 +164,25 @@ pub enum BufferError {
   /// Contains the number of passed value and the size of the buffer.
   TooManyValues(usize, usize),
   /// Mapping the buffer failed.
-  MapFailed
+  MapFailed,
+  /// A range of the buffer goes past its end.
+  ///
+  /// Contains the end of the range and the size of the buffer, in bytes.
+  RangeOverflow(usize, usize),
+  /// The offset of a range of the buffer is not correctly aligned.
+  ///
+  /// Contains the offset and the required alignment, in bytes.
+  UnalignedRange(usize, usize),
+  /// A buffer or a range of a buffer is too large to be bound.
+  ///
+  /// Contains the size of the range and the maximum allowed size, in bytes.
+  RangeTooLarge(usize, usize),
+  /// The storage flags of an immutable buffer don’t allow the operation.
+  ///
+  /// Contains the flags the buffer was created with.
+  ImmutableStorage(StorageFlags),
+  /// The buffer is already mapped by a slice.
+  AlreadyMapped
 }
 
 impl Error for BufferError {}
@@ -120,10 +205,89 @@ impl fmt::Display for BufferError {
       BufferError::MapFailed => {
         write!(f, "buffer mapping failed")
       }
+
+      BufferError::RangeOverflow(end, bytes) => {
+        write!(f, "buffer range overflow (end = {}, bytes = {})", end, bytes)
+      }
+
+      BufferError::UnalignedRange(offset, alignment) => {
+        write!(f, "unaligned buffer range (offset = {}, alignment = {})", offset, alignment)
+      }
+
+      BufferError::RangeTooLarge(size, max) => {
+        write!(f, "buffer range too large (size = {}, max = {})", size, max)
+      }
+
+      BufferError::ImmutableStorage(ref flags) => {
+        write!(f, "operation not allowed by the buffer storage ({:?})", flags)
+      }
+
+      BufferError::AlreadyMapped => {
+        write!(f, "buffer already mapped")
+      }
     }
   }
 }
 
+/// Hint about how often the content of a buffer changes.
+///
+/// Drivers use it to decide where to store the buffer; it doesn’t restrict the operations you can
+/// perform on it.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BufferUsage {
+  /// The content is written once – with `fill` or `write_whole` right after creation – and used
+  /// many times. Use it for static meshes, for instance.
+  Static,
+  /// The content is updated from time to time – with `set`, `fill` or `write_whole` – and used
+  /// many times between updates.
+  Dynamic,
+  /// The content is rewritten – with `fill` or `write_whole` – about every time it’s used, like
+  /// per-frame data.
+  Stream
+}
+
+fn opengl_usage(usage: BufferUsage) -> GLenum {
+  match usage {
+    BufferUsage::Static => gl::STATIC_DRAW,
+    BufferUsage::Dynamic => gl::DYNAMIC_DRAW,
+    BufferUsage::Stream => gl::STREAM_DRAW
+  }
+}
+
+/// Storage flags of an immutable buffer.
+///
+/// Every flag is disabled by default, which gives a buffer which content cannot be changed nor read
+/// back after creation.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct StorageFlags {
+  /// The content of the buffer can be updated after creation.
+  pub dynamic: bool,
+  /// The buffer can be mapped for reading.
+  pub map_read: bool,
+  /// The buffer can be mapped for writing.
+  pub map_write: bool
+}
+
+impl StorageFlags {
+  fn to_bits(self) -> GLbitfield {
+    let mut bits = 0;
+
+    if self.dynamic {
+      bits |= gl::DYNAMIC_STORAGE_BIT;
+    }
+
+    if self.map_read {
+      bits |= gl::MAP_READ_BIT;
+    }
+
+    if self.map_write {
+      bits |= gl::MAP_WRITE_BIT;
+    }
+
+    bits
+  }
+}
+
 /// A `Buffer` is a GPU region you can picture as an array. It has a static size and cannot be
 /// resized. The size is expressed in number of elements lying in the buffer – not in bytes.
 pub struct Buffer<T> {
@@ -133,14 +297,21 @@ pub struct Buffer<T> {
 
 impl<T> Buffer<T> {
   /// Create a new `Buffer` with a given number of elements.
+  ///
+  /// The buffer is created with the `BufferUsage::Stream` usage hint.
   pub fn new<C>(ctx: &mut C, len: usize) -> Buffer<T> where C: GraphicsContext {
+    Self::new_with_usage(ctx, len, BufferUsage::Stream)
+  }
+
+  /// Create a new `Buffer` with a given number of elements and a usage hint.
+  pub fn new_with_usage<C>(ctx: &mut C, len: usize, usage: BufferUsage) -> Buffer<T> where C: GraphicsContext {
     let mut buffer: GLuint = 0;
     let bytes = mem::size_of::<T>() * len;
 
     unsafe {
       gl::GenBuffers(1, &mut buffer);
       ctx.state().borrow_mut().bind_array_buffer(buffer);
-      gl::BufferData(gl::ARRAY_BUFFER, bytes as isize, ptr::null(), gl::STREAM_DRAW);
+      gl::BufferData(gl::ARRAY_BUFFER, bytes as isize, ptr::null(), opengl_usage(usage));
     }
 
     Buffer {
@@ -148,6 +319,77 @@ impl<T> Buffer<T> {
         handle: buffer,
         bytes: bytes,
         len: len,
+        storage: None,
+        mapped: Cell::new(false),
+        state: ctx.state().clone(),
+      },
+      _t: PhantomData
+    }
+  }
+
+  /// Create a new `Buffer` holding the given values.
+  ///
+  /// The values are uploaded along with the allocation of the buffer, which is cheaper than
+  /// calling `new` and then `fill`.
+  pub fn from_slice<C>(ctx: &mut C, values: &[T]) -> Buffer<T> where C: GraphicsContext {
+    let mut buffer: GLuint = 0;
+    let len = values.len();
+    let bytes = mem::size_of_val(values);
+    // don’t hand a dangling pointer to the driver for empty slices
+    let data = if len == 0 { ptr::null() } else { values.as_ptr() as *const c_void };
+
+    unsafe {
+      gl::GenBuffers(1, &mut buffer);
+      ctx.state().borrow_mut().bind_array_buffer(buffer);
+      gl::BufferData(gl::ARRAY_BUFFER, bytes as isize, data, gl::STREAM_DRAW);
+    }
+
+    Buffer {
+      raw: RawBuffer {
+        handle: buffer,
+        bytes,
+        len,
+        storage: None,
+        mapped: Cell::new(false),
+        state: ctx.state().clone(),
+      },
+      _t: PhantomData
+    }
+  }
+
+  /// Create a new immutable `Buffer` holding the given values.
+  ///
+  /// The buffer cannot be reallocated and only allows the operations enabled in `flags`. This
+  /// gives the driver more freedom to place the buffer optimally, which is interesting for static
+  /// data.
+  ///
+  /// > Note: immutable storage requires OpenGL 4.4. On older implementations, the buffer is
+  /// > allocated as a regular buffer, but still only allows the operations enabled in `flags`.
+  pub fn new_immutable<C>(ctx: &mut C, values: &[T], flags: StorageFlags) -> Buffer<T> where C: GraphicsContext {
+    let mut buffer: GLuint = 0;
+    let len = values.len();
+    let bytes = mem::size_of_val(values);
+    let data = if len == 0 { ptr::null() } else { values.as_ptr() as *const c_void };
+
+    unsafe {
+      gl::GenBuffers(1, &mut buffer);
+      ctx.state().borrow_mut().bind_array_buffer(buffer);
+
+      if gl_version() >= (4, 4) {
+        gl::BufferStorage(gl::ARRAY_BUFFER, bytes as isize, data, flags.to_bits());
+      } else {
+        let usage = if flags.dynamic { gl::DYNAMIC_DRAW } else { gl::STATIC_DRAW };
+        gl::BufferData(gl::ARRAY_BUFFER, bytes as isize, data, usage);
+      }
+    }
+
+    Buffer {
+      raw: RawBuffer {
+        handle: buffer,
+        bytes,
+        len,
+        storage: Some(flags),
+        mapped: Cell::new(false),
         state: ctx.state().clone(),
       },
       _t: PhantomData
@@ -162,9 +404,10 @@ impl<T> Buffer<T> {
 
   /// Retrieve an element from the `Buffer`.
   ///
-  /// Checks boundaries.
+  /// Checks boundaries. If the buffer is immutable and cannot be mapped for reading, or if it’s
+  /// already mapped by a slice, `None` is returned.
   pub fn at(&self, i: usize) -> Option<T> where T: Copy {
-    if i >= self.len {
+    if i >= self.len || !self.raw.can_map(gl::READ_ONLY) || self.raw.mapped.get() {
       return None;
     }
 
@@ -181,17 +424,12 @@ impl<T> Buffer<T> {
   }
 
   /// Retrieve the whole content of the `Buffer`.
-  pub fn whole(&self) -> Vec<T> where T: Copy {
-    unsafe {
-      self.raw.state.borrow_mut().bind_array_buffer(self.handle);
-      let ptr = gl::MapBuffer(gl::ARRAY_BUFFER, gl::READ_ONLY) as *mut T;
-
-      let values = Vec::from_raw_parts(ptr, self.len, self.len);
-
-      let _ = gl::UnmapBuffer(gl::ARRAY_BUFFER);
-
-      values
-    }
+  ///
+  /// If the buffer is immutable and cannot be mapped for reading, you’ll get a
+  /// `BufferError::ImmutableStorage` error. If it’s already mapped by a slice, you’ll get
+  /// `BufferError::AlreadyMapped`, and if mapping it fails, `BufferError::MapFailed`.
+  pub fn whole(&self) -> Result<Vec<T>, BufferError> where T: Copy {
+    self.raw.read(0, self.len)
   }
 
   /// Set a value at a given index in the `Buffer`.
@@ -202,6 +440,9 @@ impl<T> Buffer<T> {
       return Err(BufferError::Overflow(i, self.len));
     }
 
+    self.raw.check_map(gl::WRITE_ONLY)?;
+    self.raw.check_unmapped()?;
+
     unsafe {
       self.raw.state.borrow_mut().bind_array_buffer(self.handle);
       let ptr = gl::MapBuffer(gl::ARRAY_BUFFER, gl::WRITE_ONLY) as *mut T;
@@ -219,6 +460,12 @@ impl<T> Buffer<T> {
   /// If the slice you pass in has less items than the length of the buffer, you’ll get a
   /// `BufferError::TooFewValues` error. If it has more, you’ll get `BufferError::TooManyValues`.
   ///
+  /// If the buffer is immutable and was created neither dynamic nor mappable for writing, you’ll
+  /// get a `BufferError::ImmutableStorage` error.
+  ///
+  /// The values are uploaded without mapping the buffer, unless it is immutable and only mappable
+  /// for writing.
+  ///
   /// This function won’t write anything on any error.
   pub fn write_whole(&self, values: &[T]) -> Result<(), BufferError> {
     let len = values.len();
@@ -231,13 +478,115 @@ impl<T> Buffer<T> {
       _ => in_bytes
     };
 
+    self.raw.check_unmapped()?;
+
+    // immutable buffers which are not dynamic can only be updated by mapping them
+    if let Some(flags) = self.raw.storage {
+      if !flags.dynamic {
+        if !flags.map_write {
+          return Err(BufferError::ImmutableStorage(flags));
+        }
+
+        unsafe {
+          self.raw.state.borrow_mut().bind_array_buffer(self.handle);
+          let ptr = gl::MapBuffer(gl::ARRAY_BUFFER, gl::WRITE_ONLY);
+
+          ptr::copy_nonoverlapping(values.as_ptr() as *const c_void, ptr, real_bytes);
+
+          let _ = gl::UnmapBuffer(gl::ARRAY_BUFFER);
+        }
+
+        return Ok(());
+      }
+    }
+
     unsafe {
       self.raw.state.borrow_mut().bind_array_buffer(self.handle);
-      let ptr = gl::MapBuffer(gl::ARRAY_BUFFER, gl::WRITE_ONLY);
+      gl::BufferSubData(gl::ARRAY_BUFFER, 0, real_bytes as isize, values.as_ptr() as *const c_void);
+    }
 
-      ptr::copy_nonoverlapping(values.as_ptr() as *const c_void, ptr, real_bytes);
+    Ok(())
+  }
 
-      let _ = gl::UnmapBuffer(gl::ARRAY_BUFFER);
+  /// Write a slice into the buffer, starting at the element at index `offset`.
+  ///
+  /// If the slice doesn’t fit in the buffer, you’ll get a `BufferError::Overflow` error holding
+  /// the end of the range – saturated to `usize::MAX` – and the length of the buffer. If the
+  /// buffer is immutable and was not created dynamic, you’ll get a `BufferError::ImmutableStorage`
+  /// error.
+  ///
+  /// This function won’t write anything on any error.
+  pub fn write_slice_at(&self, offset: usize, values: &[T]) -> Result<(), BufferError> {
+    // a range ending past usize::MAX ends past the buffer as well
+    let end = offset.saturating_add(values.len());
+
+    if end > self.len {
+      return Err(BufferError::Overflow(end, self.len));
+    }
+
+    if let Some(flags) = self.raw.storage {
+      if !flags.dynamic {
+        return Err(BufferError::ImmutableStorage(flags));
+      }
+    }
+
+    self.raw.check_unmapped()?;
+
+    unsafe {
+      self.raw.state.borrow_mut().bind_array_buffer(self.handle);
+      gl::BufferSubData(
+        gl::ARRAY_BUFFER,
+        (offset * mem::size_of::<T>()) as isize,
+        mem::size_of_val(values) as isize,
+        values.as_ptr() as *const c_void
+      );
+    }
+
+    Ok(())
+  }
+
+  /// Copy a range of bytes from another buffer into this buffer, without any CPU round-trip.
+  ///
+  /// `len` bytes are copied from `src`, starting at `src_offset`, into this buffer, starting at
+  /// `dst_offset`. All offsets and lengths are expressed in bytes. If either range goes past the end
+  /// of its buffer, you’ll get a `BufferError::Overflow` error – holding the end of the range,
+  /// saturated to `usize::MAX`, and the size of the buffer, in bytes – and nothing is copied.
+  ///
+  /// > Note: the ranges must not overlap if `src` is this buffer.
+  pub fn copy_from(
+    &mut self,
+    src: &RawBuffer,
+    src_offset: usize,
+    dst_offset: usize,
+    len: usize
+  ) -> Result<(), BufferError> {
+    // ranges ending past usize::MAX end past the buffers as well
+    let src_end = src_offset.saturating_add(len);
+    let dst_end = dst_offset.saturating_add(len);
+
+    if src_end > src.bytes {
+      return Err(BufferError::Overflow(src_end, src.bytes));
+    }
+
+    if dst_end > self.bytes {
+      return Err(BufferError::Overflow(dst_end, self.bytes));
+    }
+
+    src.check_unmapped()?;
+    self.raw.check_unmapped()?;
+
+    unsafe {
+      // the copy binding points are only used for copies, which always bind both buffers; they
+      // don’t need to go through the state cache
+      gl::BindBuffer(gl::COPY_READ_BUFFER, src.handle);
+      gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.handle);
+      gl::CopyBufferSubData(
+        gl::COPY_READ_BUFFER,
+        gl::COPY_WRITE_BUFFER,
+        src_offset as GLintptr,
+        dst_offset as GLintptr,
+        len as GLsizeiptr
+      );
     }
 
     Ok(())
@@ -261,6 +610,8 @@ impl<T> Buffer<T> {
       handle: self.raw.handle,
       bytes: self.raw.bytes,
       len: self.raw.len,
+      storage: self.raw.storage,
+      mapped: Cell::new(self.raw.mapped.get()),
       state: self.raw.state.clone()
     };
 
@@ -270,12 +621,14 @@ impl<T> Buffer<T> {
   }
 
   /// Obtain an immutable slice view into the buffer.
-  pub fn as_slice(&self) -> Result<BufferSlice<T>, BufferError> {
+  ///
+  /// No copy is performed, so `T` doesn’t have to be `Copy`.
+  pub fn as_slice(&self) -> Result<BufferSlice<T>, BufferError> where T: Pod {
     self.raw.as_slice()
   }
 
   /// Obtain a mutable slice view into the buffer.
-  pub fn as_slice_mut(&mut self) -> Result<BufferSliceMut<T>, BufferError> {
+  pub fn as_slice_mut(&mut self) -> Result<BufferSliceMut<T>, BufferError> where T: Pod {
     self.raw.as_slice_mut()
   }
 }
@@ -300,12 +653,17 @@ pub struct RawBuffer {
   handle: GLuint,
   bytes: usize,
   len: usize,
+  storage: Option<StorageFlags>, // none means mutable storage
+  mapped: Cell<bool>, // whether a slice currently maps the buffer
   state: Rc<RefCell<GraphicsState>>
 }
 
 impl RawBuffer {
   /// Obtain an immutable slice view into the buffer.
-  pub fn as_slice<T>(&self) -> Result<BufferSlice<T>, BufferError> {
+  pub fn as_slice<T>(&self) -> Result<BufferSlice<T>, BufferError> where T: Pod {
+    self.check_map(gl::READ_ONLY)?;
+    self.check_unmapped()?;
+
     unsafe {
       self.state.borrow_mut().bind_array_buffer(self.handle);
 
@@ -315,6 +673,8 @@ impl RawBuffer {
         return Err(BufferError::MapFailed);
       }
 
+      self.mapped.set(true);
+
       Ok(BufferSlice {
         raw: self,
         ptr
@@ -323,7 +683,10 @@ impl RawBuffer {
   }
 
   /// Obtain a mutable slice view into the buffer.
-  pub fn as_slice_mut<T>(&mut self) -> Result<BufferSliceMut<T>, BufferError> {
+  pub fn as_slice_mut<T>(&mut self) -> Result<BufferSliceMut<T>, BufferError> where T: Pod {
+    self.check_map(gl::READ_WRITE)?;
+    self.check_unmapped()?;
+
     unsafe {
       self.state.borrow_mut().bind_array_buffer(self.handle);
 
@@ -333,6 +696,8 @@ impl RawBuffer {
         return Err(BufferError::MapFailed);
       }
 
+      self.mapped.set(true);
+
       Ok(BufferSliceMut {
         raw: self,
         ptr
@@ -340,10 +705,118 @@ impl RawBuffer {
     }
   }
 
+  // Copy `nb` values starting at index `start` out of the buffer.
+  pub(crate) fn read<T>(&self, start: usize, nb: usize) -> Result<Vec<T>, BufferError> where T: Copy {
+    let end = start + nb;
+
+    if end > self.len {
+      return Err(BufferError::Overflow(end, self.len));
+    }
+
+    self.check_map(gl::READ_ONLY)?;
+    self.check_unmapped()?;
+
+    if nb == 0 {
+      return Ok(Vec::new());
+    }
+
+    unsafe {
+      let size = mem::size_of::<T>();
+
+      self.state.borrow_mut().bind_array_buffer(self.handle);
+      let ptr = gl::MapBufferRange(
+        gl::ARRAY_BUFFER,
+        (start * size) as GLintptr,
+        (nb * size) as GLsizeiptr,
+        gl::MAP_READ_BIT
+      ) as *const T;
+
+      if ptr.is_null() {
+        return Err(BufferError::MapFailed);
+      }
+
+      // copy out of the mapped memory, which is owned by OpenGL and invalidated once unmapped
+      let mut values = Vec::with_capacity(nb);
+      ptr::copy_nonoverlapping(ptr, values.as_mut_ptr(), nb);
+      values.set_len(nb);
+
+      let _ = gl::UnmapBuffer(gl::ARRAY_BUFFER);
+
+      Ok(values)
+    }
+  }
+
+  // Replace the content of a mutable buffer with `values`, which must fit in it, without mapping it.
+  //
+  // The storage is orphaned first: the driver hands out fresh memory instead of waiting for the
+  // draws still reading the former content.
+  pub(crate) fn stream<T>(&self, values: &[T]) {
+    unsafe {
+      self.state.borrow_mut().bind_array_buffer(self.handle);
+      gl::BufferData(gl::ARRAY_BUFFER, self.bytes as isize, ptr::null(), gl::STREAM_DRAW);
+      gl::BufferSubData(
+        gl::ARRAY_BUFFER,
+        0,
+        mem::size_of_val(values) as GLsizeiptr,
+        values.as_ptr() as *const c_void
+      );
+    }
+  }
+
+  /// Storage flags of the buffer, if it’s immutable.
+  pub fn storage(&self) -> Option<StorageFlags> {
+    self.storage
+  }
+
+  // Check whether the buffer can be mapped with the given access.
+  fn can_map(&self, access: GLenum) -> bool {
+    match self.storage {
+      Some(flags) => match access {
+        gl::READ_ONLY => flags.map_read,
+        gl::WRITE_ONLY => flags.map_write,
+        _ => flags.map_read && flags.map_write
+      },
+
+      None => true
+    }
+  }
+
+  // Same as can_map, but fails with a BufferError::ImmutableStorage error.
+  fn check_map(&self, access: GLenum) -> Result<(), BufferError> {
+    match self.storage {
+      Some(flags) if !self.can_map(access) => Err(BufferError::ImmutableStorage(flags)),
+      _ => Ok(())
+    }
+  }
+
+  // Fail with a BufferError::AlreadyMapped error if a slice currently maps the buffer.
+  pub(crate) fn check_unmapped(&self) -> Result<(), BufferError> {
+    if self.mapped.get() {
+      Err(BufferError::AlreadyMapped)
+    } else {
+      Ok(())
+    }
+  }
+
   // Get the underlying GPU handle.
   pub(crate) fn handle(&self) -> GLuint {
     self.handle
   }
+
+  // Get the number of elements in the buffer.
+  pub(crate) fn len(&self) -> usize {
+    self.len
+  }
+
+  // Get the size of the buffer, in bytes.
+  pub(crate) fn bytes(&self) -> usize {
+    self.bytes
+  }
+
+  // Get the graphics state the buffer was created with.
+  pub(crate) fn state(&self) -> &Rc<RefCell<GraphicsState>> {
+    &self.state
+  }
 }
 
 impl Drop for RawBuffer {
@@ -358,6 +831,193 @@ impl<T> From<Buffer<T>> for RawBuffer {
   }
 }
 
+/// A buffer which length can grow.
+///
+/// The buffer keeps track of its length – the number of elements pushed into it – separately from
+/// its capacity – the number of elements the GPU memory can hold. When pushing elements past the
+/// capacity, the GPU memory is reallocated with at least twice the capacity and the elements are
+/// copied over, so that reallocations are amortized.
+pub struct GrowableBuffer<T> {
+  buffer: Buffer<T>,
+  len: usize
+}
+
+impl<T> GrowableBuffer<T> {
+  /// Create a new, empty buffer able to hold `capacity` elements before growing.
+  pub fn new<C>(ctx: &mut C, capacity: usize) -> Self where C: GraphicsContext {
+    GrowableBuffer {
+      buffer: Buffer::new(ctx, capacity.max(1)),
+      len: 0
+    }
+  }
+
+  /// Number of elements in the buffer.
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Whether the buffer is empty.
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Number of elements the buffer can hold without growing.
+  #[inline(always)]
+  pub fn capacity(&self) -> usize {
+    self.buffer.len
+  }
+
+  /// Push an element at the end of the buffer.
+  pub fn push(&mut self, x: T) where T: Copy {
+    self.extend(&[x]);
+  }
+
+  /// Push several elements at the end of the buffer.
+  pub fn extend(&mut self, values: &[T]) {
+    let new_len = self.len + values.len();
+
+    if new_len > self.capacity() {
+      self.grow(new_len);
+    }
+
+    let size = mem::size_of::<T>();
+
+    unsafe {
+      self.buffer.raw.state.borrow_mut().bind_array_buffer(self.buffer.handle);
+      gl::BufferSubData(
+        gl::ARRAY_BUFFER,
+        (self.len * size) as GLintptr,
+        mem::size_of_val(values) as GLsizeiptr,
+        values.as_ptr() as *const c_void
+      );
+    }
+
+    self.len = new_len;
+  }
+
+  /// Remove all the elements from the buffer.
+  ///
+  /// The GPU memory is not freed, so the capacity remains the same.
+  pub fn clear(&mut self) {
+    self.len = 0;
+  }
+
+  // Get the underlying raw buffer.
+  //
+  // The raw buffer changes when the buffer grows.
+  pub(crate) fn raw(&self) -> &RawBuffer {
+    &self.buffer.raw
+  }
+
+  // Reallocate the GPU memory so that it can hold at least `min_capacity` elements.
+  fn grow(&mut self, min_capacity: usize) {
+    let capacity = min_capacity.max(self.capacity() * 2);
+    let bytes = mem::size_of::<T>() * capacity;
+    let state = self.buffer.raw.state.clone();
+    let mut handle: GLuint = 0;
+
+    unsafe {
+      gl::GenBuffers(1, &mut handle);
+      state.borrow_mut().bind_array_buffer(handle);
+      gl::BufferData(gl::ARRAY_BUFFER, bytes as isize, ptr::null(), gl::STREAM_DRAW);
+
+      // copy the current elements over
+      gl::BindBuffer(gl::COPY_READ_BUFFER, self.buffer.handle);
+      gl::CopyBufferSubData(
+        gl::COPY_READ_BUFFER,
+        gl::ARRAY_BUFFER,
+        0,
+        0,
+        (mem::size_of::<T>() * self.len) as GLsizeiptr
+      );
+    }
+
+    // the former GPU memory is freed when the former buffer is dropped
+    self.buffer = Buffer {
+      raw: RawBuffer {
+        handle,
+        bytes,
+        len: capacity,
+        storage: None,
+        mapped: Cell::new(false),
+        state
+      },
+      _t: PhantomData
+    };
+  }
+}
+
+/// An atomic counter living in GPU memory.
+///
+/// Shaders can increment and decrement atomic counters (`atomicCounterIncrement` and
+/// `atomicCounterDecrement` in GLSL). Use `AtomicCounter::bind` to bind the counter to the binding
+/// point declared in the shader – i.e. `layout (binding = 0) uniform atomic_uint counter;`.
+///
+/// The storage of the counter is laid out as an indirect draw command, the counter being its
+/// instance count. That makes it possible to use the value of the counter as the number of
+/// instances to render without reading it back on the CPU. See `TessSlice::render_counted`.
+pub struct AtomicCounter {
+  buffer: Buffer<u32>
+}
+
+impl AtomicCounter {
+  /// Create a new atomic counter, initialized to `0`.
+  pub fn new<C>(ctx: &mut C) -> Self where C: GraphicsContext {
+    let buffer = Buffer::new(ctx, 5);
+    buffer.clear(0).unwrap();
+
+    AtomicCounter { buffer }
+  }
+
+  /// Reset the counter to `0`.
+  pub fn reset(&self) {
+    unsafe { self.write_u32s(1, &[0]) }
+  }
+
+  /// Read back the current value of the counter.
+  ///
+  /// > Note: this forces a synchronization with the GPU.
+  pub fn value(&self) -> u32 {
+    self.buffer.at(1).unwrap()
+  }
+
+  /// Bind the counter to the given atomic counter binding point.
+  pub fn bind(&self, binding: u32) {
+    let size = mem::size_of::<u32>() as GLsizeiptr;
+
+    unsafe {
+      gl::BindBufferRange(gl::ATOMIC_COUNTER_BUFFER, binding as GLuint, self.buffer.handle, size as GLintptr, size);
+    }
+  }
+
+  // Set the vertex range of the indirect draw command the counter is stored in.
+  pub(crate) fn set_draw_range(&self, start_index: u32, vert_nb: u32) {
+    unsafe {
+      self.write_u32s(0, &[vert_nb]);
+      self.write_u32s(2, &[start_index, 0, 0]);
+    }
+  }
+
+  // Get the underlying GPU handle.
+  pub(crate) fn handle(&self) -> GLuint {
+    self.buffer.handle
+  }
+
+  unsafe fn write_u32s(&self, offset: usize, values: &[u32]) {
+    let size = mem::size_of::<u32>();
+
+    self.buffer.raw.state.borrow_mut().bind_array_buffer(self.buffer.handle);
+    gl::BufferSubData(
+      gl::ARRAY_BUFFER,
+      (offset * size) as GLintptr,
+      mem::size_of_val(values) as GLsizeiptr,
+      values.as_ptr() as *const c_void
+    );
+  }
+}
+
 /// A buffer slice mapped into GPU memory.
 pub struct BufferSlice<'a, T> where T: 'a {
   // Borrowed raw buffer.
@@ -372,6 +1032,8 @@ impl<'a, T> Drop for BufferSlice<'a, T> where T: 'a {
       self.raw.state.borrow_mut().bind_array_buffer(self.raw.handle);
       gl::UnmapBuffer(gl::ARRAY_BUFFER);
     }
+
+    self.raw.mapped.set(false);
   }
 }
 
@@ -392,6 +1054,58 @@ impl<'a, 'b, T> IntoIterator for &'b BufferSlice<'a, T> where T: 'a {
   }
 }
 
+impl<'a, T> BufferSlice<'a, T> where T: 'a {
+  /// Number of values in the slice.
+  pub fn len(&self) -> usize {
+    self.raw.len
+  }
+
+  /// Iterate over references to the values of the slice.
+  ///
+  /// The references borrow the slice, so the buffer stays mapped as long as they live:
+  ///
+  /// ```ignore
+  /// let slice = buffer.as_slice()?;
+  /// let sum: f32 = slice.iter().sum();
+  ///
+  /// // same thing
+  /// let sum: f32 = (&slice).into_iter().sum();
+  /// ```
+  pub fn iter(&self) -> slice::Iter<'_, T> {
+    self.deref().iter()
+  }
+
+  /// Whether the slice is empty.
+  pub fn is_empty(&self) -> bool {
+    self.raw.len == 0
+  }
+
+  // Panic if `end` is out of the slice.
+  fn check_index(&self, end: usize) {
+    if end > self.raw.len {
+      panic!("{}", BufferError::Overflow(end, self.raw.len));
+    }
+  }
+}
+
+impl<'a, T> Index<usize> for BufferSlice<'a, T> where T: 'a {
+  type Output = T;
+
+  fn index(&self, i: usize) -> &Self::Output {
+    self.check_index(i + 1);
+    unsafe { &*self.ptr.add(i) }
+  }
+}
+
+impl<'a, T> Index<Range<usize>> for BufferSlice<'a, T> where T: 'a {
+  type Output = [T];
+
+  fn index(&self, range: Range<usize>) -> &Self::Output {
+    self.check_index(range.end);
+    &self.deref()[range]
+  }
+}
+
 /// A buffer mutable slice into GPU memory.
 pub struct BufferSliceMut<'a, T> where T: 'a {
   // Borrowed buffer.
@@ -406,6 +1120,8 @@ impl<'a, T> Drop for BufferSliceMut<'a, T> where T: 'a {
       self.raw.state.borrow_mut().bind_array_buffer(self.raw.handle);
       gl::UnmapBuffer(gl::ARRAY_BUFFER);
     }
+
+    self.raw.mapped.set(false);
   }
 }
 
@@ -441,72 +1157,235 @@ impl<'a, T> DerefMut for BufferSliceMut<'a, T> where T: 'a {
   }
 }
 
-/// Typeclass of types that can be used inside a uniform block. You have to be extra careful when
-/// using uniform blocks and ensure you respect the OpenGL *std140* alignment / size rules. This
-/// will be fixed in a future release.
-pub unsafe trait UniformBlock {}
+impl<'a, T> BufferSliceMut<'a, T> where T: 'a {
+  /// Number of values in the slice.
+  pub fn len(&self) -> usize {
+    self.raw.len
+  }
 
-unsafe impl UniformBlock for u8 {}
-unsafe impl UniformBlock for u16 {}
-unsafe impl UniformBlock for u32 {}
+  /// Iterate over references to the values of the slice.
+  pub fn iter(&self) -> slice::Iter<'_, T> {
+    self.deref().iter()
+  }
 
-unsafe impl UniformBlock for i8 {}
-unsafe impl UniformBlock for i16 {}
-unsafe impl UniformBlock for i32 {}
+  /// Iterate over mutable references to the values of the slice.
+  ///
+  /// The references borrow the slice, so the buffer stays mapped as long as they live:
+  ///
+  /// ```ignore
+  /// let mut slice = buffer.as_slice_mut()?;
+  ///
+  /// for v in slice.iter_mut() {
+  ///   *v *= 2.;
+  /// }
+  ///
+  /// // same thing
+  /// for v in &mut slice {
+  ///   *v *= 2.;
+  /// }
+  /// ```
+  pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+    self.deref_mut().iter_mut()
+  }
 
-unsafe impl UniformBlock for f32 {}
-unsafe impl UniformBlock for f64 {}
+  /// Whether the slice is empty.
+  pub fn is_empty(&self) -> bool {
+    self.raw.len == 0
+  }
 
-unsafe impl UniformBlock for bool {}
+  // Panic if `end` is out of the slice.
+  fn check_index(&self, end: usize) {
+    if end > self.raw.len {
+      panic!("{}", BufferError::Overflow(end, self.raw.len));
+    }
+  }
+}
 
-unsafe impl UniformBlock for M22 {}
-unsafe impl UniformBlock for M33 {}
-unsafe impl UniformBlock for M44 {}
+impl<'a, T> Index<usize> for BufferSliceMut<'a, T> where T: 'a {
+  type Output = T;
 
-unsafe impl UniformBlock for [u8; 2] {}
-unsafe impl UniformBlock for [u16; 2] {}
-unsafe impl UniformBlock for [u32; 2] {}
+  fn index(&self, i: usize) -> &Self::Output {
+    self.check_index(i + 1);
+    unsafe { &*self.ptr.add(i) }
+  }
+}
 
-unsafe impl UniformBlock for [i8; 2] {}
-unsafe impl UniformBlock for [i16; 2] {}
-unsafe impl UniformBlock for [i32; 2] {}
+impl<'a, T> Index<Range<usize>> for BufferSliceMut<'a, T> where T: 'a {
+  type Output = [T];
 
-unsafe impl UniformBlock for [f32; 2] {}
-unsafe impl UniformBlock for [f64; 2] {}
+  fn index(&self, range: Range<usize>) -> &Self::Output {
+    self.check_index(range.end);
+    &self.deref()[range]
+  }
+}
 
-unsafe impl UniformBlock for [bool; 2] {}
+impl<'a, T> IndexMut<usize> for BufferSliceMut<'a, T> where T: 'a {
+  fn index_mut(&mut self, i: usize) -> &mut Self::Output {
+    self.check_index(i + 1);
+    unsafe { &mut *self.ptr.add(i) }
+  }
+}
 
-unsafe impl UniformBlock for [u8; 3] {}
-unsafe impl UniformBlock for [u16; 3] {}
-unsafe impl UniformBlock for [u32; 3] {}
+impl<'a, T> IndexMut<Range<usize>> for BufferSliceMut<'a, T> where T: 'a {
+  fn index_mut(&mut self, range: Range<usize>) -> &mut Self::Output {
+    self.check_index(range.end);
+    &mut self.deref_mut()[range]
+  }
+}
 
-unsafe impl UniformBlock for [i8; 3] {}
-unsafe impl UniformBlock for [i16; 3] {}
-unsafe impl UniformBlock for [i32; 3] {}
+/// Typeclass of *plain old data* types.
+///
+/// Mapping a buffer gives you references directly into GPU memory, which content might have been
+/// written by the GPU. Because the values are never copied out, those types don’t have to be
+/// `Copy`.
+///
+/// # Safety
+///
+/// Any bit pattern must be a valid value of the type and the type must not hold any host resource
+/// (references, pointers, allocations, etc.). Padding is allowed – tuples like `(u8, u32)` have
+/// some: padding bytes are never read as values.
+pub unsafe trait Pod {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for u64 {}
+
+unsafe impl Pod for i8 {}
+unsafe impl Pod for i16 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for i64 {}
+
+unsafe impl Pod for f32 {}
+unsafe impl Pod for f64 {}
+
+unsafe impl<T> Pod for [T; 1] where T: Pod {}
+unsafe impl<T> Pod for [T; 2] where T: Pod {}
+unsafe impl<T> Pod for [T; 3] where T: Pod {}
+unsafe impl<T> Pod for [T; 4] where T: Pod {}
+
+unsafe impl<A, B> Pod for GTup<A, B> where A: Pod, B: Pod {}
+
+macro_rules! impl_pod_tuple {
+  ($( $t:ident ),*) => {
+    unsafe impl<$($t),*> Pod for ($($t),*) where $($t: Pod),* {}
+  }
+}
 
-unsafe impl UniformBlock for [f32; 3] {}
-unsafe impl UniformBlock for [f64; 3] {}
+impl_pod_tuple!(A, B);
+impl_pod_tuple!(A, B, C);
+impl_pod_tuple!(A, B, C, D);
+impl_pod_tuple!(A, B, C, D, E);
+impl_pod_tuple!(A, B, C, D, E, F);
+impl_pod_tuple!(A, B, C, D, E, F, G);
+impl_pod_tuple!(A, B, C, D, E, F, G, H);
+impl_pod_tuple!(A, B, C, D, E, F, G, H, I);
+impl_pod_tuple!(A, B, C, D, E, F, G, H, I, J);
 
-unsafe impl UniformBlock for [bool; 3] {}
+/// Typeclass of types that can be used inside a uniform block. You have to be extra careful when
+/// using uniform blocks and ensure you respect the OpenGL *std140* alignment / size rules. Prefer
+/// the `uniform_block!` macro, which checks them for you.
+///
+/// `std140_align` and `std140_size` give the std140 base alignment and size of the type. They
+/// default to the alignment and size of the Rust type, which is only right if you took care of
+/// the padding yourself. `Pipeline::bind_uniform_buffer` compares them to the Rust layout in debug
+/// builds.
+///
+/// # Safety
+///
+/// The memory representation of the type is uploaded as is and read by shaders with the std140
+/// layout: it must not contain pointers, references or padding bytes that are read as part of a
+/// field, and `std140_align` and `std140_size` must return the std140 base alignment and size of
+/// the type. An implementation that lies makes shaders read out of the bounds of the type.
+pub unsafe trait UniformBlock {
+  /// Base alignment of the type in a std140 uniform block, in bytes.
+  fn std140_align() -> usize where Self: Sized {
+    mem::align_of::<Self>()
+  }
 
-unsafe impl UniformBlock for [u8; 4] {}
-unsafe impl UniformBlock for [u16; 4] {}
-unsafe impl UniformBlock for [u32; 4] {}
+  /// Size of the type in a std140 uniform block, in bytes.
+  fn std140_size() -> usize where Self: Sized {
+    mem::size_of::<Self>()
+  }
+}
 
-unsafe impl UniformBlock for [i8; 4] {}
-unsafe impl UniformBlock for [i16; 4] {}
-unsafe impl UniformBlock for [i32; 4] {}
+// Scalars of less than 32 bits don’t exist in GLSL; they’re given the layout of 32-bit ones so that
+// their size never matches std140.
+macro_rules! impl_uniform_block_scalar {
+  ($t:ty, $n:expr) => {
+    unsafe impl UniformBlock for $t {
+      fn std140_align() -> usize { $n }
+      fn std140_size() -> usize { $n }
+    }
+
+    unsafe impl UniformBlock for [$t; 2] {
+      fn std140_align() -> usize { 2 * $n }
+      fn std140_size() -> usize { 2 * $n }
+    }
+
+    unsafe impl UniformBlock for [$t; 3] {
+      fn std140_align() -> usize { 4 * $n }
+      fn std140_size() -> usize { 3 * $n }
+    }
+
+    unsafe impl UniformBlock for [$t; 4] {
+      fn std140_align() -> usize { 4 * $n }
+      fn std140_size() -> usize { 4 * $n }
+    }
+  }
+}
 
-unsafe impl UniformBlock for [f32; 4] {}
-unsafe impl UniformBlock for [f64; 4] {}
+impl_uniform_block_scalar!(u8, 4);
+impl_uniform_block_scalar!(u16, 4);
+impl_uniform_block_scalar!(u32, 4);
 
-unsafe impl UniformBlock for [bool; 4] {}
+impl_uniform_block_scalar!(i8, 4);
+impl_uniform_block_scalar!(i16, 4);
+impl_uniform_block_scalar!(i32, 4);
+
+impl_uniform_block_scalar!(f32, 4);
+impl_uniform_block_scalar!(f64, 8);
+
+impl_uniform_block_scalar!(bool, 4);
+
+// Matrices are stored as arrays of column vectors, which stride is 16 bytes.
+macro_rules! impl_uniform_block_matrix {
+  ($t:ty, $columns:expr) => {
+    unsafe impl UniformBlock for $t {
+      fn std140_align() -> usize { 16 }
+      fn std140_size() -> usize { 16 * $columns }
+    }
+  }
+}
+
+impl_uniform_block_matrix!(M22, 2);
+impl_uniform_block_matrix!(M33, 3);
+impl_uniform_block_matrix!(M44, 4);
 
 unsafe impl<T> UniformBlock for [T] where T: UniformBlock {}
 
+// Round `offset` up to the next multiple of `align`.
+fn std140_align_up(offset: usize, align: usize) -> usize {
+  (offset + align - 1) / align * align
+}
+
+// Tuples are laid out as structures: each field is aligned to its base alignment and the whole
+// structure is aligned to a multiple of 16 bytes.
 macro_rules! impl_uniform_block_tuple {
   ($( $t:ident ),*) => {
-    unsafe impl<$($t),*> UniformBlock for ($($t),*) where $($t: UniformBlock),* {}
+    unsafe impl<$($t),*> UniformBlock for ($($t),*) where $($t: UniformBlock),* {
+      fn std140_align() -> usize {
+        let align = 16;
+        $( let align = align.max($t::std140_align()); )*
+        std140_align_up(align, 16)
+      }
+
+      fn std140_size() -> usize {
+        let offset = 0;
+        $( let offset = std140_align_up(offset, $t::std140_align()) + $t::std140_size(); )*
+        std140_align_up(offset, Self::std140_align())
+      }
+    }
   }
 }
 
@@ -519,3 +1398,85 @@ impl_uniform_block_tuple!(A, B, C, D, E, F, G);
 impl_uniform_block_tuple!(A, B, C, D, E, F, G, H);
 impl_uniform_block_tuple!(A, B, C, D, E, F, G, H, I);
 impl_uniform_block_tuple!(A, B, C, D, E, F, G, H, I, J);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use gl_stubs::Context;
+
+  #[test]
+  fn whole_reads_again_after_the_previous_read_is_dropped() {
+    let mut ctx = Context::new();
+    let buffer = Buffer::<u32>::new(&mut ctx, 3);
+
+    buffer.write_whole(&[1, 2, 3]).unwrap();
+    let values = buffer.whole().unwrap();
+    assert_eq!(values, [1, 2, 3]);
+    drop(values);
+
+    buffer.write_whole(&[4, 5, 6]).unwrap();
+    assert_eq!(buffer.whole().unwrap(), [4, 5, 6]);
+  }
+
+  #[test]
+  fn whole_doesnt_alias_the_buffer() {
+    let mut ctx = Context::new();
+    let buffer = Buffer::<u32>::from_slice(&mut ctx, &[1, 2, 3]);
+
+    let values = buffer.whole().unwrap();
+    buffer.write_whole(&[4, 5, 6]).unwrap();
+    drop(buffer);
+
+    assert_eq!(values, [1, 2, 3]);
+  }
+
+  #[test]
+  fn write_slice_at_writes_the_range() {
+    let mut ctx = Context::new();
+    let buffer = Buffer::<u32>::from_slice(&mut ctx, &[1, 2, 3, 4]);
+
+    buffer.write_slice_at(1, &[9, 9]).unwrap();
+    assert_eq!(buffer.whole().unwrap(), [1, 9, 9, 4]);
+  }
+
+  #[test]
+  fn write_slice_at_fails_past_the_end() {
+    let mut ctx = Context::new();
+    let buffer = Buffer::<u32>::from_slice(&mut ctx, &[1, 2, 3, 4]);
+
+    assert_eq!(buffer.write_slice_at(3, &[9, 9]), Err(BufferError::Overflow(5, 4)));
+    assert_eq!(buffer.write_slice_at(usize::MAX, &[9]), Err(BufferError::Overflow(usize::MAX, 4)));
+    assert_eq!(buffer.whole().unwrap(), [1, 2, 3, 4]);
+  }
+
+  #[test]
+  fn copy_from_fails_past_the_end() {
+    let mut ctx = Context::new();
+    let src = Buffer::<u32>::from_slice(&mut ctx, &[1, 2]);
+    let mut dst = Buffer::<u32>::from_slice(&mut ctx, &[3, 4]);
+
+    assert_eq!(dst.copy_from(&src, 4, 0, 8), Err(BufferError::Overflow(12, 8)));
+    assert_eq!(dst.copy_from(&src, 0, 4, 8), Err(BufferError::Overflow(12, 8)));
+    assert_eq!(dst.copy_from(&src, usize::MAX, 0, 1), Err(BufferError::Overflow(usize::MAX, 8)));
+    assert_eq!(dst.copy_from(&src, 0, usize::MAX, 1), Err(BufferError::Overflow(usize::MAX, 8)));
+    assert_eq!(dst.whole().unwrap(), [3, 4]);
+  }
+
+  #[test]
+  fn whole_fails_while_mapped() {
+    let mut ctx = Context::new();
+    let buffer = Buffer::<u32>::from_slice(&mut ctx, &[1, 2, 3]);
+    let _slice = buffer.as_slice().unwrap();
+
+    assert_eq!(buffer.whole(), Err(BufferError::AlreadyMapped));
+  }
+
+  #[test]
+  fn whole_fails_on_unreadable_immutable_buffers() {
+    let mut ctx = Context::new();
+    let flags = StorageFlags::default();
+    let buffer = Buffer::<u32>::new_immutable(&mut ctx, &[1, 2, 3], flags);
+
+    assert_eq!(buffer.whole(), Err(BufferError::ImmutableStorage(flags)));
+  }
+}
@@ -0,0 +1,284 @@
+//! A simple first-person camera.
+//!
+//! Most applications need to compute a *view* and a *projection* matrix to pass to their shaders.
+//! A `Camera` gathers the parameters required to build them – position, orientation, field of
+//! view, clipping planes and aspect ratio – and exposes the matrices as `M44`, directly usable as
+//! uniforms.
+//!
+//! The orientation is expressed with a *yaw* (rotation around the Y axis) and a *pitch* (rotation
+//! around the X axis), both in radians. The camera looks down the negative Z axis when both are
+//! zero. The pitch is clamped so that the camera never flips over.
+//!
+//! ```ignore
+//! let mut camera = Camera::new([0., 0., 5.], 60f32.to_radians(), 800. / 600.);
+//!
+//! // in your event loop
+//! camera.rotate(-mouse_dx * 0.005, -mouse_dy * 0.005);
+//! camera.move_local(0., 0., -speed * dt); // move forward
+//!
+//! // in your pipeline
+//! iface.view_proj.update(camera.view_projection());
+//! ```
+//!
+//! Because luminance doesn’t handle any input, the update functions take plain deltas rather than
+//! window events: the caller maps the events of its windowing crate to them. With
+//! `luminance-glfw`, for instance:
+//!
+//! ```ignore
+//! match event {
+//!   WindowEvent::CursorPos(x, y) => {
+//!     // moving the cursor right turns right, moving it down looks down
+//!     let (dx, dy) = (x - last_x, y - last_y);
+//!     camera.rotate(-dx as f32 * 0.005, -dy as f32 * 0.005);
+//!     last_x = x;
+//!     last_y = y;
+//!   }
+//!
+//!   WindowEvent::Key(Key::W, _, Action::Press, _) => {
+//!     camera.move_local(0., 0., -0.1);
+//!   }
+//!
+//!   _ => ()
+//! }
+//! ```
+//!
+//! This module is only available with the `camera` feature.
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use linear::M44;
+
+// Small margin to prevent the pitch from reaching exactly ±π/2.
+const PITCH_EPSILON: f32 = 1e-3;
+
+/// A first-person camera.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+  /// Position of the camera in world space.
+  pub position: [f32; 3],
+  /// Rotation around the Y axis, in radians.
+  yaw: f32,
+  /// Rotation around the X axis, in radians.
+  pitch: f32,
+  /// Vertical field of view, in radians.
+  pub fovy: f32,
+  /// Distance to the near clipping plane.
+  pub near: f32,
+  /// Distance to the far clipping plane.
+  pub far: f32,
+  /// Aspect ratio (width / height) of the viewport.
+  pub aspect: f32
+}
+
+impl Camera {
+  /// Create a new camera at a given position, looking down the negative Z axis.
+  ///
+  /// The clipping planes are set to `0.1` and `100.`.
+  pub fn new(position: [f32; 3], fovy: f32, aspect: f32) -> Self {
+    Camera {
+      position,
+      yaw: 0.,
+      pitch: 0.,
+      fovy,
+      near: 0.1,
+      far: 100.,
+      aspect
+    }
+  }
+
+  /// Set the clipping planes.
+  pub fn set_clipping(self, near: f32, far: f32) -> Self {
+    Camera { near, far, ..self }
+  }
+
+  /// Rotation around the Y axis, in radians.
+  pub fn yaw(&self) -> f32 {
+    self.yaw
+  }
+
+  /// Rotation around the X axis, in radians.
+  pub fn pitch(&self) -> f32 {
+    self.pitch
+  }
+
+  /// Set the orientation of the camera.
+  ///
+  /// The pitch is clamped to `]-π/2; π/2[`.
+  pub fn set_orientation(&mut self, yaw: f32, pitch: f32) {
+    let max_pitch = FRAC_PI_2 - PITCH_EPSILON;
+
+    self.yaw = yaw % (2. * PI);
+    self.pitch = pitch.max(-max_pitch).min(max_pitch);
+  }
+
+  /// Rotate the camera by the given yaw and pitch deltas, in radians.
+  ///
+  /// This is typically driven by mouse motion.
+  pub fn rotate(&mut self, dyaw: f32, dpitch: f32) {
+    let (yaw, pitch) = (self.yaw + dyaw, self.pitch + dpitch);
+    self.set_orientation(yaw, pitch);
+  }
+
+  /// Move the camera relative to its orientation.
+  ///
+  /// `dx` moves right, `dy` moves up and `dz` moves backward. This is typically driven by
+  /// keyboard input.
+  pub fn move_local(&mut self, dx: f32, dy: f32, dz: f32) {
+    let [rx, ry, rz] = self.right();
+    let [ux, uy, uz] = self.up();
+    let [fx, fy, fz] = self.forward();
+
+    self.position[0] += rx * dx + ux * dy - fx * dz;
+    self.position[1] += ry * dx + uy * dy - fy * dz;
+    self.position[2] += rz * dx + uz * dy - fz * dz;
+  }
+
+  /// Direction the camera looks at, in world space.
+  pub fn forward(&self) -> [f32; 3] {
+    let (sy, cy) = self.yaw.sin_cos();
+    let (sp, cp) = self.pitch.sin_cos();
+
+    [-sy * cp, sp, -cy * cp]
+  }
+
+  /// Right direction of the camera, in world space.
+  pub fn right(&self) -> [f32; 3] {
+    let (sy, cy) = self.yaw.sin_cos();
+    [cy, 0., -sy]
+  }
+
+  /// Up direction of the camera, in world space.
+  pub fn up(&self) -> [f32; 3] {
+    let [fx, fy, fz] = self.forward();
+    let [rx, ry, rz] = self.right();
+
+    // right × forward
+    [ry * fz - rz * fy, rz * fx - rx * fz, rx * fy - ry * fx]
+  }
+
+  /// View matrix, transforming world space into camera space.
+  pub fn view(&self) -> M44 {
+    let [rx, ry, rz] = self.right();
+    let [ux, uy, uz] = self.up();
+    let [fx, fy, fz] = self.forward();
+    let [px, py, pz] = self.position;
+
+    let dot = |x: f32, y: f32, z: f32| x * px + y * py + z * pz;
+
+    // column-major
+    [
+      [rx, ux, -fx, 0.],
+      [ry, uy, -fy, 0.],
+      [rz, uz, -fz, 0.],
+      [-dot(rx, ry, rz), -dot(ux, uy, uz), dot(fx, fy, fz), 1.]
+    ]
+  }
+
+  /// Perspective projection matrix, transforming camera space into clip space.
+  pub fn projection(&self) -> M44 {
+    let f = 1. / (self.fovy * 0.5).tan();
+    let nf = 1. / (self.near - self.far);
+
+    // column-major
+    [
+      [f / self.aspect, 0., 0., 0.],
+      [0., f, 0., 0.],
+      [0., 0., (self.far + self.near) * nf, -1.],
+      [0., 0., 2. * self.far * self.near * nf, 0.]
+    ]
+  }
+
+  /// Product of the projection and view matrices.
+  pub fn view_projection(&self) -> M44 {
+    mul(&self.projection(), &self.view())
+  }
+}
+
+// Multiply two column-major matrices.
+fn mul(a: &M44, b: &M44) -> M44 {
+  let mut m = [[0.; 4]; 4];
+
+  for (col, b_col) in m.iter_mut().zip(b) {
+    for (row, v) in col.iter_mut().enumerate() {
+      *v = (0..4).map(|k| a[k][row] * b_col[k]).sum();
+    }
+  }
+
+  m
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_close(a: &[f32], b: &[f32]) {
+    for (x, y) in a.iter().zip(b) {
+      assert!((x - y).abs() < 1e-5, "{:?} != {:?}", a, b);
+    }
+  }
+
+  // Transform a point by a column-major matrix.
+  fn transform(m: &M44, p: [f32; 4]) -> [f32; 4] {
+    let mut r = [0.; 4];
+
+    for (row, v) in r.iter_mut().enumerate() {
+      *v = (0..4).map(|k| m[k][row] * p[k]).sum();
+    }
+
+    r
+  }
+
+  #[test]
+  fn identity_orientation_looks_down_negative_z() {
+    let camera = Camera::new([1., 2., 3.], FRAC_PI_2, 1.);
+
+    assert_close(&camera.forward(), &[0., 0., -1.]);
+    assert_close(&camera.right(), &[1., 0., 0.]);
+    assert_close(&camera.up(), &[0., 1., 0.]);
+
+    // the camera is at the origin of camera space, which looks down -Z as well
+    assert_close(&transform(&camera.view(), [1., 2., 3., 1.]), &[0., 0., 0., 1.]);
+    assert_close(&transform(&camera.view(), [1., 2., 0., 1.]), &[0., 0., -3., 1.]);
+  }
+
+  #[test]
+  fn pitch_is_clamped() {
+    let mut camera = Camera::new([0.; 3], FRAC_PI_2, 1.);
+
+    camera.rotate(0., 10.);
+    assert!(camera.pitch() < FRAC_PI_2);
+    assert!(camera.forward()[1] < 1.);
+
+    camera.rotate(0., -20.);
+    assert!(camera.pitch() > -FRAC_PI_2);
+
+    camera.set_orientation(0., FRAC_PI_2);
+    assert_eq!(camera.pitch(), FRAC_PI_2 - PITCH_EPSILON);
+  }
+
+  #[test]
+  fn move_local_follows_orientation() {
+    let mut camera = Camera::new([0.; 3], FRAC_PI_2, 1.);
+    camera.set_orientation(FRAC_PI_2, 0.);
+
+    // looking down -X: moving forward decreases X, moving right decreases Z
+    camera.move_local(0., 0., -1.);
+    assert_close(&camera.position, &[-1., 0., 0.]);
+
+    camera.move_local(1., 0., 0.);
+    assert_close(&camera.position, &[-1., 0., -1.]);
+  }
+
+  #[test]
+  fn view_projection_is_projection_times_view() {
+    let mut camera = Camera::new([1., -2., 3.], 1., 1.5).set_clipping(0.5, 50.);
+    camera.rotate(0.3, -0.2);
+
+    let vp = camera.view_projection();
+
+    for &p in &[[0., 0., 0., 1.], [1., 2., -3., 1.], [-4., 0.5, 2., 1.]] {
+      let expected = transform(&camera.projection(), transform(&camera.view(), p));
+      assert_close(&transform(&vp, p), &expected);
+    }
+  }
+}
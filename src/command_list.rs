@@ -0,0 +1,103 @@
+//! Recorded rendering commands.
+//!
+//! Walking a scene to issue the very same gate calls frame after frame might cost a lot of CPU
+//! time when the scene is mostly static. A `CommandList` lets you record a sequence of rendering
+//! commands once and replay it as many times as you want, without walking your scene again.
+//!
+//! You can record:
+//!
+//!   - Shader programs to use, with `CommandList::shade`.
+//!   - Render states, with `CommandList::render_state`.
+//!   - Tessellation renders, with `CommandList::render`.
+//!   - Any other operation – uniform updates, for instance – with `CommandList::record`.
+//!
+//! ```ignore
+//! let mut commands = CommandList::new();
+//!
+//! commands
+//!   .shade(&program)
+//!   .render_state(RenderState::default())
+//!   .record(move |_| iface.color.update([1., 0., 0.]))
+//!   .render(TessSlice::one_whole(&tess));
+//!
+//! // later, in a pipeline
+//! commands.replay(&mut ctx);
+//! ```
+//!
+//! A command list borrows the resources it references. You then cannot drop a program or a
+//! tessellation while a command list still refers to it: the command list must be dropped – and
+//! recorded again if needed – first.
+
+use context::GraphicsContext;
+use render_state::RenderState;
+use shader::program::RawProgram;
+use tess::TessSlice;
+use vertex::Vertex;
+
+// A single recorded command.
+type Command<'a, C> = Box<dyn Fn(&mut C) + 'a>;
+
+/// A list of recorded rendering commands.
+///
+/// `C` is the type of the graphics context the commands are replayed in.
+pub struct CommandList<'a, C> {
+  commands: Vec<Command<'a, C>>
+}
+
+impl<'a, C> CommandList<'a, C> where C: GraphicsContext {
+  /// Create an empty command list.
+  pub fn new() -> Self {
+    CommandList {
+      commands: Vec::new()
+    }
+  }
+
+  /// Number of recorded commands.
+  pub fn len(&self) -> usize {
+    self.commands.len()
+  }
+
+  /// Whether no command was recorded.
+  pub fn is_empty(&self) -> bool {
+    self.commands.is_empty()
+  }
+
+  /// Record the use of a shader program for the commands that follow.
+  pub fn shade(&mut self, program: &'a RawProgram) -> &mut Self {
+    self.record(move |ctx| unsafe { ctx.state().borrow_mut().use_program(program.handle(), program.has_tessellation()) })
+  }
+
+  /// Record a render state change for the commands that follow.
+  pub fn render_state(&mut self, rdr_st: RenderState) -> &mut Self {
+    self.record(move |ctx| unsafe { rdr_st.apply(&mut ctx.state().borrow_mut()) })
+  }
+
+  /// Record the render of a tessellation.
+  pub fn render<V>(&mut self, tess: TessSlice<'a, V>) -> &mut Self where V: Vertex {
+    self.record(move |ctx| tess.render(ctx))
+  }
+
+  /// Record an arbitrary command.
+  pub fn record<F>(&mut self, f: F) -> &mut Self where F: 'a + Fn(&mut C) {
+    self.commands.push(Box::new(f));
+    self
+  }
+
+  /// Remove all the recorded commands.
+  pub fn clear(&mut self) {
+    self.commands.clear();
+  }
+
+  /// Replay all the recorded commands, in order.
+  pub fn replay(&self, ctx: &mut C) {
+    for command in &self.commands {
+      command(ctx);
+    }
+  }
+}
+
+impl<'a, C> Default for CommandList<'a, C> where C: GraphicsContext {
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -13,19 +13,44 @@
 //!   - An object which type implements `GraphicsContext` must be `!Send` and `!Sync`. This
 //!     enforces that it cannot be moved nor shared between threads. Because of `GraphicsState`,
 //!     it’s very likely it’ll be `!Send` and `!Sync` automatically.
-//!   - You can only create a single context per thread. Doing otherwise is undefined behavior.
+//!   - You can only create a single context per thread. `GraphicsState::new` fails with
+//!     `StateQueryError::UnavailableGraphicsState` if a context was already created on the current
+//!     thread.
 //!   - You can create as many contexts as you want as long as they respectively live on a
 //!     separate thread. In other terms, if you want `n` contexts, you need `n` threads.
 //!
 //! That last property might seem to be a drawback to you but is required to remove a lot of
 //! dynamic branches in the implementation and reduce the number of required safety
 //! checks – enforced at compile time instead.
+//!
+//! ## Independent contexts
+//!
+//! Each context owns its own `GraphicsState`, which caches the OpenGL state of that context only.
+//! Two contexts living on two threads never share any cache: the only thread-local data luminance
+//! uses is the token ensuring a single `GraphicsState` is created per thread. GPU objects – buffers,
+//! textures, tessellations, etc. – are bound to the context they were created with and can’t be
+//! sent to another thread, so they can’t be used with another context either.
+//!
+//! > Note: the OpenGL function pointers, however, are loaded once for the whole process by the `gl`
+//! > crate. All your contexts must then be created by the same driver, with compatible versions.
 
+use gl;
+use gl::types::*;
 use std::cell::RefCell;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr;
 use std::rc::Rc;
 
+use debug::{DebugMessage, DebugSeverity, DebugSource, DebugType, GlError, opengl_debug_severity,
+            opengl_debug_source, opengl_debug_type, poll_errors};
+use depth_bias::polygon_offset_clamp_available;
+use framebuffer::Region;
+use hint::{Hint, HintMode, opengl_hint, opengl_hint_mode};
 use pipeline::Builder;
-use state::GraphicsState;
+use pixel::{FormatSupport, PixelFormat, opengl_pixel_format};
+use state::{GraphicsState, StateGuard, StateQueryError, gl_version, has_extension};
+use texture::max_anisotropy;
 
 /// Class of graphics context.
 ///
@@ -48,4 +73,323 @@ pub unsafe trait GraphicsContext {
   fn pipeline_builder(&self) -> Builder {
     Builder::new(self.state().clone())
   }
+
+  /// Capture the current OpenGL state and restore it when the returned guard is dropped.
+  ///
+  /// Use this when you interleave luminance with foreign OpenGL code. See `StateGuard` for
+  /// further details.
+  fn state_guard(&self) -> Result<StateGuard, StateQueryError> {
+    StateGuard::new(self.state().clone())
+  }
+
+  /// Give the implementation a hint about how to perform an operation.
+  ///
+  /// Hints might be ignored. See the `hint` module for further details.
+  fn set_hint(&mut self, hint: Hint, mode: HintMode) {
+    unsafe { gl::Hint(opengl_hint(hint), opengl_hint_mode(mode)) }
+  }
+
+  /// Insert a texture barrier.
+  ///
+  /// A texture barrier makes the writes performed by the previous draw commands into a texture
+  /// visible to the texture fetches of the next draw commands. It enables you to sample a texture
+  /// that is also attached to the framebuffer you render into – custom blending modes reading the
+  /// current content of the framebuffer, for instance.
+  ///
+  /// Reading from and writing to the same texture is only defined if, for a given draw command,
+  /// either:
+  ///
+  ///   - The texels read and the texels written don’t overlap.
+  ///   - Each texel is read and written at most once by a single fragment, and every texel written
+  ///     by a previous draw command was separated from this one by a texture barrier.
+  ///
+  /// Any other access creates a feedback loop with undefined results. Keep in mind that a barrier
+  /// only orders draw commands: overlapping primitives inside the same draw command still race.
+  ///
+  /// Texture barriers require OpenGL 4.5 or the `GL_ARB_texture_barrier` extension. If neither is
+  /// available, this function does nothing and returns `false`.
+  fn texture_barrier(&mut self) -> bool {
+    if gl_version() < (4, 5) && !has_extension(&[b"GL_ARB_texture_barrier"]) {
+      return false;
+    }
+
+    unsafe { gl::TextureBarrier() };
+    true
+  }
+
+  /// Set the callback receiving the debug messages of the implementation.
+  ///
+  /// The callback replaces any previously set one and lives as long as the context. It must neither
+  /// panic nor use luminance. See the `debug` module for further details.
+  ///
+  /// Debug output requires OpenGL 4.3 or the `GL_KHR_debug` extension. If neither is available,
+  /// this function does nothing and returns `false`; use `GraphicsContext::poll_errors` instead.
+  fn set_debug_callback<F>(&mut self, callback: F) -> bool
+  where Self: Sized,
+        F: 'static + FnMut(&DebugMessage) {
+    if !has_debug_output() {
+      return false;
+    }
+
+    unsafe { self.state().borrow_mut().set_debug_callback(Some(Box::new(callback))) };
+    true
+  }
+
+  /// Remove the debug callback, if any, and disable debug output.
+  fn remove_debug_callback(&mut self) {
+    if has_debug_output() {
+      unsafe { self.state().borrow_mut().set_debug_callback(None) };
+    }
+  }
+
+  /// Enable or disable the debug messages matching a source, a type and a severity.
+  ///
+  /// `None` matches any source, type or severity. Disabled messages are discarded by the
+  /// implementation and never reach the debug callback. For instance, to ignore notifications:
+  ///
+  /// ```ignore
+  /// surface.set_debug_filter(None, None, Some(DebugSeverity::Notification), false);
+  /// ```
+  ///
+  /// Returns `false` if debug output is not available.
+  fn set_debug_filter(
+    &mut self,
+    source: Option<DebugSource>,
+    ty: Option<DebugType>,
+    severity: Option<DebugSeverity>,
+    enabled: bool
+  ) -> bool {
+    if !has_debug_output() {
+      return false;
+    }
+
+    let source = source.map_or(gl::DONT_CARE, opengl_debug_source);
+    let ty = ty.map_or(gl::DONT_CARE, opengl_debug_type);
+    let severity = severity.map_or(gl::DONT_CARE, opengl_debug_severity);
+    let enabled = if enabled { gl::TRUE } else { gl::FALSE };
+
+    unsafe { gl::DebugMessageControl(source, ty, severity, 0, ptr::null(), enabled) };
+    true
+  }
+
+  /// Retrieve the errors recorded by the implementation since the last call, oldest first.
+  ///
+  /// This is the fallback for implementations without debug output: call it once per frame, for
+  /// instance, and narrow down with more calls if something shows up. It’s rather slow – it
+  /// synchronizes with the GPU – so don’t leave it in hot paths. If a debug callback is set, errors
+  /// are reported to it as well.
+  fn poll_errors(&mut self) -> Vec<GlError> {
+    poll_errors()
+  }
+
+  /// Query which operations the implementation supports for a pixel format.
+  ///
+  /// Use this to pick, at runtime, a format that is renderable, blendable or filterable on the
+  /// current GPU – and degrade gracefully to another one – instead of failing to create a
+  /// framebuffer. The support is queried for 2D textures.
+  ///
+  /// Format queries require OpenGL 4.3 or the `GL_ARB_internalformat_query2` extension. If neither
+  /// is available, this function returns `None`. It also returns `None` if luminance has no OpenGL
+  /// representation of `format`.
+  fn format_support(&self, format: PixelFormat) -> Option<FormatSupport> {
+    if gl_version() < (4, 3) && !has_extension(&[b"GL_ARB_internalformat_query2"]) {
+      return None;
+    }
+
+    let (_, internal_format, _) = opengl_pixel_format(format)?;
+
+    let query = |pname| {
+      let mut value = gl::NONE as GLint;
+      unsafe { gl::GetInternalformativ(gl::TEXTURE_2D, internal_format, pname, 1, &mut value) };
+      value
+    };
+    let supports = |pname| {
+      let value = query(pname) as GLenum;
+      value == gl::FULL_SUPPORT || value == gl::CAVEAT_SUPPORT
+    };
+
+    Some(FormatSupport {
+      supported: query(gl::INTERNALFORMAT_SUPPORTED) == gl::TRUE as GLint,
+      renderable: supports(gl::FRAMEBUFFER_RENDERABLE),
+      blendable: supports(gl::FRAMEBUFFER_BLEND),
+      filterable: supports(gl::FILTER)
+    })
+  }
+
+  /// Maximum degree of anisotropy supported by the implementation.
+  ///
+  /// Anisotropic filtering requires OpenGL 4.6 or one of the `GL_ARB_texture_filter_anisotropic`
+  /// and `GL_EXT_texture_filter_anisotropic` extensions. If none is available, this function
+  /// returns `None` and `Sampler::anisotropy` is ignored – use this to grey out an anisotropy
+  /// option, for instance.
+  fn max_anisotropy(&self) -> Option<f32> {
+    max_anisotropy()
+  }
+
+  /// Capabilities and limits of the implementation.
+  ///
+  /// Use this to size your resources before creating them – to pick a number of color buffers or
+  /// of samples, for instance. The capabilities are queried on the first call only.
+  fn capabilities(&self) -> Capabilities {
+    self.state().borrow_mut().capabilities().clone()
+  }
+
+  /// Current viewport.
+  ///
+  /// Pipelines set the viewport to their whole framebuffer when they start. Use this along with
+  /// `GraphicsContext::set_viewport` to render into a part of the framebuffer – a minimap, for
+  /// instance – and restore the previous viewport afterwards. `Pipeline::with_viewport` does both
+  /// for you.
+  fn viewport(&self) -> Region {
+    let [x, y, w, h] = self.state().borrow().viewport();
+    Region::new(x as u32, y as u32, w as u32, h as u32)
+  }
+
+  /// Set the viewport.
+  fn set_viewport(&mut self, viewport: Region) {
+    let viewport = [viewport.x as GLint, viewport.y as GLint, viewport.width as GLint, viewport.height as GLint];
+    unsafe { self.state().borrow_mut().set_viewport(viewport) };
+  }
+
+  /// Current scissor region, if the scissor test is enabled.
+  ///
+  /// When enabled, only the fragments lying in the scissor region are rendered.
+  fn scissor(&self) -> Option<Region> {
+    self.state().borrow().scissor().map(|[x, y, w, h]| Region::new(x as u32, y as u32, w as u32, h as u32))
+  }
+
+  /// Set the scissor region, or disable the scissor test with `None`.
+  ///
+  /// Renders set the scissor of their `RenderState`, so this is mostly useful to restrict the clear
+  /// of the next pipeline.
+  fn set_scissor(&mut self, scissor: Option<Region>) {
+    let scissor = scissor.map(|r| [r.x as GLint, r.y as GLint, r.width as GLint, r.height as GLint]);
+    unsafe { self.state().borrow_mut().set_scissor(scissor) };
+  }
+}
+
+/// Capabilities and limits of an implementation.
+///
+/// See `GraphicsContext::capabilities`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capabilities {
+  /// Maximum width and height of 1D and 2D textures (`GL_MAX_TEXTURE_SIZE`).
+  pub max_texture_size: u32,
+  /// Maximum width, height and depth of 3D textures (`GL_MAX_3D_TEXTURE_SIZE`).
+  pub max_3d_texture_size: u32,
+  /// Maximum size of the faces of cubemaps (`GL_MAX_CUBE_MAP_TEXTURE_SIZE`).
+  pub max_cube_map_texture_size: u32,
+  /// Maximum number of layers of layered textures (`GL_MAX_ARRAY_TEXTURE_LAYERS`).
+  pub max_array_texture_layers: u32,
+  /// Maximum number of color buffers of a framebuffer (`GL_MAX_COLOR_ATTACHMENTS`).
+  pub max_color_attachments: u32,
+  /// Maximum number of samples of multisampled framebuffers (`GL_MAX_SAMPLES`).
+  pub max_samples: u32,
+  /// Maximum number of uniform buffer binding points (`GL_MAX_UNIFORM_BUFFER_BINDINGS`).
+  pub max_uniform_buffer_bindings: u32,
+  /// Maximum number of shader storage buffer binding points
+  /// (`GL_MAX_SHADER_STORAGE_BUFFER_BINDINGS`). `0` if shader storage buffers are not supported.
+  pub max_shader_storage_buffer_bindings: u32,
+  /// Maximum size of a shader storage block, in bytes (`GL_MAX_SHADER_STORAGE_BLOCK_SIZE`). `0` if
+  /// shader storage buffers are not supported.
+  pub max_shader_storage_block_size: usize,
+  /// Maximum number of vertex attributes (`GL_MAX_VERTEX_ATTRIBS`).
+  pub max_vertex_attribs: u32,
+  /// Maximum degree of anisotropy, if anisotropic filtering is supported. See
+  /// `GraphicsContext::max_anisotropy`.
+  pub max_anisotropy: Option<f32>,
+  /// Whether the clamp of depth biases is honored – see the `depth_bias` module.
+  pub polygon_offset_clamp: bool,
+  /// Version of the implementation (`GL_VERSION`).
+  pub version: String,
+  /// Version of GLSL supported by the implementation (`GL_SHADING_LANGUAGE_VERSION`).
+  pub shading_language_version: String,
+  /// Company responsible for the implementation (`GL_VENDOR`).
+  pub vendor: String,
+  /// Name of the renderer – typically, the GPU (`GL_RENDERER`).
+  pub renderer: String
+}
+
+impl Capabilities {
+  // Query the capabilities of the current context.
+  pub(crate) fn from_context() -> Self {
+    let integer = |pname| {
+      let mut value = 0;
+      unsafe { gl::GetIntegerv(pname, &mut value) };
+      value as u32
+    };
+    // sizes may not fit in a GLint
+    let integer64 = |pname| {
+      let mut value = 0;
+      unsafe { gl::GetInteger64v(pname, &mut value) };
+      value as usize
+    };
+    // shader storage buffers require OpenGL 4.3; querying their limits on older contexts leaves a
+    // GL_INVALID_ENUM error
+    let storage_buffers = gl_version() >= (4, 3) ||
+      has_extension(&[b"GL_ARB_shader_storage_buffer_object"]);
+    let string = |name| unsafe {
+      let ptr = gl::GetString(name);
+
+      if ptr.is_null() {
+        String::new()
+      } else {
+        CStr::from_ptr(ptr as *const c_char).to_string_lossy().into_owned()
+      }
+    };
+
+    Capabilities {
+      max_texture_size: integer(gl::MAX_TEXTURE_SIZE),
+      max_3d_texture_size: integer(gl::MAX_3D_TEXTURE_SIZE),
+      max_cube_map_texture_size: integer(gl::MAX_CUBE_MAP_TEXTURE_SIZE),
+      max_array_texture_layers: integer(gl::MAX_ARRAY_TEXTURE_LAYERS),
+      max_color_attachments: integer(gl::MAX_COLOR_ATTACHMENTS),
+      max_samples: integer(gl::MAX_SAMPLES),
+      max_uniform_buffer_bindings: integer(gl::MAX_UNIFORM_BUFFER_BINDINGS),
+      max_shader_storage_buffer_bindings: if storage_buffers {
+        integer(gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS)
+      } else {
+        0
+      },
+      max_shader_storage_block_size: if storage_buffers {
+        integer64(gl::MAX_SHADER_STORAGE_BLOCK_SIZE)
+      } else {
+        0
+      },
+      max_vertex_attribs: integer(gl::MAX_VERTEX_ATTRIBS),
+      max_anisotropy: max_anisotropy(),
+      polygon_offset_clamp: polygon_offset_clamp_available(),
+      version: string(gl::VERSION),
+      shading_language_version: string(gl::SHADING_LANGUAGE_VERSION),
+      vendor: string(gl::VENDOR),
+      renderer: string(gl::RENDERER)
+    }
+  }
+}
+
+// Check whether debug output is available.
+fn has_debug_output() -> bool {
+  gl_version() >= (4, 3) || has_extension(&[b"GL_KHR_debug"])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use gl_stubs;
+
+  #[test]
+  fn storage_buffer_limits_not_queried_before_gl_4_3() {
+    gl_stubs::load();
+    let capabilities = Capabilities::from_context();
+
+    assert_eq!(capabilities.max_shader_storage_buffer_bindings, 0);
+    assert_eq!(capabilities.max_shader_storage_block_size, 0);
+    assert!(!gl_stubs::queries().contains(&gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS));
+  }
+
+  #[test]
+  fn graphics_context_is_dyn_compatible() {
+    let ctx: Option<&dyn GraphicsContext> = None;
+    assert!(ctx.is_none());
+  }
 }
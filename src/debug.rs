@@ -0,0 +1,293 @@
+//! OpenGL debug output.
+//!
+//! Tracking down an OpenGL error with `glGetError` requires checking after every single call. With
+//! *debug output*, the implementation reports errors – and warnings about deprecated or slow
+//! usage – itself, as messages handed to a callback. Set one with
+//! `GraphicsContext::set_debug_callback`:
+//!
+//! ```ignore
+//! surface.set_debug_callback(|msg: &DebugMessage| {
+//!   if msg.severity >= DebugSeverity::Medium {
+//!     eprintln!("{:?} ({:?}): {}", msg.ty, msg.severity, msg.message);
+//!   }
+//! });
+//! ```
+//!
+//! The callback is a plain closure: route the messages to the `log` crate or a file, and map
+//! `DebugSeverity` to your log levels as you see fit. Messages can also be discarded by the
+//! implementation before reaching the callback with `GraphicsContext::set_debug_filter`.
+//!
+//! Messages are delivered synchronously, on the thread of the context, from within the OpenGL call
+//! that caused them – set a breakpoint in the callback to get the faulty call in the backtrace.
+//! That has two consequences:
+//!
+//!   - The callback must not panic: it’s called by OpenGL, which can’t be unwound through, so a
+//!     panic in the callback aborts the process.
+//!   - The callback must not use luminance – the context or any GPU object. It runs inside
+//!     luminance calls that hold the graphics state, which can’t be borrowed again. Store the
+//!     messages somewhere instead, and handle them once the faulty call has returned.
+//!
+//! Debug messages refer to OpenGL objects by their names – the integer handles. luminance
+//! doesn’t label its objects with `glObjectLabel`: the errors it reports – incomplete framebuffers,
+//! shader compilation and link failures – occur while creating the objects, before any label could
+//! be attached to them.
+//!
+//! > Note: debug output requires OpenGL 4.3 or the `GL_KHR_debug` extension. Implementations may
+//! > only report a few messages – or none at all – if the context was not created as a *debug
+//! > context*. If debug output is not available, fall back to `GraphicsContext::poll_errors`.
+
+use gl;
+use gl::types::*;
+use std::error::Error;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::slice;
+
+/// A message reported by the implementation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DebugMessage {
+  /// Part of the system that emitted the message.
+  pub source: DebugSource,
+  /// Kind of message.
+  pub ty: DebugType,
+  /// Implementation-defined identifier of the message.
+  pub id: u32,
+  /// Severity of the message.
+  pub severity: DebugSeverity,
+  /// Human-readable content of the message.
+  pub message: String
+}
+
+/// Part of the system that emitted a debug message.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DebugSource {
+  /// The OpenGL API.
+  Api,
+  /// The window system – GLX, WGL, etc.
+  WindowSystem,
+  /// The GLSL compiler.
+  ShaderCompiler,
+  /// A third-party application, such as a debugger.
+  ThirdParty,
+  /// The application itself.
+  Application,
+  /// Any other source.
+  Other
+}
+
+/// Kind of debug message.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DebugType {
+  /// An error, typically the one `glGetError` would report.
+  Error,
+  /// Usage of a deprecated feature.
+  DeprecatedBehavior,
+  /// Usage leading to undefined behavior.
+  UndefinedBehavior,
+  /// Usage that is not portable to other implementations.
+  Portability,
+  /// Usage that might hurt performance.
+  Performance,
+  /// An annotation of the command stream.
+  Marker,
+  /// Start of a debug group.
+  PushGroup,
+  /// End of a debug group.
+  PopGroup,
+  /// Any other kind of message.
+  Other
+}
+
+/// Severity of a debug message.
+///
+/// Severities are ordered from the least to the most severe, so that you can compare them to a
+/// threshold.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum DebugSeverity {
+  /// Informational message.
+  Notification,
+  /// Minor performance warnings, redundant state changes, etc.
+  Low,
+  /// Major performance warnings, usage of deprecated features, etc.
+  Medium,
+  /// Errors and undefined behavior.
+  High
+}
+
+/// An error reported by `glGetError`.
+///
+/// See `GraphicsContext::poll_errors`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GlError {
+  /// An enum argument is out of range (`GL_INVALID_ENUM`).
+  InvalidEnum,
+  /// A numeric argument is out of range (`GL_INVALID_VALUE`).
+  InvalidValue,
+  /// The operation is not allowed in the current state (`GL_INVALID_OPERATION`).
+  InvalidOperation,
+  /// The framebuffer is not complete (`GL_INVALID_FRAMEBUFFER_OPERATION`).
+  InvalidFramebufferOperation,
+  /// There is not enough memory left to execute the command (`GL_OUT_OF_MEMORY`).
+  OutOfMemory,
+  /// An internal stack would underflow (`GL_STACK_UNDERFLOW`).
+  StackUnderflow,
+  /// An internal stack would overflow (`GL_STACK_OVERFLOW`).
+  StackOverflow,
+  /// Any other error. The contained value is the OpenGL error code.
+  Other(u32)
+}
+
+impl fmt::Display for GlError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match *self {
+      GlError::InvalidEnum => f.write_str("invalid enum"),
+      GlError::InvalidValue => f.write_str("invalid value"),
+      GlError::InvalidOperation => f.write_str("invalid operation"),
+      GlError::InvalidFramebufferOperation => f.write_str("invalid framebuffer operation"),
+      GlError::OutOfMemory => f.write_str("out of memory"),
+      GlError::StackUnderflow => f.write_str("stack underflow"),
+      GlError::StackOverflow => f.write_str("stack overflow"),
+      GlError::Other(code) => write!(f, "unknown OpenGL error 0x{:x}", code)
+    }
+  }
+}
+
+impl Error for GlError {}
+
+pub(crate) type DebugCallback = Box<dyn FnMut(&DebugMessage)>;
+
+// Function registered with glDebugMessageCallback. The user parameter is either null – no callback –
+// or a pointer to the boxed callback owned by the graphics state.
+pub(crate) extern "system" fn debug_message_callback(
+  source: GLenum,
+  ty: GLenum,
+  id: GLuint,
+  severity: GLenum,
+  length: GLsizei,
+  message: *const GLchar,
+  user_param: *mut c_void
+) {
+  if user_param.is_null() {
+    return;
+  }
+
+  let message = unsafe {
+    if length < 0 {
+      CStr::from_ptr(message as *const c_char).to_string_lossy().into_owned()
+    } else {
+      String::from_utf8_lossy(slice::from_raw_parts(message as *const u8, length as usize)).into_owned()
+    }
+  };
+
+  let msg = DebugMessage {
+    source: from_debug_source(source),
+    ty: from_debug_type(ty),
+    id,
+    severity: from_debug_severity(severity),
+    message
+  };
+
+  let callback = unsafe { &mut *(user_param as *mut DebugCallback) };
+
+  // unwinding into OpenGL is undefined behavior
+  if panic::catch_unwind(AssertUnwindSafe(|| callback(&msg))).is_err() {
+    process::abort();
+  }
+}
+
+// Poll the pending errors, oldest first.
+pub(crate) fn poll_errors() -> Vec<GlError> {
+  // a lost context reports an error forever; don’t loop endlessly
+  const MAX_ERRORS: usize = 32;
+
+  let mut errors = Vec::new();
+
+  while errors.len() < MAX_ERRORS {
+    let error = match unsafe { gl::GetError() } {
+      gl::NO_ERROR => break,
+      gl::INVALID_ENUM => GlError::InvalidEnum,
+      gl::INVALID_VALUE => GlError::InvalidValue,
+      gl::INVALID_OPERATION => GlError::InvalidOperation,
+      gl::INVALID_FRAMEBUFFER_OPERATION => GlError::InvalidFramebufferOperation,
+      gl::OUT_OF_MEMORY => GlError::OutOfMemory,
+      gl::STACK_UNDERFLOW => GlError::StackUnderflow,
+      gl::STACK_OVERFLOW => GlError::StackOverflow,
+      code => GlError::Other(code)
+    };
+
+    errors.push(error);
+  }
+
+  errors
+}
+
+fn from_debug_source(source: GLenum) -> DebugSource {
+  match source {
+    gl::DEBUG_SOURCE_API => DebugSource::Api,
+    gl::DEBUG_SOURCE_WINDOW_SYSTEM => DebugSource::WindowSystem,
+    gl::DEBUG_SOURCE_SHADER_COMPILER => DebugSource::ShaderCompiler,
+    gl::DEBUG_SOURCE_THIRD_PARTY => DebugSource::ThirdParty,
+    gl::DEBUG_SOURCE_APPLICATION => DebugSource::Application,
+    _ => DebugSource::Other
+  }
+}
+
+fn from_debug_type(ty: GLenum) -> DebugType {
+  match ty {
+    gl::DEBUG_TYPE_ERROR => DebugType::Error,
+    gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => DebugType::DeprecatedBehavior,
+    gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => DebugType::UndefinedBehavior,
+    gl::DEBUG_TYPE_PORTABILITY => DebugType::Portability,
+    gl::DEBUG_TYPE_PERFORMANCE => DebugType::Performance,
+    gl::DEBUG_TYPE_MARKER => DebugType::Marker,
+    gl::DEBUG_TYPE_PUSH_GROUP => DebugType::PushGroup,
+    gl::DEBUG_TYPE_POP_GROUP => DebugType::PopGroup,
+    _ => DebugType::Other
+  }
+}
+
+fn from_debug_severity(severity: GLenum) -> DebugSeverity {
+  match severity {
+    gl::DEBUG_SEVERITY_HIGH => DebugSeverity::High,
+    gl::DEBUG_SEVERITY_MEDIUM => DebugSeverity::Medium,
+    gl::DEBUG_SEVERITY_LOW => DebugSeverity::Low,
+    _ => DebugSeverity::Notification
+  }
+}
+
+pub(crate) fn opengl_debug_source(source: DebugSource) -> GLenum {
+  match source {
+    DebugSource::Api => gl::DEBUG_SOURCE_API,
+    DebugSource::WindowSystem => gl::DEBUG_SOURCE_WINDOW_SYSTEM,
+    DebugSource::ShaderCompiler => gl::DEBUG_SOURCE_SHADER_COMPILER,
+    DebugSource::ThirdParty => gl::DEBUG_SOURCE_THIRD_PARTY,
+    DebugSource::Application => gl::DEBUG_SOURCE_APPLICATION,
+    DebugSource::Other => gl::DEBUG_SOURCE_OTHER
+  }
+}
+
+pub(crate) fn opengl_debug_type(ty: DebugType) -> GLenum {
+  match ty {
+    DebugType::Error => gl::DEBUG_TYPE_ERROR,
+    DebugType::DeprecatedBehavior => gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR,
+    DebugType::UndefinedBehavior => gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR,
+    DebugType::Portability => gl::DEBUG_TYPE_PORTABILITY,
+    DebugType::Performance => gl::DEBUG_TYPE_PERFORMANCE,
+    DebugType::Marker => gl::DEBUG_TYPE_MARKER,
+    DebugType::PushGroup => gl::DEBUG_TYPE_PUSH_GROUP,
+    DebugType::PopGroup => gl::DEBUG_TYPE_POP_GROUP,
+    DebugType::Other => gl::DEBUG_TYPE_OTHER
+  }
+}
+
+pub(crate) fn opengl_debug_severity(severity: DebugSeverity) -> GLenum {
+  match severity {
+    DebugSeverity::Notification => gl::DEBUG_SEVERITY_NOTIFICATION,
+    DebugSeverity::Low => gl::DEBUG_SEVERITY_LOW,
+    DebugSeverity::Medium => gl::DEBUG_SEVERITY_MEDIUM,
+    DebugSeverity::High => gl::DEBUG_SEVERITY_HIGH
+  }
+}
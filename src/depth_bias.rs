@@ -0,0 +1,109 @@
+//! Depth bias related features.
+//!
+//! Depth bias – also known as *polygon offset* – offsets the depth of the fragments of polygons.
+//! It’s typically used to prevent *shadow acne* when rendering shadow maps, or *z-fighting* when
+//! rendering decals.
+//!
+//! The offset applied to a fragment is `factor * dz + units * r`, where `dz` is the slope of the
+//! depth of the polygon and `r` the smallest resolvable depth difference. At grazing angles, `dz`
+//! gets large and so does the offset, leading to *peter-panning*. The `clamp` field bounds the
+//! applied offset to solve that issue.
+//!
+//! # Clamping
+//!
+//! Clamping requires `glPolygonOffsetClamp` – OpenGL 4.6 or the `GL_ARB_polygon_offset_clamp`
+//! extension. The OpenGL bindings luminance uses don’t provide that function, so it must be loaded
+//! with `load_with`, with the same loader as the one used to load OpenGL:
+//!
+//! ```ignore
+//! depth_bias::load_with(|s| window.get_proc_address(s) as *const _);
+//! ```
+//!
+//! `Capabilities::polygon_offset_clamp` tells whether the clamp is honored. If it’s not – the
+//! function was not loaded or the implementation doesn’t support it – the bias falls back to a
+//! regular, unclamped `glPolygonOffset`.
+
+use gl::types::*;
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use state::{gl_version, has_extension};
+
+// GL_POLYGON_OFFSET_CLAMP, missing from the OpenGL bindings.
+pub(crate) const POLYGON_OFFSET_CLAMP: GLenum = 0x8E1B;
+
+// Address of glPolygonOffsetClamp, set by load_with; 0 if not loaded.
+static POLYGON_OFFSET_CLAMP_FN: AtomicUsize = AtomicUsize::new(0);
+
+type PolygonOffsetClampFn = extern "system" fn(GLfloat, GLfloat, GLfloat);
+
+/// Load `glPolygonOffsetClamp` with the given loader.
+///
+/// The loader maps the name of an OpenGL function to its address – it’s the same as the one
+/// passed to `gl::load_with`. Like the other OpenGL functions, `glPolygonOffsetClamp` is loaded
+/// once for the whole process. Call this before the first render using a clamped depth bias.
+pub fn load_with<F>(mut loader: F) where F: FnMut(&str) -> *const c_void {
+  let f = ["glPolygonOffsetClamp", "glPolygonOffsetClampEXT"].iter()
+    .map(|name| loader(name))
+    .find(|f| !f.is_null())
+    .unwrap_or(ptr::null());
+
+  POLYGON_OFFSET_CLAMP_FN.store(f as usize, Ordering::Relaxed);
+}
+
+// Check whether glPolygonOffsetClamp was loaded and is supported by the current context.
+pub(crate) fn polygon_offset_clamp_available() -> bool {
+  POLYGON_OFFSET_CLAMP_FN.load(Ordering::Relaxed) != 0 && (
+    gl_version() >= (4, 6) ||
+    has_extension(&[b"GL_ARB_polygon_offset_clamp", b"GL_EXT_polygon_offset_clamp"])
+  )
+}
+
+// Call glPolygonOffsetClamp.
+//
+// The function must have been loaded; see polygon_offset_clamp_available.
+pub(crate) unsafe fn polygon_offset_clamp(factor: GLfloat, units: GLfloat, clamp: GLfloat) {
+  let f = POLYGON_OFFSET_CLAMP_FN.load(Ordering::Relaxed);
+  debug_assert!(f != 0, "glPolygonOffsetClamp is not loaded");
+
+  let f = mem::transmute::<usize, PolygonOffsetClampFn>(f);
+  f(factor, units, clamp);
+}
+
+/// Depth bias setup.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DepthBias {
+  /// Scale factor applied to the depth slope of polygons.
+  pub factor: f32,
+  /// Constant offset, in units of the smallest resolvable depth difference.
+  pub units: f32,
+  /// Maximum (or minimum, if negative) offset to apply; `0.` means no clamping.
+  ///
+  /// Ignored if `Capabilities::polygon_offset_clamp` is `false`. See the documentation of the
+  /// module.
+  pub clamp: f32
+}
+
+impl DepthBias {
+  /// Create an unclamped depth bias offsetting the depth of fragments by
+  /// `factor * dz + units * r`.
+  pub fn new(factor: f32, units: f32) -> Self {
+    DepthBias {
+      factor,
+      units,
+      clamp: 0.
+    }
+  }
+
+  /// Clamp the offset to `clamp` – or to `-clamp`, if negative. `0.` disables clamping.
+  ///
+  /// The clamp requires `glPolygonOffsetClamp`; see the documentation of the module.
+  pub fn set_clamp(self, clamp: f32) -> Self {
+    DepthBias {
+      clamp,
+      .. self
+    }
+  }
+}
@@ -1,4 +1,9 @@
 //! Depth test related features.
+//!
+//! The depth test, its comparison and depth writes are set independently in `RenderState`. The
+//! usual way to mix opaque and transparent objects is to render the opaque ones first, with the
+//! depth test and depth writes enabled, and then the transparent ones with the depth test enabled
+//! but depth writes disabled – see `RenderState::transparent`.
 
 /// Whether or not depth test should be enabled.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -8,3 +13,39 @@ pub enum DepthTest {
   /// The depth test is disabled.
   Disabled
 }
+
+/// Comparison performed by the depth test between the depth of a fragment and the depth already
+/// stored in the depth buffer.
+///
+/// The fragment passes the test if the comparison holds.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DepthComparison {
+  /// The test never passes.
+  Never,
+  /// The test always passes.
+  Always,
+  /// The test passes if the depths are equal.
+  Equal,
+  /// The test passes if the depths are not equal.
+  NotEqual,
+  /// The test passes if the fragment’s depth is less than the stored one. This is the default.
+  Less,
+  /// The test passes if the fragment’s depth is less than or equal to the stored one.
+  LessOrEqual,
+  /// The test passes if the fragment’s depth is greater than the stored one.
+  Greater,
+  /// The test passes if the fragment’s depth is greater than or equal to the stored one.
+  GreaterOrEqual
+}
+
+/// Whether or not fragments passing the depth test write their depth to the depth buffer.
+///
+/// Keep in mind that when the depth test is disabled, the depth buffer is never written to. If you
+/// want to write the depth of every fragment, enable the depth test with `DepthComparison::Always`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DepthWrite {
+  /// Depth is written.
+  Enabled,
+  /// Depth is not written.
+  Disabled
+}
@@ -27,24 +27,114 @@
 //! their respective texture representation so that you can handle the corresponding texels.
 //!
 //! Color buffers are abstracted by `ColorSlot` and the depth buffer by `DepthSlot`.
+//!
+//! # Sampling the depth buffer
+//!
+//! The depth slot of a framebuffer is a depth texture – `Texture<L, D, Depth32F>` or
+//! `Texture<L, D, Depth24>`. Once rendered to, it can be bound with `Pipeline::bind_texture` and
+//! read as a regular `sampler2D` in a later pass, which is what *shadow mapping* or *SSAO* need:
+//!
+//! ```ignore
+//! let shadow_map = Framebuffer::<Flat, Dim2, (), Texture<Flat, Dim2, Depth32F>>::new(&mut ctx, [1024, 1024], 0)?;
+//!
+//! // later, in another pipeline
+//! let depths = pipeline.bind_texture(shadow_map.depth_slot());
+//! ```
+//!
+//! To sample it with a depth comparison – with a `sampler2DShadow` – enable the comparison with
+//! `Texture::set_depth_comparison` on the depth slot.
+//!
+//! When the depth slot is `()`, luminance uses a depth and stencil *renderbuffer* instead, which
+//! cannot be sampled.
+//!
+//! # Multiple render targets
+//!
+//! A framebuffer can hold several color buffers at once – up to ten – by using a tuple of textures
+//! as color slot. That’s typically used for *deferred shading*, in which a first pass writes the
+//! position, normal and albedo of the fragments – the *G-buffer* – to several textures at once:
+//!
+//! ```ignore
+//! type GBuffer = Framebuffer<Flat, Dim2, (Texture<Flat, Dim2, RGBA32F>, Texture<Flat, Dim2, RGBA32F>, Texture<Flat, Dim2, RGBA32F>), Texture<Flat, Dim2, Depth32F>>;
+//! ```
+//!
+//! All the color buffers are drawn to: the *n*-th texture of the tuple receives the fragment
+//! shader’s output declared with `layout (location = n)`. The pipeline clears all of them with
+//! its clear color. Each texture can then be bound on its own, from the tuple returned by
+//! `Framebuffer::color_slot`.
+//!
+//! # Cubemaps
+//!
+//! A framebuffer of dimension `Cubemap` renders into one face of its cubemaps at a time. It’s
+//! created with the `CubeFace::PositiveX` face attached; select another face with
+//! `Framebuffer::attach_face` before rendering to it. Rendering the six faces that way – with a
+//! 90° field of view camera looking along each axis – is how *environment maps* and *point light
+//! shadow maps* are built:
+//!
+//! ```ignore
+//! let env_map = Framebuffer::<Flat, Cubemap, Texture<Flat, Cubemap, RGBA32F>, ()>::new(&mut ctx, 512, 0)?;
+//!
+//! for face in CubeFace::ALL.iter() {
+//!   env_map.attach_face(&mut ctx, *face);
+//!   builder.pipeline(&env_map, [0., 0., 0., 1.], |_, shd_gate| { /* … */ });
+//! }
+//! ```
+//!
+//! # Multisampling
+//!
+//! `Framebuffer::new_multisampled` creates a framebuffer which buffers hold several samples per
+//! pixel, for *antialiased* offscreen renders. Multisampled color and depth textures cannot be
+//! sampled in shaders nor read back: they must first be *resolved* into a regular framebuffer of
+//! the same size with `Framebuffer::blit`.
+//!
+//! # sRGB
+//!
+//! Lighting must be computed with linear colors, while images are usually authored and displayed
+//! sRGB-encoded. luminance handles both ends of that conversion independently:
+//!
+//!   - Textures with an sRGB pixel format – `SRGB8` and `SRGBA8` – are decoded to linear by the
+//!     GPU when sampled. Textures with any other format are sampled as-is.
+//!   - When `set_srgb_encoding` is called with `SRGBEncoding::Enabled`, colors written to sRGB
+//!     color buffers are encoded from linear to sRGB. Color buffers with any other format are
+//!     written as-is.
+//!
+//! A typical setup samples `SRGBA8` textures, renders into `RGBA32F` buffers – left untouched by
+//! the encoding – and ends with a pass writing to an sRGB back buffer with the encoding enabled.
+//! Without an sRGB back buffer, encode the colors yourself in the last shader instead.
+//!
+//! # Reading back
+//!
+//! You can read back the content of a framebuffer with `Framebuffer::read_color` and
+//! `Framebuffer::read_depth`. When reading colors, the GPU might clamp them to *[0;1]*, depending
+//! on the `ClampColor` setting – see `set_clamp_color`. `Framebuffer::read_color` disables that
+//! clamping so that the values of floating-point formats – `RGBA32F`, for instance – survive the
+//! round-trip.
 
 use gl;
 use gl::types::*;
 use std::error::Error;
 use std::fmt;
 use std::marker::PhantomData;
+use std::os::raw::c_void;
 
 use context::GraphicsContext;
 use gtup::GTup;
-use pixel::{ColorPixel, DepthPixel, PixelFormat, RenderablePixel};
-use texture::{Dim2, Dimensionable, Flat, Layerable, RawTexture, Texture, TextureError,
-              create_texture, opengl_target};
+use pixel::{ColorPixel, DepthPixel, Pixel, PixelFormat, RenderablePixel, opengl_pixel_format,
+            pixel_components};
+use texture::{CubeFace, Cubemap, Dim, Dim2, Dimensionable, Flat, Layerable, Layering, MagFilter,
+              RawTexture, Texture, TextureError, check_texture_size, create_texture, opengl_cube_face,
+              opengl_target};
 
 /// Framebuffer error.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum FramebufferError {
   TextureError(TextureError),
-  Incomplete(IncompleteReason)
+  Incomplete(IncompleteReason),
+  /// The requested number of samples is zero or greater than the maximum number of samples
+  /// supported by the implementation (`GL_MAX_SAMPLES`), given second.
+  UnsupportedSamples(usize, usize),
+  /// The color slot has more color buffers, given first, than the implementation supports
+  /// (`GL_MAX_COLOR_ATTACHMENTS`), given second.
+  TooManyColorAttachments(usize, usize)
 }
 
 impl fmt::Display for FramebufferError {
@@ -57,49 +147,89 @@ impl fmt::Display for FramebufferError {
       FramebufferError::Incomplete(ref e) => {
         write!(f, "incomplete framebuffer: {}", e)
       }
+
+      FramebufferError::UnsupportedSamples(samples, max_samples) => {
+        write!(f, "unsupported number of samples: {} (maximum is {})", samples, max_samples)
+      }
+
+      FramebufferError::TooManyColorAttachments(color_buf_nb, max_color_buf_nb) => {
+        write!(f, "too many color buffers: {} (maximum is {})", color_buf_nb, max_color_buf_nb)
+      }
     }
   }
 }
 
 impl Error for FramebufferError {
-  fn cause(&self) -> Option<&Error> {
-    Some(match *self {
-      FramebufferError::TextureError(ref e) => e,
-      FramebufferError::Incomplete(ref e) => e
-    })
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match *self {
+      FramebufferError::TextureError(ref e) => Some(e),
+      FramebufferError::Incomplete(ref e) => Some(e),
+      FramebufferError::UnsupportedSamples(..) | FramebufferError::TooManyColorAttachments(..) => None
+    }
   }
 }
 
-/// Reason a framebuffer is incomplete.
+/// Reason a framebuffer is incomplete, as reported by `glCheckFramebufferStatus`.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum IncompleteReason {
+  /// The default framebuffer doesn’t exist.
   Undefined,
+  /// An attachment is not renderable or has a zero size.
   IncompleteAttachment,
+  /// The framebuffer has no attachment at all.
   MissingAttachment,
+  /// A color buffer selected to be drawn to has no attachment.
   IncompleteDrawBuffer,
+  /// The color buffer selected to be read from has no attachment.
   IncompleteReadBuffer,
+  /// The combination of the pixel formats of the attachments is not supported by the
+  /// implementation.
   Unsupported,
+  /// The attachments don’t have the same number of samples.
   IncompleteMultisample,
+  /// Some attachments are layered and others are not, or they don’t have the same target.
   IncompleteLayerTargets,
+  /// The implementation returned a status luminance doesn’t know about – `0` if an error occurred
+  /// while checking the framebuffer.
+  Unknown(GLenum)
 }
 
 impl fmt::Display for IncompleteReason {
   fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
     match *self {
-      IncompleteReason::Undefined => write!(f, "incomplete reason"),
+      IncompleteReason::Undefined => write!(f, "undefined framebuffer"),
       IncompleteReason::IncompleteAttachment => write!(f, "incomplete attachment"),
       IncompleteReason::MissingAttachment => write!(f, "missing attachment"),
       IncompleteReason::IncompleteDrawBuffer => write!(f, "incomplete draw buffer"),
       IncompleteReason::IncompleteReadBuffer => write!(f, "incomplete read buffer"),
       IncompleteReason::Unsupported => write!(f, "unsupported"),
       IncompleteReason::IncompleteMultisample => write!(f, "incomplete multisample"),
-      IncompleteReason::IncompleteLayerTargets => write!(f, "incomplete layer targets")
+      IncompleteReason::IncompleteLayerTargets => write!(f, "incomplete layer targets"),
+      IncompleteReason::Unknown(ref status) => write!(f, "unknown framebuffer status: {}", status)
     }
   }
 }
 
 impl Error for IncompleteReason {}
 
+/// Error that can occur when blitting a framebuffer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BlitError {
+  /// A linear filter was asked for while blitting the depth or stencil buffers; only
+  /// `MagFilter::Nearest` is allowed.
+  LinearDepthStencilFilter
+}
+
+impl fmt::Display for BlitError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match *self {
+      BlitError::LinearDepthStencilFilter => write!(f, "cannot blit depth or stencil buffers with a linear filter")
+    }
+  }
+}
+
+impl Error for BlitError {}
+
 /// Framebuffer with static layering, dimension, access and slots formats.
 ///
 /// A `Framebuffer` is a *GPU* special object used to render to. Because framebuffers have a
@@ -122,6 +252,7 @@ pub struct Framebuffer<L, D, CS, DS>
           CS: ColorSlot<L, D>,
           DS: DepthSlot<L, D> {
   handle: GLuint,
+  owned: bool, // whether the OpenGL framebuffer must be deleted on drop
   renderbuffer: Option<GLuint>,
   w: u32,
   h: u32,
@@ -136,6 +267,35 @@ impl Framebuffer<Flat, Dim2, (), ()> {
   pub fn back_buffer(size: <Dim2 as Dimensionable>::Size) -> Self {
     Framebuffer {
       handle: 0,
+      owned: false,
+      renderbuffer: None,
+      w: size[0],
+      h: size[1],
+      color_slot: (),
+      depth_slot: (),
+      _l: PhantomData,
+      _d: PhantomData,
+    }
+  }
+
+  /// Wrap a framebuffer created outside of luminance, with the given dimension.
+  ///
+  /// This is useful when a third party owns the render targets – a VR runtime handing out
+  /// swapchain framebuffers or a compositor, for instance – and you want luminance pipelines to
+  /// render into them.
+  ///
+  /// The returned framebuffer doesn’t own `handle`: dropping it doesn’t delete the OpenGL
+  /// framebuffer nor its attachments, which stay under your responsibility. Because luminance
+  /// doesn’t know about the attachments, the color and depth slots are empty.
+  ///
+  /// # Safety
+  ///
+  /// `handle` must be a complete framebuffer of the current context, which attachments have the
+  /// given dimension, and must outlive the returned value.
+  pub unsafe fn from_raw(handle: GLuint, size: <Dim2 as Dimensionable>::Size) -> Self {
+    Framebuffer {
+      handle,
+      owned: false,
       renderbuffer: None,
       w: size[0],
       h: size[1],
@@ -147,6 +307,167 @@ impl Framebuffer<Flat, Dim2, (), ()> {
   }
 }
 
+impl<CS, DS> Framebuffer<Flat, Dim2, CS, DS>
+    where CS: ColorSlot<Flat, Dim2>,
+          DS: DepthSlot<Flat, Dim2> {
+  /// Create a new multisampled framebuffer, with `samples` samples per pixel.
+  ///
+  /// The color and depth textures are multisampled: they cannot be bound, sampled nor read back.
+  /// Resolve them into a regular framebuffer of the same size with `Framebuffer::blit` first. If
+  /// the depth slot is empty, the depth and stencil renderbuffer is multisampled as well.
+  ///
+  /// `samples` must lie in *[1;GL_MAX_SAMPLES]*; otherwise, `FramebufferError::UnsupportedSamples`
+  /// is returned. As with `Framebuffer::new`, the framebuffer is checked for completeness.
+  pub fn new_multisampled<C>(
+    ctx: &mut C,
+    size: <Dim2 as Dimensionable>::Size,
+    samples: usize
+  ) -> Result<Self, FramebufferError>
+  where C: GraphicsContext {
+    let max_samples = ctx.state().borrow_mut().capabilities().max_samples as usize;
+
+    if samples == 0 || samples > max_samples {
+      return Err(FramebufferError::UnsupportedSamples(samples, max_samples));
+    }
+
+    let mut handle: GLuint = 0;
+    let color_formats = CS::color_formats();
+
+    check_limits::<_, Dim2>(ctx, size, color_formats.len())?;
+    let depth_format = DS::depth_format();
+    let mut textures = vec![0; color_formats.len() + if depth_format.is_some() { 1 } else { 0 }];
+    let mut depth_texture: Option<GLuint> = None;
+    let mut depth_renderbuffer: Option<GLuint> = None;
+
+    unsafe {
+      gl::GenFramebuffers(1, &mut handle);
+
+      ctx.state().borrow_mut().bind_draw_framebuffer(handle);
+
+      gl::GenTextures((textures.len()) as GLint, textures.as_mut_ptr());
+
+      // color textures
+      for (i, (format, texture)) in color_formats.iter().zip(&textures).enumerate() {
+        create_multisampled_texture(ctx, *texture, *format, size, samples);
+        gl::FramebufferTexture(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0 + i as GLenum, *texture, 0);
+      }
+
+      set_draw_buffers(color_formats.len());
+
+      // depth texture, if exists
+      if let Some(format) = depth_format {
+        let texture = textures.pop().unwrap();
+
+        create_multisampled_texture(ctx, texture, format, size, samples);
+        gl::FramebufferTexture(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, texture, 0);
+
+        depth_texture = Some(texture);
+      } else {
+        let mut renderbuffer: GLuint = 0;
+
+        gl::GenRenderbuffers(1, &mut renderbuffer);
+        gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+        gl::RenderbufferStorageMultisample(
+          gl::RENDERBUFFER,
+          samples as GLsizei,
+          gl::DEPTH32F_STENCIL8,
+          size[0] as GLsizei,
+          size[1] as GLsizei
+        );
+        gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+        gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, renderbuffer);
+
+        depth_renderbuffer = Some(renderbuffer);
+      }
+
+      let framebuffer = Framebuffer {
+        handle,
+        owned: true,
+        renderbuffer: depth_renderbuffer,
+        w: size[0],
+        h: size[1],
+        color_slot: CS::reify_textures(ctx, size, 1, &mut textures.into_iter()),
+        depth_slot: DS::reify_texture(ctx, size, 1, depth_texture),
+        _l: PhantomData,
+        _d: PhantomData,
+      };
+
+      Self::check(ctx, framebuffer)
+    }
+  }
+}
+
+// Attach a texture to the bound framebuffer. Flat cubemaps get their first face attached, as a
+// whole cubemap would be a layered attachment, incompatible with the depth renderbuffer.
+unsafe fn attach_texture<L, D>(attachment: GLenum, texture: GLuint) where L: Layerable, D: Dimensionable {
+  match (L::layering(), D::dim()) {
+    (Layering::Flat, Dim::Cubemap) => {
+      gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, opengl_cube_face(CubeFace::PositiveX), texture, 0)
+    }
+
+    _ => gl::FramebufferTexture(gl::FRAMEBUFFER, attachment, texture, 0)
+  }
+}
+
+// Get the texture attached to an attachment point of the bound framebuffer.
+unsafe fn get_attached_texture(attachment: GLenum) -> GLuint {
+  let mut texture = 0;
+  gl::GetFramebufferAttachmentParameteriv(gl::FRAMEBUFFER, attachment, gl::FRAMEBUFFER_ATTACHMENT_OBJECT_NAME, &mut texture);
+  texture as GLuint
+}
+
+// Allocate the storage of a multisampled 2D texture.
+unsafe fn create_multisampled_texture<C>(
+  ctx: &mut C,
+  texture: GLuint,
+  format: PixelFormat,
+  size: <Dim2 as Dimensionable>::Size,
+  samples: usize
+) where C: GraphicsContext {
+  let (_, iformat, _) = opengl_pixel_format(format).expect("framebuffer pixel format");
+  let mut state = ctx.state().borrow_mut();
+
+  state.bind_texture(gl::TEXTURE_2D_MULTISAMPLE, texture);
+  gl::TexImage2DMultisample(
+    gl::TEXTURE_2D_MULTISAMPLE,
+    samples as GLsizei,
+    iformat,
+    size[0] as GLsizei,
+    size[1] as GLsizei,
+    gl::TRUE
+  );
+  state.bind_texture(gl::TEXTURE_2D_MULTISAMPLE, 0);
+}
+
+impl<CS, DS> Framebuffer<Flat, Cubemap, CS, DS>
+    where CS: ColorSlot<Flat, Cubemap>,
+          DS: DepthSlot<Flat, Cubemap> {
+  /// Attach the `face` face of the cubemaps of the slots, so that the next renders go to it.
+  ///
+  /// The depth and stencil renderbuffer used when the depth slot is empty is shared by all the
+  /// faces: the pipeline clears it before each render anyway.
+  pub fn attach_face<C>(&self, ctx: &mut C, face: CubeFace) where C: GraphicsContext {
+    let color_buf_nb = CS::color_formats().len();
+    let face = opengl_cube_face(face);
+
+    unsafe {
+      ctx.state().borrow_mut().bind_draw_framebuffer(self.handle);
+
+      for i in 0..color_buf_nb {
+        let attachment = gl::COLOR_ATTACHMENT0 + i as GLenum;
+        gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, face, get_attached_texture(attachment), 0);
+      }
+
+      if DS::depth_format().is_some() {
+        gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, face, get_attached_texture(gl::DEPTH_ATTACHMENT), 0);
+      }
+
+      ctx.state().borrow_mut().bind_draw_framebuffer(0);
+    }
+  }
+}
+
 impl<L, D, CS, DS> Drop for Framebuffer<L, D, CS, DS>
     where L: Layerable,
           D: Dimensionable,
@@ -168,6 +489,16 @@ impl<L, D, CS, DS> Framebuffer<L, D, CS, DS>
   ///
   /// You’re always handed at least the base level of the texture. If you require any *additional*
   /// levels, you can pass the number via the `mipmaps` parameter.
+  ///
+  /// The framebuffer is checked for completeness once its buffers are attached. If the
+  /// implementation cannot render into it – because it doesn’t support the combination of pixel
+  /// formats, for instance – `FramebufferError::Incomplete` is returned with the reason, instead
+  /// of silently rendering nothing.
+  ///
+  /// The size and the number of color buffers are checked against the limits of the
+  /// implementation beforehand – see `GraphicsContext::capabilities`.
+  /// `FramebufferError::TooManyColorAttachments` is returned if the color slot has too many color
+  /// buffers.
   pub fn new<C>(
     ctx: &mut C,
     size: D::Size,
@@ -179,6 +510,8 @@ impl<L, D, CS, DS> Framebuffer<L, D, CS, DS>
     let color_formats = CS::color_formats();
     let depth_format = DS::depth_format();
     let target = opengl_target(L::layering(), D::dim());
+
+    check_limits::<_, D>(ctx, size, color_formats.len())?;
     let mut textures = vec![0; color_formats.len() + if depth_format.is_some() { 1 } else { 0 }];
     let mut depth_texture: Option<GLuint> = None;
     let mut depth_renderbuffer: Option<GLuint> = None;
@@ -193,29 +526,21 @@ impl<L, D, CS, DS> Framebuffer<L, D, CS, DS>
       gl::GenTextures((textures.len()) as GLint, textures.as_mut_ptr());
 
       // color textures
-      if color_formats.is_empty() {
-        gl::DrawBuffer(gl::NONE);
-      } else {
-        for (i, (format, texture)) in color_formats.iter().zip(&textures).enumerate() {
-          ctx.state().borrow_mut().bind_texture(target, *texture);
-          create_texture::<L, D>(target, size, mipmaps, *format, &Default::default()).map_err(FramebufferError::TextureError)?;
-          gl::FramebufferTexture(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0 + i as GLenum, *texture, 0);
-        }
-
-        // specify the list of color buffers to draw to
-        let color_buf_nb = color_formats.len() as GLsizei;
-        let color_buffers: Vec<_> = (gl::COLOR_ATTACHMENT0..gl::COLOR_ATTACHMENT0 + color_buf_nb as GLenum).collect();
-
-        gl::DrawBuffers(color_buf_nb, color_buffers.as_ptr());
+      for (i, (format, texture)) in color_formats.iter().zip(&textures).enumerate() {
+        ctx.state().borrow_mut().bind_texture(target, *texture);
+        create_texture::<L, D>(target, size, mipmaps, *format, &Default::default()).map_err(FramebufferError::TextureError)?;
+        attach_texture::<L, D>(gl::COLOR_ATTACHMENT0 + i as GLenum, *texture);
       }
 
+      set_draw_buffers(color_formats.len());
+
       // depth texture, if exists
       if let Some(format) = depth_format {
         let texture = textures.pop().unwrap();
 
         ctx.state().borrow_mut().bind_texture(target, texture);
         create_texture::<L, D>(target, size, mipmaps, format, &Default::default()).map_err(FramebufferError::TextureError)?;
-        gl::FramebufferTexture(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, texture, 0);
+        attach_texture::<L, D>(gl::DEPTH_ATTACHMENT, texture);
 
         depth_texture = Some(texture);
       } else {
@@ -223,10 +548,10 @@ impl<L, D, CS, DS> Framebuffer<L, D, CS, DS>
 
         gl::GenRenderbuffers(1, &mut renderbuffer);
         gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
-        gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT32F, D::width(size) as GLsizei, D::height(size) as GLsizei);
+        gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH32F_STENCIL8, D::width(size) as GLsizei, D::height(size) as GLsizei);
         gl::BindRenderbuffer(gl::RENDERBUFFER, 0); // FIXME: see whether really needed
 
-        gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, renderbuffer);
+        gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, renderbuffer);
 
         depth_renderbuffer = Some(renderbuffer);
       }
@@ -235,6 +560,7 @@ impl<L, D, CS, DS> Framebuffer<L, D, CS, DS>
 
       let framebuffer = Framebuffer {
         handle: handle,
+        owned: true,
         renderbuffer: depth_renderbuffer,
         w: D::width(size),
         h: D::height(size),
@@ -244,19 +570,23 @@ impl<L, D, CS, DS> Framebuffer<L, D, CS, DS>
         _d: PhantomData,
       };
 
-      match get_status() {
-        Ok(_) => {
-          ctx.state().borrow_mut().bind_draw_framebuffer(0); // FIXME: see whether really needed
+      Self::check(ctx, framebuffer)
+    }
+  }
 
-          Ok(framebuffer)
-        },
-        Err(reason) => {
-          ctx.state().borrow_mut().bind_draw_framebuffer(0); // FIXME: see whether really needed
+  // Check the completeness of a freshly created framebuffer, bound as draw framebuffer.
+  fn check<C>(ctx: &mut C, framebuffer: Self) -> Result<Self, FramebufferError> where C: GraphicsContext {
+    match get_status() {
+      Ok(_) => {
+        unsafe { ctx.state().borrow_mut().bind_draw_framebuffer(0) }; // FIXME: see whether really needed
 
-          Self::destroy(&framebuffer);
+        Ok(framebuffer)
+      },
+      Err(reason) => {
+        unsafe { ctx.state().borrow_mut().bind_draw_framebuffer(0) }; // FIXME: see whether really needed
 
-          Err(FramebufferError::Incomplete(reason))
-        }
+        // dropping the framebuffer releases its GPU resources
+        Err(FramebufferError::Incomplete(reason))
       }
     }
   }
@@ -268,7 +598,7 @@ impl<L, D, CS, DS> Framebuffer<L, D, CS, DS>
         gl::DeleteRenderbuffers(1, &renderbuffer);
       }
 
-      if self.handle != 0 {
+      if self.owned {
         gl::DeleteFramebuffers(1, &self.handle);
       }
     }
@@ -294,10 +624,413 @@ impl<L, D, CS, DS> Framebuffer<L, D, CS, DS>
     &self.color_slot
   }
 
+  /// Depth buffer of the framebuffer.
+  ///
+  /// If the depth slot is a texture, it can be bound and sampled in shaders.
   #[inline]
   pub fn depth_slot(&self) -> &DS {
     &self.depth_slot
   }
+
+  /// Number of samples per pixel of the framebuffer.
+  ///
+  /// Framebuffers created with `Framebuffer::new` are not multisampled and have zero sample; use
+  /// `Framebuffer::new_multisampled` to get a multisampled one. The back buffer is multisampled if
+  /// your windowing crate asked for it when creating the window – it cannot be changed afterwards.
+  /// That number might differ from the requested one, as the implementation is free to grant more
+  /// samples.
+  ///
+  /// > Note: you cannot read back the pixels of a multisampled framebuffer.
+  pub fn samples<C>(&self, ctx: &mut C) -> u32 where C: GraphicsContext {
+    let mut samples: GLint = 0;
+
+    unsafe {
+      ctx.state().borrow_mut().bind_draw_framebuffer(self.handle);
+      gl::GetIntegerv(gl::SAMPLES, &mut samples);
+    }
+
+    samples as u32
+  }
+
+  /// Read back the depth values of a region of the framebuffer.
+  ///
+  /// The depth values are returned row by row, starting at the lower-left corner of the region.
+  ///
+  /// > Note: the returned values are window-space depths, lying in *[0;1]*. If you use a
+  /// > perspective projection, they are **not** linear with the distance to the camera. You need to
+  /// > linearize them with the *near* and *far* planes of your projection to get view-space
+  /// > distances: `z = 2 * near * far / (far + near - (2 * depth - 1) * (far - near))`.
+  pub fn read_depth<C>(&self, ctx: &mut C, region: Region) -> Vec<f32> where C: GraphicsContext {
+    let mut depths = vec![0.; region.width as usize * region.height as usize];
+
+    unsafe {
+      ctx.state().borrow_mut().bind_read_framebuffer(self.handle);
+
+      gl::ReadPixels(
+        region.x as GLint,
+        region.y as GLint,
+        region.width as GLsizei,
+        region.height as GLsizei,
+        gl::DEPTH_COMPONENT,
+        gl::FLOAT,
+        depths.as_mut_ptr() as *mut c_void
+      );
+    }
+
+    depths
+  }
+
+  /// Read back the stencil values of a region of the framebuffer.
+  ///
+  /// The stencil values are returned row by row, starting at the lower-left corner of the region.
+  ///
+  /// > Note: only framebuffers without a depth slot – and the back buffer, if your windowing crate
+  /// > asked for one – have a stencil buffer. Reading from a framebuffer without a stencil buffer
+  /// > returns zeros.
+  pub fn read_stencil<C>(&self, ctx: &mut C, region: Region) -> Vec<u8> where C: GraphicsContext {
+    let mut stencils = vec![0; region.width as usize * region.height as usize];
+
+    unsafe {
+      ctx.state().borrow_mut().bind_read_framebuffer(self.handle);
+
+      // rows of bytes are not 4-byte aligned
+      gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+      gl::ReadPixels(
+        region.x as GLint,
+        region.y as GLint,
+        region.width as GLsizei,
+        region.height as GLsizei,
+        gl::STENCIL_INDEX,
+        gl::UNSIGNED_BYTE,
+        stencils.as_mut_ptr() as *mut c_void
+      );
+      gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+    }
+
+    stencils
+  }
+
+  /// Read back the colors of a region of the framebuffer.
+  ///
+  /// The colors are read from the first color buffer, converted to *RGBA* floating-point values
+  /// and returned row by row, starting at the lower-left corner of the region.
+  ///
+  /// Read color clamping is disabled before reading, so that *HDR* values stored in
+  /// floating-point formats aren’t clipped to *[0;1]*. The setting is left disabled afterwards; use
+  /// `set_clamp_color` if you need to change it back.
+  pub fn read_color<C>(&self, ctx: &mut C, region: Region) -> Vec<[f32; 4]> where C: GraphicsContext {
+    let mut colors = vec![[0.; 4]; region.width as usize * region.height as usize];
+
+    unsafe {
+      let mut state = ctx.state().borrow_mut();
+      state.bind_read_framebuffer(self.handle);
+      state.set_clamp_read_color(ClampColor::Disabled);
+
+      gl::ReadPixels(
+        region.x as GLint,
+        region.y as GLint,
+        region.width as GLsizei,
+        region.height as GLsizei,
+        gl::RGBA,
+        gl::FLOAT,
+        colors.as_mut_ptr() as *mut c_void
+      );
+    }
+
+    colors
+  }
+
+  /// Read back the raw pixels of a region of a color buffer.
+  ///
+  /// `index` selects the color buffer – the *n*-th texture of the color slot – and `P` must be its
+  /// pixel format. For the back buffer, which pixel format is chosen by your windowing crate, only
+  /// the index `0` is valid and `P` is the format the pixels are converted to; `SRGBA8` gives
+  /// 8-bit RGBA pixels, which is what you want for screenshots.
+  ///
+  /// The pixels are returned row by row, starting at the lower-left corner of the region, in their
+  /// raw encoding: a pixel is made of `P::pixel_format()` components, tightly packed. That’s the
+  /// same layout as `Texture::get_raw_texels`.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if there’s no color buffer at `index` or if its pixel format is not the
+  /// one of `P`.
+  pub fn read_pixels<C, P>(&self, ctx: &mut C, index: usize, region: Region) -> Vec<P::RawEncoding>
+  where C: GraphicsContext,
+        P: Pixel,
+        P::RawEncoding: Copy + Default {
+    let pf = P::pixel_format();
+    let mut pixels = vec![P::RawEncoding::default(); region.width as usize * region.height as usize * pixel_components(pf)];
+
+    unsafe { self.read_pixels_into::<C, P>(ctx, index, region, pixels.as_mut_ptr() as *mut c_void) };
+
+    pixels
+  }
+
+  // Read back the raw pixels of a region of a color buffer into `pixels` – which is an offset into
+  // the buffer bound to GL_PIXEL_PACK_BUFFER if any. See read_pixels.
+  pub(crate) unsafe fn read_pixels_into<C, P>(&self, ctx: &mut C, index: usize, region: Region, pixels: *mut c_void)
+  where C: GraphicsContext,
+        P: Pixel {
+    let pf = P::pixel_format();
+
+    let read_buffer = if self.handle == 0 {
+      if index != 0 {
+        panic!("cannot read color buffer {} of the back buffer, which only has one", index);
+      }
+
+      gl::BACK
+    } else {
+      match CS::color_formats().get(index) {
+        Some(format) if *format == pf => gl::COLOR_ATTACHMENT0 + index as GLenum,
+        Some(format) => panic!("cannot read color buffer {} with format {:?} as {:?}", index, format, pf),
+        None => panic!("cannot read color buffer {} out of {} color buffers", index, CS::color_formats().len())
+      }
+    };
+
+    let (format, _, ty) = opengl_pixel_format(pf).expect("pixel format without an OpenGL equivalent");
+
+    ctx.state().borrow_mut().bind_read_framebuffer(self.handle);
+
+    gl::ReadBuffer(read_buffer);
+
+    // rows are not necessarily 4-byte aligned
+    gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+    gl::ReadPixels(
+      region.x as GLint,
+      region.y as GLint,
+      region.width as GLsizei,
+      region.height as GLsizei,
+      format,
+      ty,
+      pixels
+    );
+    gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+
+    // the other read functions read from the first color buffer
+    if read_buffer != gl::BACK {
+      gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+    }
+  }
+
+  /// Read back a single pixel of an unsigned integral framebuffer.
+  ///
+  /// The value is read from the red channel of the first color buffer, which must have an unsigned
+  /// integral pixel format, such as `R32UI` or `RG32UI`. This is typically used for *GPU picking*:
+  /// render the identifier of each object in such a color buffer and read back the one under the
+  /// cursor.
+  pub fn read_pixel_u32<C>(&self, ctx: &mut C, x: u32, y: u32) -> u32 where C: GraphicsContext {
+    let mut value: GLuint = 0;
+
+    unsafe {
+      ctx.state().borrow_mut().bind_read_framebuffer(self.handle);
+
+      gl::ReadPixels(
+        x as GLint,
+        y as GLint,
+        1,
+        1,
+        gl::RED_INTEGER,
+        gl::UNSIGNED_INT,
+        &mut value as *mut GLuint as *mut c_void
+      );
+    }
+
+    value
+  }
+
+  /// Copy a region of this framebuffer into a region of another framebuffer.
+  ///
+  /// `mask` selects the buffers to copy. Colors are read from the first color buffer of this
+  /// framebuffer and written to all the color buffers of `dst`. If the regions have different
+  /// sizes, the copy is stretched with `filter`; depth and stencil buffers only support
+  /// `MagFilter::Nearest`.
+  ///
+  /// That’s typically used to downsample a render or to *resolve* a multisampled framebuffer – the
+  /// back buffer, for instance – into a regular one, in which case both regions must have the same
+  /// size.
+  ///
+  /// The bound framebuffers are restored afterwards.
+  ///
+  /// > Note: the copy is restricted by the scissor test, if enabled.
+  pub fn blit<C, LD, DD, CSD, DSD>(
+    &self,
+    ctx: &mut C,
+    dst: &Framebuffer<LD, DD, CSD, DSD>,
+    src_region: Region,
+    dst_region: Region,
+    mask: BlitMask,
+    filter: MagFilter
+  ) -> Result<(), BlitError>
+  where C: GraphicsContext,
+        LD: Layerable,
+        DD: Dimensionable,
+        DD::Size: Copy,
+        CSD: ColorSlot<LD, DD>,
+        DSD: DepthSlot<LD, DD> {
+    if matches!(filter, MagFilter::Linear) && (mask.depth || mask.stencil) {
+      return Err(BlitError::LinearDepthStencilFilter);
+    }
+
+    let mut bits = 0;
+
+    if mask.color {
+      bits |= gl::COLOR_BUFFER_BIT;
+    }
+
+    if mask.depth {
+      bits |= gl::DEPTH_BUFFER_BIT;
+    }
+
+    if mask.stencil {
+      bits |= gl::STENCIL_BUFFER_BIT;
+    }
+
+    let filter = match filter {
+      MagFilter::Nearest => gl::NEAREST,
+      MagFilter::Linear => gl::LINEAR
+    };
+
+    unsafe {
+      let mut state = ctx.state().borrow_mut();
+      let prev_read_framebuffer = state.bound_read_framebuffer();
+      let prev_draw_framebuffer = state.bound_draw_framebuffer();
+
+      state.bind_read_framebuffer(self.handle);
+      state.bind_draw_framebuffer(dst.handle);
+
+      gl::BlitFramebuffer(
+        src_region.x as GLint,
+        src_region.y as GLint,
+        (src_region.x + src_region.width) as GLint,
+        (src_region.y + src_region.height) as GLint,
+        dst_region.x as GLint,
+        dst_region.y as GLint,
+        (dst_region.x + dst_region.width) as GLint,
+        (dst_region.y + dst_region.height) as GLint,
+        bits,
+        filter
+      );
+
+      state.bind_read_framebuffer(prev_read_framebuffer);
+      state.bind_draw_framebuffer(prev_draw_framebuffer);
+    }
+
+    Ok(())
+  }
+}
+
+/// Buffers copied by `Framebuffer::blit`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BlitMask {
+  /// Copy the color buffers.
+  pub color: bool,
+  /// Copy the depth buffer.
+  pub depth: bool,
+  /// Copy the stencil buffer.
+  pub stencil: bool
+}
+
+impl BlitMask {
+  /// Copy the color buffers only.
+  pub const COLOR: BlitMask = BlitMask { color: true, depth: false, stencil: false };
+
+  /// Copy the depth buffer only.
+  pub const DEPTH: BlitMask = BlitMask { color: false, depth: true, stencil: false };
+
+  /// Copy the stencil buffer only.
+  pub const STENCIL: BlitMask = BlitMask { color: false, depth: false, stencil: true };
+
+  /// Copy all the buffers.
+  pub const ALL: BlitMask = BlitMask { color: true, depth: true, stencil: true };
+}
+
+/// Clamping of colors read back from a framebuffer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClampColor {
+  /// Colors are always clamped to *[0;1]*.
+  Enabled,
+  /// Colors are never clamped.
+  Disabled,
+  /// Colors are clamped only if the read color buffer has a fixed-point format. This is the
+  /// default.
+  FixedOnly
+}
+
+/// Set how colors read back from a framebuffer are clamped.
+pub fn set_clamp_color<C>(ctx: &mut C, clamp: ClampColor) where C: GraphicsContext {
+  unsafe { ctx.state().borrow_mut().set_clamp_read_color(clamp) }
+}
+
+/// Encoding of the colors written to sRGB color buffers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SRGBEncoding {
+  /// Linear colors output by shaders are encoded to sRGB before being written – and blending
+  /// happens in linear space.
+  Enabled,
+  /// Colors are written as-is. This is the default.
+  Disabled
+}
+
+/// Set whether colors written to sRGB color buffers are encoded from linear to sRGB.
+///
+/// Only sRGB color buffers – `SRGBA8` textures, or the back buffer if the windowing system created
+/// an sRGB one – are affected. The setting persists until it’s changed again. See the
+/// documentation of the module for further details.
+pub fn set_srgb_encoding<C>(ctx: &mut C, encoding: SRGBEncoding) where C: GraphicsContext {
+  unsafe { ctx.state().borrow_mut().set_srgb_encoding(encoding) }
+}
+
+/// Set the value the stencil buffer is cleared to when starting a pipeline with
+/// `Builder::pipeline`.
+///
+/// The default value is `0`. The value persists until it’s changed again. Pipelines started with
+/// `Builder::pipeline_with_clear` use the stencil value of their `ClearOptions` instead.
+pub fn set_clear_stencil<C>(ctx: &mut C, value: u8) where C: GraphicsContext {
+  unsafe { ctx.state().borrow_mut().set_clear_stencil(value as GLint) }
+}
+
+/// A rectangular region of a framebuffer, expressed in pixels.
+///
+/// The origin (`x`, `y`) is the lower-left corner of the region.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Region {
+  pub x: u32,
+  pub y: u32,
+  pub width: u32,
+  pub height: u32
+}
+
+impl Region {
+  pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+    Region { x, y, width, height }
+  }
+}
+
+// Check that the implementation supports framebuffers of the given size and number of color
+// buffers.
+fn check_limits<C, D>(ctx: &mut C, size: D::Size, color_buf_nb: usize) -> Result<(), FramebufferError>
+where C: GraphicsContext,
+      D: Dimensionable {
+  let mut state = ctx.state().borrow_mut();
+  let capabilities = state.capabilities();
+  let max_color_buf_nb = capabilities.max_color_attachments as usize;
+
+  if color_buf_nb > max_color_buf_nb {
+    return Err(FramebufferError::TooManyColorAttachments(color_buf_nb, max_color_buf_nb));
+  }
+
+  check_texture_size::<D>(capabilities, size).map_err(FramebufferError::TextureError)
+}
+
+// Set the color attachments to draw to; the first `color_buf_nb` ones, or none.
+unsafe fn set_draw_buffers(color_buf_nb: usize) {
+  if color_buf_nb == 0 {
+    gl::DrawBuffer(gl::NONE);
+  } else {
+    let color_buffers: Vec<_> = (gl::COLOR_ATTACHMENT0..gl::COLOR_ATTACHMENT0 + color_buf_nb as GLenum).collect();
+    gl::DrawBuffers(color_buf_nb as GLsizei, color_buffers.as_ptr());
+  }
 }
 
 fn get_status() -> Result<(), IncompleteReason> {
@@ -313,7 +1046,7 @@ fn get_status() -> Result<(), IncompleteReason> {
     gl::FRAMEBUFFER_UNSUPPORTED => Err(IncompleteReason::Unsupported),
     gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => Err(IncompleteReason::IncompleteMultisample),
     gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => Err(IncompleteReason::IncompleteLayerTargets),
-    _ => panic!("unknown OpenGL framebuffer incomplete status! status={}", status)
+    _ => Err(IncompleteReason::Unknown(status))
   }
 }
 
@@ -0,0 +1,165 @@
+//! Stand-ins for the OpenGL functions, so that tests can run without a context.
+//!
+//! Binds are no-ops, integer queries report `0` and string queries null. Buffers live in memory
+//! owned by the stubs, one store per thread – tests run on separate threads, so they don’t see each
+//! other’s buffers.
+
+use gl;
+use gl::types::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::os::raw::c_void;
+use std::ptr;
+use std::rc::Rc;
+use std::slice;
+use std::sync::Once;
+
+use context::GraphicsContext;
+use state::GraphicsState;
+
+#[derive(Default)]
+struct Buffers {
+  next_handle: GLuint,
+  bound: GLuint, // buffer bound to GL_ARRAY_BUFFER
+  storage: HashMap<GLuint, Vec<u8>>
+}
+
+thread_local!(static BUFFERS: RefCell<Buffers> = RefCell::new(Buffers::default()));
+thread_local!(static QUERIES: RefCell<Vec<GLenum>> = const { RefCell::new(Vec::new()) });
+
+// Storage of the buffer bound to GL_ARRAY_BUFFER.
+fn with_bound<F, R>(f: F) -> R where F: FnOnce(&mut Vec<u8>) -> R {
+  BUFFERS.with(|buffers| {
+    let mut buffers = buffers.borrow_mut();
+    let bound = buffers.bound;
+    f(buffers.storage.get_mut(&bound).expect("no buffer bound"))
+  })
+}
+
+extern "system" fn gen_buffers(n: GLsizei, handles: *mut GLuint) {
+  BUFFERS.with(|buffers| {
+    let mut buffers = buffers.borrow_mut();
+
+    for i in 0..n as usize {
+      buffers.next_handle += 1;
+      let handle = buffers.next_handle;
+      buffers.storage.insert(handle, Vec::new());
+      unsafe { *handles.add(i) = handle };
+    }
+  })
+}
+
+extern "system" fn delete_buffers(n: GLsizei, handles: *const GLuint) {
+  BUFFERS.with(|buffers| {
+    let mut buffers = buffers.borrow_mut();
+
+    for &handle in unsafe { slice::from_raw_parts(handles, n as usize) } {
+      buffers.storage.remove(&handle);
+    }
+  })
+}
+
+extern "system" fn bind_buffer(target: GLenum, handle: GLuint) {
+  if target == gl::ARRAY_BUFFER {
+    BUFFERS.with(|buffers| buffers.borrow_mut().bound = handle);
+  }
+}
+
+extern "system" fn buffer_data(_: GLenum, size: GLsizeiptr, data: *const c_void, _: GLenum) {
+  with_bound(|storage| {
+    *storage = if data.is_null() {
+      vec![0; size as usize]
+    } else {
+      unsafe { slice::from_raw_parts(data as *const u8, size as usize).to_vec() }
+    };
+  })
+}
+
+extern "system" fn buffer_sub_data(_: GLenum, offset: GLintptr, size: GLsizeiptr, data: *const c_void) {
+  let offset = offset as usize;
+  let data = unsafe { slice::from_raw_parts(data as *const u8, size as usize) };
+  with_bound(|storage| storage[offset..offset + data.len()].copy_from_slice(data))
+}
+
+extern "system" fn map_buffer(_: GLenum, _: GLenum) -> *mut c_void {
+  with_bound(|storage| storage.as_mut_ptr() as *mut c_void)
+}
+
+extern "system" fn map_buffer_range(_: GLenum, offset: GLintptr, _: GLsizeiptr, _: GLbitfield) -> *mut c_void {
+  with_bound(|storage| unsafe { storage.as_mut_ptr().add(offset as usize) as *mut c_void })
+}
+
+extern "system" fn unmap_buffer(_: GLenum) -> GLboolean {
+  gl::TRUE
+}
+
+extern "system" fn get_integerv(name: GLenum, data: *mut GLint) {
+  QUERIES.with(|queries| queries.borrow_mut().push(name));
+  unsafe { *data = 0 };
+}
+
+/// Names queried with `glGetIntegerv` on this thread so far.
+pub fn queries() -> Vec<GLenum> {
+  QUERIES.with(|queries| queries.borrow().clone())
+}
+
+extern "system" fn get_string(_: GLenum) -> *const GLubyte {
+  ptr::null()
+}
+
+extern "system" fn bind_buffer_base(_: GLenum, _: GLuint, _: GLuint) {}
+extern "system" fn bind_texture(_: GLenum, _: GLuint) {}
+extern "system" fn bind_vertex_array(_: GLuint) {}
+extern "system" fn active_texture(_: GLenum) {}
+extern "system" fn use_program(_: GLuint) {}
+extern "system" fn patch_parameteri(_: GLenum, _: GLint) {}
+
+/// Load the stubs; functions without a stub are left unloaded.
+pub fn load() {
+  static LOAD: Once = Once::new();
+
+  LOAD.call_once(|| {
+    gl::load_with(|name| match name {
+      "glGenBuffers" => gen_buffers as *const c_void,
+      "glDeleteBuffers" => delete_buffers as *const c_void,
+      "glBindBuffer" => bind_buffer as *const c_void,
+      "glBufferData" => buffer_data as *const c_void,
+      "glBufferSubData" => buffer_sub_data as *const c_void,
+      "glMapBuffer" => map_buffer as *const c_void,
+      "glMapBufferRange" => map_buffer_range as *const c_void,
+      "glUnmapBuffer" => unmap_buffer as *const c_void,
+      "glGetIntegerv" => get_integerv as *const c_void,
+      "glGetString" => get_string as *const c_void,
+      "glBindBufferBase" => bind_buffer_base as *const c_void,
+      "glBindTexture" => bind_texture as *const c_void,
+      "glBindVertexArray" => bind_vertex_array as *const c_void,
+      "glActiveTexture" => active_texture as *const c_void,
+      "glUseProgram" => use_program as *const c_void,
+      "glPatchParameteri" => patch_parameteri as *const c_void,
+      _ => ptr::null()
+    });
+  });
+}
+
+/// A context running on the stubs.
+pub struct Context {
+  state: Rc<RefCell<GraphicsState>>
+}
+
+impl Context {
+  pub fn new() -> Self {
+    load();
+
+    Context {
+      state: Rc::new(RefCell::new(GraphicsState::stub()))
+    }
+  }
+}
+
+unsafe impl GraphicsContext for Context {
+  fn state(&self) -> &Rc<RefCell<GraphicsState>> {
+    &self.state
+  }
+
+  fn swap_buffers(&mut self) {}
+}
@@ -0,0 +1,65 @@
+//! Implementation hints.
+//!
+//! Hints let you tell the driver whether you prefer quality or speed for some operations. They’re
+//! only *hints*: implementations are free to ignore them, and most modern drivers do. In practice:
+//!
+//!   - `Hint::FragmentShaderDerivative` is respected by some drivers and changes the precision of
+//!     `dFdx`, `dFdy` and `fwidth` in fragment shaders. You can also use the `dFdxFine` /
+//!     `dFdxCoarse` family of functions in your shaders to get a guaranteed behavior.
+//!   - `Hint::TextureCompression` might change the quality of textures compressed by the driver.
+//!   - `Hint::LineSmooth` and `Hint::PolygonSmooth` only matter if line or polygon smoothing is
+//!     enabled, which luminance doesn’t do.
+//!   - `Hint::GenerateMipmap` is not part of the core profile. Setting it on a core profile context
+//!     generates an OpenGL error and does nothing.
+//!
+//! All hints default to `HintMode::DontCare`.
+
+use gl;
+use gl::types::*;
+
+// GENERATE_MIPMAP_HINT; only available in compatibility profiles.
+const GENERATE_MIPMAP_HINT: GLenum = 0x8192;
+
+/// Operation to give a hint about.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Hint {
+  /// Accuracy of the derivative functions in fragment shaders.
+  FragmentShaderDerivative,
+  /// Quality of the generated mipmaps.
+  GenerateMipmap,
+  /// Sampling quality of antialiased lines.
+  LineSmooth,
+  /// Sampling quality of antialiased polygons.
+  PolygonSmooth,
+  /// Quality and performance of compressing textures.
+  TextureCompression
+}
+
+/// Preference given for an operation.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HintMode {
+  /// The most efficient option should be chosen.
+  Fastest,
+  /// The most correct, or highest quality, option should be chosen.
+  Nicest,
+  /// No preference.
+  DontCare
+}
+
+pub(crate) fn opengl_hint(hint: Hint) -> GLenum {
+  match hint {
+    Hint::FragmentShaderDerivative => gl::FRAGMENT_SHADER_DERIVATIVE_HINT,
+    Hint::GenerateMipmap => GENERATE_MIPMAP_HINT,
+    Hint::LineSmooth => gl::LINE_SMOOTH_HINT,
+    Hint::PolygonSmooth => gl::POLYGON_SMOOTH_HINT,
+    Hint::TextureCompression => gl::TEXTURE_COMPRESSION_HINT
+  }
+}
+
+pub(crate) fn opengl_hint_mode(mode: HintMode) -> GLenum {
+  match mode {
+    HintMode::Fastest => gl::FASTEST,
+    HintMode::Nicest => gl::NICEST,
+    HintMode::DontCare => gl::DONT_CARE
+  }
+}
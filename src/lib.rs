@@ -58,6 +58,15 @@
 //! Currently, **luminance is powered by OpenGL 3.3**. It might change, but it’ll always be in favor
 //! on supporting more devices and technologies – a shift to Vulkan is planned, for instance.
 //!
+//! # Errors
+//!
+//! Fallible operations return plain enums – one per module, like `BufferError` or
+//! `FramebufferError` – that you can match on. They implement `Display` and `Error`; formatting
+//! them writes into the formatter and doesn’t allocate.
+//!
+//! `luminance` requires the standard library: its objects share the graphics state with `Rc` and
+//! some errors carry `String`s – compilation logs, for instance. There is no `no_std` support.
+//!
 //! # Windowing
 //!
 //! `luminance` does not provide a way to create windows because it’s important that it not depend
@@ -65,6 +74,16 @@
 //! libraries typically implement windowing and events features, which have nothing to do with our
 //! initial purpose.
 //!
+//! Everything about the window and the input is thus provided by the windowing crate – like
+//! `luminance-glfw`, which the examples use – and requests for such features belong there:
+//!
+//!   - Cursor modes – hiding or grabbing the cursor – and raw mouse motion.
+//!   - Vertical synchronization and the swap interval: `GraphicsContext::swap_buffers` is
+//!     implemented by the windowing crate.
+//!   - Switching between windowed and fullscreen modes. Rebuild your back buffer – with
+//!     `Framebuffer::back_buffer` – when the windowing crate reports a new framebuffer size.
+//!   - Gamepads and joysticks.
+//!
 //! # User-guide and contributor-guide
 //!
 //! If you just plan to use `luminance`, just read the *User-guide* section.
@@ -111,17 +130,33 @@ extern crate gl;
 
 pub mod blending;
 pub mod buffer;
+#[cfg(feature = "camera")] pub mod camera;
+pub mod command_list;
 pub mod context;
+pub mod debug;
+pub mod depth_bias;
 pub mod depth_test;
 pub mod face_culling;
 #[macro_use] pub mod framebuffer;
 #[macro_use] pub mod gtup;
+pub mod hint;
 pub mod linear;
+pub mod occlusion_query;
+pub mod ping_pong;
 pub mod pipeline;
 pub mod pixel;
+pub mod polygon_mode;
+pub mod provoking_vertex;
+pub mod readback;
 pub mod render_state;
 pub mod shader;
 pub mod state;
+pub mod stencil;
+#[macro_use] pub mod std140;
 pub mod tess;
 pub mod texture;
+pub mod timer_query;
+pub mod transform_feedback;
 pub mod vertex;
+
+#[cfg(test)] mod gl_stubs;
@@ -0,0 +1,129 @@
+//! Occlusion queries.
+//!
+//! An occlusion query counts the samples that pass the depth and stencil tests while rendering. Use
+//! it to skip hidden objects: render a cheap *proxy* of an object – its bounding box, for
+//! instance – with an occlusion query and only render the object itself if some samples passed.
+//! The proxy shouldn’t alter the framebuffer, so render it with the color and depth writes
+//! disabled:
+//!
+//! ```ignore
+//! let proxy_state = RenderState::default()
+//!   .set_color_mask([false; 4])
+//!   .set_depth_write(DepthWrite::Disabled);
+//!
+//! rdr_gate.render(proxy_state, |tess_gate| {
+//!   query.measure(|| tess_gate.render(&mut surface, (&bounding_box).into()));
+//! });
+//! ```
+//!
+//! # Latency
+//!
+//! The result is computed asynchronously by the GPU. Waiting for it right after the proxy render
+//! would stall the CPU until the GPU has caught up, which usually costs more than rendering the
+//! object. Instead, read the result of the *previous* frame with `OcclusionQuery::samples`, which
+//! never blocks:
+//!
+//!   1. Render the object if the query of the last frame says it’s visible – or if the result is
+//!      not available yet, to be on the safe side.
+//!   2. Render its proxy with the query, for the next frame to read.
+//!
+//! An object is then culled one frame late and shows up one frame late, which is rarely
+//! noticeable. Render the proxy after the occluders, so that the depth buffer holds them.
+
+use gl;
+use gl::types::*;
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+/// What an occlusion query counts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Occlusion {
+  /// Count the samples passing the tests.
+  SamplesPassed,
+  /// Only tell whether any sample passed the tests. The result is then either `0` or `1`. This is
+  /// cheaper, as the implementation can stop counting at the first sample.
+  AnySamplesPassed
+}
+
+/// A query counting the samples passing the depth and stencil tests.
+pub struct OcclusionQuery {
+  handle: GLuint,
+  occlusion: Occlusion,
+  // a query object doesn’t hold any result until it’s used for the first time
+  measured: Cell<bool>,
+  _p: PhantomData<*const ()>
+}
+
+impl OcclusionQuery {
+  /// Create a new query.
+  pub fn new(occlusion: Occlusion) -> Self {
+    let mut handle: GLuint = 0;
+
+    unsafe { gl::GenQueries(1, &mut handle) };
+
+    OcclusionQuery {
+      handle,
+      occlusion,
+      measured: Cell::new(false),
+      _p: PhantomData
+    }
+  }
+
+  /// What the query counts.
+  pub fn occlusion(&self) -> Occlusion {
+    self.occlusion
+  }
+
+  /// Count the samples passing the tests during the renders issued in `f`.
+  ///
+  /// The result of the previous measure, if not read yet, is lost.
+  pub fn measure<F, R>(&self, f: F) -> R where F: FnOnce() -> R {
+    let target = opengl_occlusion(self.occlusion);
+
+    unsafe { gl::BeginQuery(target, self.handle) };
+    let r = f();
+    unsafe { gl::EndQuery(target) };
+
+    self.measured.set(true);
+    r
+  }
+
+  /// Whether the result of the last measure is available.
+  ///
+  /// This is always `false` if nothing was measured yet.
+  pub fn is_ready(&self) -> bool {
+    if !self.measured.get() {
+      return false;
+    }
+
+    let mut ready: GLuint = gl::FALSE as GLuint;
+    unsafe { gl::GetQueryObjectuiv(self.handle, gl::QUERY_RESULT_AVAILABLE, &mut ready) };
+    ready == gl::TRUE as GLuint
+  }
+
+  /// Number of samples that passed the tests during the last measure.
+  ///
+  /// This function doesn’t block: it returns `None` if the result is not available yet.
+  pub fn samples(&self) -> Option<u64> {
+    if !self.is_ready() {
+      return None;
+    }
+
+    let mut samples: GLuint64 = 0;
+    unsafe { gl::GetQueryObjectui64v(self.handle, gl::QUERY_RESULT, &mut samples) };
+    Some(samples)
+  }
+}
+
+impl Drop for OcclusionQuery {
+  fn drop(&mut self) {
+    unsafe { gl::DeleteQueries(1, &self.handle) }
+  }
+}
+
+fn opengl_occlusion(occlusion: Occlusion) -> GLenum {
+  match occlusion {
+    Occlusion::SamplesPassed => gl::SAMPLES_PASSED,
+    Occlusion::AnySamplesPassed => gl::ANY_SAMPLES_PASSED
+  }
+}
@@ -0,0 +1,80 @@
+//! Ping-pong framebuffers.
+//!
+//! Several effects – iterative blurs, bloom, GPU simulations, etc. – need to render several times
+//! in a row, each pass reading the result of the previous one. Because you cannot read from a
+//! framebuffer you’re rendering into, such effects alternate between two framebuffers: one is
+//! read while the other is written to, and their roles are swapped after each pass.
+//!
+//! A `PingPong` holds two framebuffers with the same format and size and keeps track of which one
+//! must be read and which one must be written to:
+//!
+//! ```ignore
+//! let mut ping_pong = PingPong::<Flat, Dim2, Texture<Flat, Dim2, RGBA32F>, ()>::new(&mut ctx, size, 0)?;
+//!
+//! for _ in 0..iterations {
+//!   builder.pipeline(ping_pong.write(), [0., 0., 0., 0.], |pipeline, shd_gate| {
+//!     let source = pipeline.bind_texture(ping_pong.read().color_slot());
+//!     // render a fullscreen quad reading from source
+//!   });
+//!
+//!   ping_pong.swap();
+//! }
+//!
+//! // the result of the last pass
+//! let result = ping_pong.read();
+//! ```
+
+use context::GraphicsContext;
+use framebuffer::{ColorSlot, DepthSlot, Framebuffer, FramebufferError};
+use texture::{Dimensionable, Layerable};
+
+/// Two framebuffers alternatively read and written to.
+pub struct PingPong<L, D, CS, DS>
+    where L: Layerable,
+          D: Dimensionable,
+          D::Size: Copy,
+          CS: ColorSlot<L, D>,
+          DS: DepthSlot<L, D> {
+  framebuffers: [Framebuffer<L, D, CS, DS>; 2],
+  read_index: usize
+}
+
+impl<L, D, CS, DS> PingPong<L, D, CS, DS>
+    where L: Layerable,
+          D: Dimensionable,
+          D::Size: Copy,
+          CS: ColorSlot<L, D>,
+          DS: DepthSlot<L, D> {
+  /// Create two framebuffers with the same format and size.
+  ///
+  /// The arguments are the same as in `Framebuffer::new`.
+  pub fn new<C>(
+    ctx: &mut C,
+    size: D::Size,
+    mipmaps: usize
+  ) -> Result<Self, FramebufferError>
+  where C: GraphicsContext {
+    let a = Framebuffer::new(ctx, size, mipmaps)?;
+    let b = Framebuffer::new(ctx, size, mipmaps)?;
+
+    Ok(PingPong {
+      framebuffers: [a, b],
+      read_index: 0
+    })
+  }
+
+  /// Framebuffer to read from – i.e. the one written to before the last swap.
+  pub fn read(&self) -> &Framebuffer<L, D, CS, DS> {
+    &self.framebuffers[self.read_index]
+  }
+
+  /// Framebuffer to write to.
+  pub fn write(&self) -> &Framebuffer<L, D, CS, DS> {
+    &self.framebuffers[1 - self.read_index]
+  }
+
+  /// Swap the framebuffers: the one written to becomes the one to read from, and conversely.
+  pub fn swap(&mut self) {
+    self.read_index = 1 - self.read_index;
+  }
+}
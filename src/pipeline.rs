@@ -31,8 +31,9 @@
 //!   - A *stencil buffer*, which often acts as a mask to create interesting effects to your
 //!     renders.
 //!
-//! luminance gives you access to the first two – the stencil buffer will be added in a future
-//! release.
+//! The stencil buffer is cleared along with the other buffers – see
+//! `framebuffer::set_clear_stencil` and `ClearOptions` – tested and written with
+//! `RenderState::set_stencil_test` and read back with `Framebuffer::read_stencil`.
 //!
 //! Alternatively, you can also tell your GPU that you won’t be using a depth buffer, or that you
 //! need several color buffers – this is called [MRT](https://en.wikipedia.org/wiki/Multiple_Render_Targets).
@@ -95,26 +96,46 @@
 //!
 //! A pipeline is just an aggregation of shadings commands with a few extra information. It
 //! especially gives you the power to scope-bind GPU resources.
+//!
+//! # Binding resources
+//!
+//! Textures and buffers are bound with `Pipeline::bind_texture` and `Pipeline::bind_buffer` – or
+//! `Pipeline::bind_uniform_buffer` for buffers of `UniformBlock` and `Pipeline::bind_storage_buffer`
+//! for shader storage buffers. luminance picks a free texture
+//! unit or binding index and returns a bound resource, which keeps it bound as long as it lives.
+//! Pass that bound resource to the uniform interface to point a sampler or a uniform block to it:
+//!
+//! ```ignore
+//! builder.pipeline(&back_buffer, [0., 0., 0., 1.], |pipeline, shd_gate| {
+//!   let lights = pipeline.bind_uniform_buffer(&lights_buffer);
+//!
+//!   shd_gate.shade(&program, |rdr_gate, iface| {
+//!     iface.lights.update(&lights);
+//!     // …
+//!   });
+//! });
+//! ```
 
 use gl;
 use gl::types::*;
 
 use std::cell::RefCell;
 use std::marker::PhantomData;
+use std::mem;
 use std::ops::Deref;
 use std::rc::Rc;
 
-use buffer::{Buffer, RawBuffer};
-use blending::BlendingState;
+use buffer::{AtomicCounter, Buffer, BufferError, RawBuffer, UniformBlock};
 use context::GraphicsContext;
-use face_culling::FaceCullingState;
-use framebuffer::{ColorSlot, DepthSlot, Framebuffer};
+use depth_test::DepthWrite;
+use framebuffer::{ColorSlot, DepthSlot, Framebuffer, Region};
 use pixel::{Pixel, Type as PxType};
 use render_state::RenderState;
 use shader::program::{Program, Type, Uniform, Uniformable, UniformInterface};
 use state::GraphicsState;
-use tess::TessSlice;
+use tess::{IndirectDraw, Mode, TessSlice};
 use texture::{Dim, Dimensionable, Layerable, Texture};
+use transform_feedback::TransformFeedback;
 use vertex::{CompatibleVertex, Vertex};
 
 // A stack of bindings.
@@ -127,7 +148,9 @@ struct BindingStack {
   next_texture_unit: u32,
   free_texture_units: Vec<u32>,
   next_buffer_binding: u32,
-  free_buffer_bindings: Vec<u32>
+  free_buffer_bindings: Vec<u32>,
+  next_storage_binding: u32,
+  free_storage_bindings: Vec<u32>
 }
 
 impl BindingStack {
@@ -138,9 +161,73 @@ impl BindingStack {
       next_texture_unit: 0,
       free_texture_units: Vec::new(),
       next_buffer_binding: 0,
-      free_buffer_bindings: Vec::new()
+      free_buffer_bindings: Vec::new(),
+      next_storage_binding: 0,
+      free_storage_bindings: Vec::new()
+    }
+  }
+}
+
+/// Buffers to clear when starting a pipeline, along with the values to clear them to.
+///
+/// A buffer whose value is `None` is left untouched. See `Builder::pipeline_with_clear`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClearOptions {
+  /// Clear color of the color buffers.
+  pub color: Option<[f32; 4]>,
+  /// Clear value of the depth buffer.
+  pub depth: Option<f32>,
+  /// Clear value of the stencil buffer.
+  pub stencil: Option<u8>
+}
+
+impl ClearOptions {
+  /// Clear all the buffers: the color buffers to `color`, the depth buffer to `1` and the stencil
+  /// buffer to `0`.
+  pub fn all(color: [f32; 4]) -> Self {
+    ClearOptions {
+      color: Some(color),
+      depth: Some(1.),
+      stencil: Some(0)
+    }
+  }
+
+  /// Clear no buffer.
+  pub fn none() -> Self {
+    ClearOptions {
+      color: None,
+      depth: None,
+      stencil: None
+    }
+  }
+
+  pub fn set_color<C>(self, color: C) -> Self where C: Into<Option<[f32; 4]>> {
+    ClearOptions {
+      color: color.into(),
+      .. self
+    }
+  }
+
+  pub fn set_depth<V>(self, depth: V) -> Self where V: Into<Option<f32>> {
+    ClearOptions {
+      depth: depth.into(),
+      .. self
     }
   }
+
+  pub fn set_stencil<V>(self, stencil: V) -> Self where V: Into<Option<u8>> {
+    ClearOptions {
+      stencil: stencil.into(),
+      .. self
+    }
+  }
+}
+
+impl Default for ClearOptions {
+  /// Clear all the buffers, the color buffers to opaque black.
+  fn default() -> Self {
+    ClearOptions::all([0., 0., 0., 1.])
+  }
 }
 
 /// An opaque type used to create pipelines.
@@ -170,7 +257,25 @@ impl Builder {
   /// `CS` and `DS` are – respectively – the *color* and *depth* `Slot`(s) of the underlying
   /// `Framebuffer`.
   ///
-  /// Pipelines also have a *clear color*, used to clear the framebuffer.
+  /// Pipelines also have a *clear color*, used to clear the framebuffer. Color buffers with an
+  /// integral pixel format – `R32UI`, for instance – are cleared with the clear color converted to
+  /// integers.
+  ///
+  /// Pipelines can be nested – to render into a texture in the middle of another pipeline, for
+  /// instance. The bound framebuffer, the viewport, the scissor and the shader program in use are
+  /// restored when a pipeline returns, so that the outer pipeline goes on rendering as if nothing
+  /// happened.
+  ///
+  /// The clear respects the scissor test active when the pipeline starts: set a scissor region
+  /// with `GraphicsContext::set_scissor` before calling this function to only clear that region of
+  /// the framebuffer – a pane of a split-screen render, for instance. Renders then use the scissor
+  /// of their `RenderState`. Keep in mind that a pipeline nested in another one starts with the
+  /// scissor of the last render of the outer pipeline.
+  ///
+  /// All the buffers of the framebuffer are cleared: the color buffers to `clear_color`, the depth
+  /// buffer to `1` and the stencil buffer to the value set with `framebuffer::set_clear_stencil`.
+  /// The color and depth write masks are reset before clearing; the stencil write mask is left as
+  /// is. Use `Builder::pipeline_with_clear` to choose which buffers are cleared and how.
   pub fn pipeline<'a, L, D, CS, DS, F>(
     &self,
     framebuffer: &Framebuffer<L, D, CS, DS>,
@@ -182,21 +287,168 @@ impl Builder {
         CS: ColorSlot<L, D>,
         DS: DepthSlot<L, D>,
         F: FnOnce(Pipeline, ShadingGate) {
+    self.run_pipeline(framebuffer, f, |gfx_state| unsafe {
+      // the color and depth write masks apply to clears too; a previous render might have masked
+      // them out
+      gfx_state.set_color_mask([true; 4]);
+      gfx_state.set_depth_write(DepthWrite::Enabled);
+
+      gl::ClearColor(clear_color[0], clear_color[1], clear_color[2], clear_color[3]);
+      gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+
+      clear_integral_color_buffers::<L, D, CS>(clear_color);
+    })
+  }
+
+  /// Create a new `Pipeline` clearing the buffers selected by `clear`, and consume it immediately.
+  ///
+  /// This is the same as `Builder::pipeline`, but the buffers left to `None` in `clear` are not
+  /// cleared at all – clear the depth buffer only to render on top of a previous pipeline, or clear
+  /// nothing to accumulate the renders of several pipelines, for instance. The write masks of the
+  /// cleared buffers are reset, so that previous renders can’t mask the clear out.
+  pub fn pipeline_with_clear<L, D, CS, DS, F>(
+    &self,
+    framebuffer: &Framebuffer<L, D, CS, DS>,
+    clear: ClearOptions,
+    f: F
+  )
+  where L: Layerable,
+        D: Dimensionable,
+        CS: ColorSlot<L, D>,
+        DS: DepthSlot<L, D>,
+        F: FnOnce(Pipeline, ShadingGate) {
+    self.run_pipeline(framebuffer, f, |gfx_state| unsafe {
+      // the clear values used by Builder::pipeline are restored once cleared
+      let prev_clear_depth = gfx_state.clear_depth();
+      let prev_clear_stencil = gfx_state.clear_stencil();
+      let mut clear_bits = 0;
+
+      if let Some(clear_color) = clear.color {
+        gfx_state.set_color_mask([true; 4]);
+        gl::ClearColor(clear_color[0], clear_color[1], clear_color[2], clear_color[3]);
+        clear_bits |= gl::COLOR_BUFFER_BIT;
+      }
+
+      if let Some(clear_depth) = clear.depth {
+        gfx_state.set_depth_write(DepthWrite::Enabled);
+        gfx_state.set_clear_depth(clear_depth);
+        clear_bits |= gl::DEPTH_BUFFER_BIT;
+      }
+
+      if let Some(clear_stencil) = clear.stencil {
+        gfx_state.set_stencil_write_mask(0xff);
+        gfx_state.set_clear_stencil(clear_stencil as GLint);
+        clear_bits |= gl::STENCIL_BUFFER_BIT;
+      }
+
+      if clear_bits != 0 {
+        gl::Clear(clear_bits);
+      }
+
+      if let Some(clear_color) = clear.color {
+        clear_integral_color_buffers::<L, D, CS>(clear_color);
+      }
+
+      gfx_state.set_clear_depth(prev_clear_depth);
+      gfx_state.set_clear_stencil(prev_clear_stencil);
+    })
+  }
+
+  // Run a pipeline rendering into `framebuffer`, which is cleared with `clear`.
+  fn run_pipeline<L, D, CS, DS, F, C>(&self, framebuffer: &Framebuffer<L, D, CS, DS>, f: F, clear: C)
+  where L: Layerable,
+        D: Dimensionable,
+        CS: ColorSlot<L, D>,
+        DS: DepthSlot<L, D>,
+        F: FnOnce(Pipeline, ShadingGate),
+        C: FnOnce(&mut GraphicsState) {
     let binding_stack = &self.binding_stack;
 
+    // save the state the pipeline alters, so that a pipeline nested in another one doesn’t leak
+    // into the outer one
+    let (prev_framebuffer, prev_viewport, prev_scissor, prev_program) = {
+      let bs = binding_stack.borrow();
+      let gfx_state = bs.gfx_state.borrow();
+      (
+        gfx_state.bound_draw_framebuffer(),
+        gfx_state.viewport(),
+        gfx_state.scissor(),
+        (gfx_state.current_program(), gfx_state.current_program_tessellation())
+      )
+    };
+
     unsafe {
       let bs = binding_stack.borrow();
-      bs.gfx_state.borrow_mut().bind_draw_framebuffer(framebuffer.handle());
+      let mut gfx_state = bs.gfx_state.borrow_mut();
 
-      gl::Viewport(0, 0, framebuffer.width() as GLint, framebuffer.height() as GLint);
-      gl::ClearColor(clear_color[0], clear_color[1], clear_color[2], clear_color[3]);
-      gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+      gfx_state.bind_draw_framebuffer(framebuffer.handle());
+      gfx_state.set_viewport([0, 0, framebuffer.width() as GLint, framebuffer.height() as GLint]);
+
+      clear(&mut gfx_state);
     }
 
     let p = Pipeline { binding_stack };
     let shd_gt = ShadingGate { binding_stack };
 
     f(p, shd_gt);
+
+    unsafe {
+      let bs = binding_stack.borrow();
+      let mut gfx_state = bs.gfx_state.borrow_mut();
+
+      gfx_state.bind_draw_framebuffer(prev_framebuffer);
+      gfx_state.set_viewport(prev_viewport);
+      gfx_state.set_scissor(prev_scissor);
+      gfx_state.use_program(prev_program.0, prev_program.1);
+    }
+  }
+}
+
+// Check that a range of a buffer of `bytes` bytes can be bound as a uniform buffer.
+fn check_buffer_range(
+  offset: usize,
+  size: usize,
+  bytes: usize,
+  alignment: usize,
+  max_size: usize
+) -> Result<(), BufferError> {
+  // a range ending past usize::MAX ends past the buffer as well
+  let end = offset.saturating_add(size);
+
+  if end > bytes {
+    return Err(BufferError::RangeOverflow(end, bytes));
+  }
+
+  if offset % alignment != 0 {
+    return Err(BufferError::UnalignedRange(offset, alignment));
+  }
+
+  if size > max_size {
+    return Err(BufferError::RangeTooLarge(size, max_size));
+  }
+
+  Ok(())
+}
+
+// Clear the integral color buffers, for which glClear is undefined, one by one.
+unsafe fn clear_integral_color_buffers<L, D, CS>(clear_color: [f32; 4])
+where L: Layerable,
+      D: Dimensionable,
+      CS: ColorSlot<L, D> {
+  for (i, format) in CS::color_formats().iter().enumerate() {
+    match format.encoding {
+      PxType::Unsigned => {
+        let color = [clear_color[0] as GLuint, clear_color[1] as GLuint, clear_color[2] as GLuint, clear_color[3] as GLuint];
+        gl::ClearBufferuiv(gl::COLOR, i as GLint, color.as_ptr());
+      }
+
+      PxType::Integral => {
+        let color = [clear_color[0] as GLint, clear_color[1] as GLint, clear_color[2] as GLint, clear_color[3] as GLint];
+        gl::ClearBufferiv(gl::COLOR, i as GLint, color.as_ptr());
+      }
+
+      _ => ()
+    }
   }
 }
 
@@ -211,7 +463,13 @@ pub struct Pipeline<'a> {
 impl<'a> Pipeline<'a> {
   /// Bind a texture and return the bound texture.
   ///
-  /// The texture remains bound as long as the return value lives.
+  /// The texture is bound to a free texture unit and remains bound as long as the return value
+  /// lives. Once it’s dropped, the unit is reused by the next binds.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if more textures are bound at once than the implementation has texture
+  /// units (`GL_MAX_COMBINED_TEXTURE_IMAGE_UNITS`).
   pub fn bind_texture<L, D, P>(
     &'a self,
     texture: &'a Texture<L, D, P>
@@ -224,6 +482,12 @@ impl<'a> Pipeline<'a> {
     let unit = bstack.free_texture_units.pop().unwrap_or_else(|| {
       // no more free units; reserve one
       let unit = bstack.next_texture_unit;
+      let max_units = bstack.gfx_state.borrow().max_texture_units();
+
+      if unit >= max_units {
+        panic!("cannot bind more than {} textures at once", max_units);
+      }
+
       bstack.next_texture_unit += 1;
       unit
     });
@@ -256,6 +520,147 @@ impl<'a> Pipeline<'a> {
 
     BoundBuffer::new(self.binding_stack, binding)
   }
+
+  /// Bind a buffer holding a uniform block and return the bound buffer.
+  ///
+  /// This is the same as `Pipeline::bind_buffer`, but in debug builds, it also checks that the size
+  /// of `T` matches its std140 size.
+  ///
+  /// # Panics
+  ///
+  /// In debug builds, this function panics if `T` doesn’t have the size required by std140.
+  pub fn bind_uniform_buffer<T>(&'a self, buffer: &'a Buffer<T>) -> BoundBuffer<'a, Buffer<T>>
+  where T: UniformBlock {
+    debug_assert!(
+      mem::size_of::<T>() == T::std140_size(),
+      "uniform block type has a size of {} bytes but std140 requires {} bytes",
+      mem::size_of::<T>(),
+      T::std140_size()
+    );
+
+    self.bind_buffer(buffer)
+  }
+
+  /// Bind a range of a buffer.
+  ///
+  /// The range starts at `offset` and spans `size` bytes. This is handy if you pack the uniform
+  /// blocks of several objects into a single buffer and want each render to read its own part.
+  ///
+  /// The offset must be a multiple of the uniform buffer offset alignment of the implementation
+  /// and the size cannot exceed the maximum uniform block size. `BufferError::UnalignedRange` and
+  /// `BufferError::RangeTooLarge` are respectively returned otherwise.
+  pub fn bind_buffer_range<T>(
+    &'a self,
+    buffer: &'a T,
+    offset: usize,
+    size: usize
+  ) -> Result<BoundBuffer<'a, T>, BufferError>
+  where T: Deref<Target = RawBuffer> {
+    let mut bstack = self.binding_stack.borrow_mut();
+
+    {
+      let gfx_state = bstack.gfx_state.borrow();
+      let alignment = gfx_state.uniform_buffer_offset_alignment();
+      let max_size = gfx_state.max_uniform_block_size();
+
+      check_buffer_range(offset, size, buffer.bytes(), alignment, max_size)?;
+    }
+
+    let binding = bstack.free_buffer_bindings.pop().unwrap_or_else(|| {
+      // no more free bindings; reserve one
+      let binding = bstack.next_buffer_binding;
+      bstack.next_buffer_binding += 1;
+      binding
+    });
+
+    unsafe {
+      bstack.gfx_state.borrow_mut().bind_buffer_range(buffer.handle(), binding, offset, size);
+    }
+
+    Ok(BoundBuffer::new(self.binding_stack, binding))
+  }
+
+  /// Bind a buffer as a shader storage buffer and return the bound buffer.
+  ///
+  /// Unlike uniform blocks, shader storage blocks can be written by shaders and can be much larger
+  /// – implementations support at least 16 MiB. Shader storage binding points are distinct from
+  /// uniform buffer ones. The buffer remains bound as long as the return value lives.
+  ///
+  /// If the buffer is larger than the maximum shader storage block size of the implementation,
+  /// `BufferError::RangeTooLarge` is returned. Shader storage buffers require OpenGL 4.3; the
+  /// maximum size is `0` – and the bind always fails – on older implementations.
+  ///
+  /// > Note: writes to a shader storage buffer are not guaranteed to be visible to subsequent
+  /// > renders until a memory barrier is issued (`glMemoryBarrier`).
+  pub fn bind_storage_buffer<T>(&'a self, buffer: &'a T) -> Result<BoundStorageBuffer<'a, T>, BufferError>
+  where T: Deref<Target = RawBuffer> {
+    let mut bstack = self.binding_stack.borrow_mut();
+
+    {
+      let max_size = bstack.gfx_state.borrow_mut().capabilities().max_shader_storage_block_size;
+
+      if buffer.bytes() > max_size {
+        return Err(BufferError::RangeTooLarge(buffer.bytes(), max_size));
+      }
+    }
+
+    let binding = bstack.free_storage_bindings.pop().unwrap_or_else(|| {
+      // no more free bindings; reserve one
+      let binding = bstack.next_storage_binding;
+      bstack.next_storage_binding += 1;
+      binding
+    });
+
+    unsafe {
+      bstack.gfx_state.borrow_mut().bind_storage_buffer_base(buffer.handle(), binding);
+    }
+
+    Ok(BoundStorageBuffer::new(self.binding_stack, binding))
+  }
+
+  /// Render into a part of the framebuffer.
+  ///
+  /// The viewport – in pixels, from the lower-left corner of the framebuffer – is set to `viewport`
+  /// while `f` runs and the previous viewport is restored afterwards; the whole framebuffer when not
+  /// nested in another call. Use the shading gate of the pipeline in `f` to render:
+  ///
+  /// ```ignore
+  /// builder.pipeline(&back_buffer, [0., 0., 0., 1.], |pipeline, shd_gate| {
+  ///   pipeline.with_viewport(Region::new(0, 0, w / 2, h), || {
+  ///     shd_gate.shade(&program, |rdr_gate, iface| {
+  ///       // …
+  ///     });
+  ///   });
+  /// });
+  /// ```
+  ///
+  /// The viewport only maps the rendered primitives; it doesn’t restrict the clear of the
+  /// pipeline. Combine it with a scissor region if you need that too.
+  pub fn with_viewport<F>(&self, viewport: Region, f: F) where F: FnOnce() {
+    let prev_viewport = {
+      let bs = self.binding_stack.borrow();
+      let mut gfx_state = bs.gfx_state.borrow_mut();
+      let prev_viewport = gfx_state.viewport();
+
+      unsafe {
+        gfx_state.set_viewport([
+          viewport.x as GLint,
+          viewport.y as GLint,
+          viewport.width as GLint,
+          viewport.height as GLint
+        ]);
+      }
+
+      prev_viewport
+    };
+
+    f();
+
+    unsafe {
+      let bs = self.binding_stack.borrow();
+      bs.gfx_state.borrow_mut().set_viewport(prev_viewport);
+    }
+  }
 }
 
 /// An opaque type representing a bound texture in a `Builder`. You may want to pass such an object
@@ -305,16 +710,16 @@ where L: 'a + Layerable,
     match (P::pixel_format().encoding, D::dim()) {
       (PxType::Integral, Dim::Dim1) => Type::ISampler1D,
       (PxType::Unsigned, Dim::Dim1) => Type::UISampler1D,
-      (PxType::Floating, Dim::Dim1) => Type::Sampler1D,
+      (PxType::Floating, Dim::Dim1) | (PxType::NormUnsigned, Dim::Dim1) => Type::Sampler1D,
       (PxType::Integral, Dim::Dim2) => Type::ISampler2D,
       (PxType::Unsigned, Dim::Dim2) => Type::UISampler2D,
-      (PxType::Floating, Dim::Dim2) => Type::Sampler2D,
+      (PxType::Floating, Dim::Dim2) | (PxType::NormUnsigned, Dim::Dim2) => Type::Sampler2D,
       (PxType::Integral, Dim::Dim3) => Type::ISampler3D,
       (PxType::Unsigned, Dim::Dim3) => Type::UISampler3D,
-      (PxType::Floating, Dim::Dim3) => Type::Sampler3D,
+      (PxType::Floating, Dim::Dim3) | (PxType::NormUnsigned, Dim::Dim3) => Type::Sampler3D,
       (PxType::Integral, Dim::Cubemap) => Type::ICubemap,
       (PxType::Unsigned, Dim::Cubemap) => Type::UICubemap,
-      (PxType::Floating, Dim::Cubemap) => Type::Cubemap
+      (PxType::Floating, Dim::Cubemap) | (PxType::NormUnsigned, Dim::Cubemap) => Type::Cubemap
     }
   }
 }
@@ -353,6 +758,45 @@ unsafe impl<'a, 'b, T> Uniformable for &'b BoundBuffer<'a, T> {
   fn ty() -> Type { Type::BufferBinding }
 }
 
+/// An opaque type representing a buffer bound as a shader storage buffer in a `Builder`. You may
+/// want to pass such an object to a shader’s uniform’s update.
+pub struct BoundStorageBuffer<'a, T> where T: 'a {
+  binding: u32,
+  binding_stack: &'a Rc<RefCell<BindingStack>>,
+  _t: PhantomData<&'a Buffer<T>>
+}
+
+impl<'a, T> BoundStorageBuffer<'a, T> {
+  fn new(binding_stack: &'a Rc<RefCell<BindingStack>>, binding: u32) -> Self {
+    BoundStorageBuffer {
+      binding,
+      binding_stack,
+      _t: PhantomData
+    }
+  }
+
+  /// Shader storage buffer binding point the buffer is bound to.
+  pub fn binding(&self) -> u32 {
+    self.binding
+  }
+}
+
+impl<'a, T> Drop for BoundStorageBuffer<'a, T> {
+  fn drop(&mut self) {
+    let mut bstack = self.binding_stack.borrow_mut();
+    // place the binding into the free list
+    bstack.free_storage_bindings.push(self.binding);
+  }
+}
+
+unsafe impl<'a, 'b, T> Uniformable for &'b BoundStorageBuffer<'a, T> {
+  fn update(self, u: &Uniform<Self>) {
+    unsafe { gl::ShaderStorageBlockBinding(u.program(), u.index() as GLuint, self.binding as GLuint) }
+  }
+
+  fn ty() -> Type { Type::StorageBinding }
+}
+
 /// A shading gate provides you with a way to run shaders on rendering commands.
 pub struct ShadingGate<'a> {
   binding_stack: &'a Rc<RefCell<BindingStack>>
@@ -369,7 +813,7 @@ impl<'a> ShadingGate<'a> {
           F: FnOnce(&RenderGate<In>, &Uni) {
     unsafe {
       let bstack = self.binding_stack.borrow_mut();
-      bstack.gfx_state.borrow_mut().use_program(program.handle());
+      bstack.gfx_state.borrow_mut().use_program(program.handle(), program.has_tessellation());
     };
 
     let render_gate = RenderGate {
@@ -393,31 +837,7 @@ impl<'a, V> RenderGate<'a, V> {
   pub fn render<F>(&self, rdr_st: RenderState, f: F) where F: FnOnce(&TessGate<V>) {
     unsafe {
       let bstack = self.binding_stack.borrow_mut();
-      let mut gfx_state = bstack.gfx_state.borrow_mut();
-
-      match rdr_st.blending {
-        Some((equation, src_factor, dst_factor)) => {
-          gfx_state.set_blending_state(BlendingState::Enabled);
-          gfx_state.set_blending_equation(equation);
-          gfx_state.set_blending_func(src_factor, dst_factor);
-        },
-        None => {
-          gfx_state.set_blending_state(BlendingState::Disabled);
-        }
-      }
-
-      gfx_state.set_depth_test(rdr_st.depth_test);
-
-      match rdr_st.face_culling {
-        Some(face_culling) => {
-          gfx_state.set_face_culling_state(FaceCullingState::Enabled);
-          gfx_state.set_face_culling_order(face_culling.order);
-          gfx_state.set_face_culling_mode(face_culling.mode);
-        },
-        None => {
-          gfx_state.set_face_culling_state(FaceCullingState::Disabled);
-        }
-      }
+      rdr_st.apply(&mut bstack.gfx_state.borrow_mut());
     }
 
     let tess_gate = TessGate {
@@ -442,4 +862,77 @@ impl<V> TessGate<V> where V: Vertex {
   ) where C: GraphicsContext, W: CompatibleVertex<V> {
     tess.render(ctx);
   }
+
+  /// Render several instances of a tessellation. See `TessSlice::render_instanced`.
+  pub fn render_instanced<C, W>(
+    &self,
+    ctx: &mut C,
+    tess: TessSlice<W>,
+    inst_nb: usize
+  ) where C: GraphicsContext, W: CompatibleVertex<V> {
+    tess.render_instanced(ctx, inst_nb);
+  }
+
+  /// Render a tessellation with an indirect draw command. See `TessSlice::render_indirect`.
+  pub fn render_indirect<C, W>(
+    &self,
+    ctx: &mut C,
+    tess: TessSlice<W>,
+    commands: &Buffer<IndirectDraw>,
+    i: usize
+  ) where C: GraphicsContext, W: CompatibleVertex<V> {
+    tess.render_indirect(ctx, commands, i);
+  }
+
+  /// Render a tessellation with as many instances as the value of an atomic counter. See
+  /// `TessSlice::render_counted`.
+  pub fn render_counted<C, W>(
+    &self,
+    ctx: &mut C,
+    tess: TessSlice<W>,
+    counter: &AtomicCounter
+  ) where C: GraphicsContext, W: CompatibleVertex<V> {
+    tess.render_counted(ctx, counter);
+  }
+
+  /// Render the vertices captured by a transform feedback object, without querying their number
+  /// back on the CPU.
+  pub fn render_transform_feedback<C, W>(
+    &self,
+    ctx: &mut C,
+    transform_feedback: &TransformFeedback<W>,
+    mode: Mode
+  ) where C: GraphicsContext, W: CompatibleVertex<V> {
+    transform_feedback.render(ctx, mode);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn buffer_range_in_bounds() {
+    assert_eq!(check_buffer_range(0, 64, 1024, 256, 16384), Ok(()));
+    assert_eq!(check_buffer_range(768, 256, 1024, 256, 16384), Ok(()));
+  }
+
+  #[test]
+  fn buffer_range_past_the_end() {
+    assert_eq!(check_buffer_range(768, 512, 1024, 256, 16384), Err(BufferError::RangeOverflow(1280, 1024)));
+    assert_eq!(
+      check_buffer_range(usize::MAX - 255, 512, 1024, 256, 16384),
+      Err(BufferError::RangeOverflow(usize::MAX, 1024))
+    );
+  }
+
+  #[test]
+  fn buffer_range_unaligned() {
+    assert_eq!(check_buffer_range(64, 64, 1024, 256, 16384), Err(BufferError::UnalignedRange(64, 256)));
+  }
+
+  #[test]
+  fn buffer_range_too_large() {
+    assert_eq!(check_buffer_range(0, 1024, 1024, 256, 512), Err(BufferError::RangeTooLarge(1024, 512)));
+  }
 }
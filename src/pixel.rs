@@ -1,6 +1,10 @@
 //! Pixel formats types and function manipulation.
 //!
 //! The `Pixel` trait is used to reify a pixel type at runtime via `PixelFormat`.
+//!
+//! Floating-point formats (`R32F`, `RGBA32F`, etc.) can hold values outside of *[0;1]*. Keep in
+//! mind that colors read back from a framebuffer might get clamped; see
+//! `framebuffer::ClampColor`.
 
 use gl;
 use gl::types::*;
@@ -26,6 +30,13 @@ pub unsafe trait DepthPixel: Pixel {}
 /// Constaint on `Pixel` for renderable ones.
 pub unsafe trait RenderablePixel: Pixel {}
 
+/// Constraint on `Pixel` for sRGB-encoded ones.
+///
+/// # Safety
+///
+/// The `PixelFormat` of the type must have a `Format::SRGB` or `Format::SRGBA` format.
+pub unsafe trait SRGBPixel: ColorPixel {}
+
 /// A `PixelFormat` gathers a `Type` along with a `Format`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct PixelFormat {
@@ -35,12 +46,30 @@ pub struct PixelFormat {
   pub format: Format
 }
 
+/// Operations supported by the implementation for a given `PixelFormat`.
+///
+/// Such an object is returned by `GraphicsContext::format_support`. Operations supported with
+/// caveats – typically, with a performance penalty – are reported as supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatSupport {
+  /// Whether textures can be created with this format at all.
+  pub supported: bool,
+  /// Whether textures with this format can be attached to a framebuffer and rendered into.
+  pub renderable: bool,
+  /// Whether blending works when rendering into textures with this format.
+  pub blendable: bool,
+  /// Whether textures with this format can be sampled with linear filtering.
+  pub filterable: bool
+}
+
 /// Pixel type.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Type {
   Integral,
   Unsigned,
-  Floating
+  Floating,
+  /// Unsigned integers normalized to *[0;1]*, read as floating-point values in shaders.
+  NormUnsigned
 }
 
 /// Format of a pixel.
@@ -57,6 +86,10 @@ pub enum Format {
   RGB(Size, Size, Size),
   /// Holds red, green, blue and alpha channels.
   RGBA(Size, Size, Size, Size),
+  /// Holds sRGB-encoded red, green and blue channels.
+  SRGB(Size, Size, Size),
+  /// Holds sRGB-encoded red, green and blue channels and a linear alpha channel.
+  SRGBA(Size, Size, Size, Size),
   /// Holds a depth channel.
   Depth(Size)
 }
@@ -64,8 +97,12 @@ pub enum Format {
 /// Size in bits a pixel channel can be.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Size {
+  Two,
   Eight,
+  Ten,
+  Eleven,
   Sixteen,
+  TwentyFour,
   ThirtyTwo
 }
 
@@ -82,6 +119,22 @@ pub fn is_depth_pixel(f: PixelFormat) -> bool {
   !is_color_pixel(f)
 }
 
+/// Does a `PixelFormat` hold sRGB-encoded colors?
+pub fn is_srgb_pixel(f: PixelFormat) -> bool {
+  matches!(f.format, Format::SRGB(..) | Format::SRGBA(..))
+}
+
+/// Convert an sRGB-encoded channel to linear.
+///
+/// Both the input and output values are in *[0;1]*.
+pub fn srgb_to_linear(x: f32) -> f32 {
+  if x <= 0.04045 {
+    x / 12.92
+  } else {
+    ((x + 0.055) / 1.055).powf(2.4)
+  }
+}
+
 macro_rules! impl_Pixel {
   ($t:ty, $encoding:ty, $raw_encoding:ty, $encoding_ty:expr, $format:expr) => {
     unsafe impl Pixel for $t {
@@ -116,6 +169,12 @@ macro_rules! impl_RenderablePixel {
   }
 }
 
+macro_rules! impl_SRGBPixel {
+  ($t:ty) => {
+    unsafe impl SRGBPixel for $t {}
+  }
+}
+
 /// A red 8-bit signed integral pixel format.
 #[derive(Clone, Copy, Debug)]
 pub struct R8I;
@@ -300,6 +359,17 @@ impl_Pixel!(RGB32F, (f32, f32, f32), f32, Type::Floating, Format::RGB(Size::Thir
 impl_ColorPixel!(RGB32F);
 impl_RenderablePixel!(RGB32F);
 
+/// A red and green 11-bit and blue 10-bit unsigned floating pixel format.
+///
+/// This is a cheap format for *HDR* color buffers which don’t need alpha. Texels are packed into a
+/// single `u32` – blue in the most significant bits, red in the least significant ones.
+#[derive(Clone, Copy, Debug)]
+pub struct R11FG11FB10F;
+
+impl_Pixel!(R11FG11FB10F, u32, u32, Type::Floating, Format::RGB(Size::Eleven, Size::Eleven, Size::Ten));
+impl_ColorPixel!(R11FG11FB10F);
+impl_RenderablePixel!(R11FG11FB10F);
+
 // --------------------
 
 /// A red, green, blue and alpha 8-bit signed integral pixel format.
@@ -362,9 +432,59 @@ impl_Pixel!(RGBA32F, (f32, f32, f32, f32), f32, Type::Floating, Format::RGBA(Siz
 impl_ColorPixel!(RGBA32F);
 impl_RenderablePixel!(RGBA32F);
 
+/// A red, green and blue 10-bit and alpha 2-bit unsigned normalized pixel format.
+///
+/// Texels are packed into a single `u32` – alpha in the most significant bits, red in the least
+/// significant ones.
+#[derive(Clone, Copy, Debug)]
+pub struct RGB10A2;
+
+impl_Pixel!(RGB10A2, u32, u32, Type::NormUnsigned, Format::RGBA(Size::Ten, Size::Ten, Size::Ten, Size::Two));
+impl_ColorPixel!(RGB10A2);
+impl_RenderablePixel!(RGB10A2);
+
 // --------------------
 
+/// A red, green and blue 8-bit sRGB-encoded pixel format.
+///
+/// Shaders sampling such a texture get linear values: the sRGB decoding is done by the GPU.
+#[derive(Clone, Copy, Debug)]
+pub struct SRGB8;
+
+impl_Pixel!(SRGB8, (u8, u8, u8), u8, Type::NormUnsigned, Format::SRGB(Size::Eight, Size::Eight, Size::Eight));
+impl_ColorPixel!(SRGB8);
+impl_SRGBPixel!(SRGB8);
+
+/// A red, green and blue 8-bit sRGB-encoded and alpha 8-bit linear pixel format.
+///
+/// Shaders sampling such a texture get linear values: the sRGB decoding is done by the GPU.
+#[derive(Clone, Copy, Debug)]
+pub struct SRGBA8;
+
+impl_Pixel!(SRGBA8, (u8, u8, u8, u8), u8, Type::NormUnsigned, Format::SRGBA(Size::Eight, Size::Eight, Size::Eight, Size::Eight));
+impl_ColorPixel!(SRGBA8);
+impl_RenderablePixel!(SRGBA8);
+impl_SRGBPixel!(SRGBA8);
+
+// --------------------
+
+/// A depth 24-bit normalized unsigned pixel format.
+///
+/// Depths are stored as fixed-point values, evenly spread over *[0;1]*. That’s the most widely
+/// supported depth format and it’s typically packed with an 8-bit stencil buffer by the
+/// implementation, making it cheaper in memory and bandwidth than `Depth32F`. Read back, a depth
+/// is an unsigned integer normalized over `0xffffffff`.
+#[derive(Clone, Copy, Debug)]
+pub struct Depth24;
+
+impl_Pixel!(Depth24, u32, u32, Type::NormUnsigned, Format::Depth(Size::TwentyFour));
+impl_DepthPixel!(Depth24);
+
 /// A depth 32-bit floating pixel format.
+///
+/// Floating-point depths have more precision close to `0.`, which mitigates the loss of precision
+/// of perspective projections far from the camera when combined with a *reversed* depth range.
+/// Prefer that format for shadow maps and large scenes, and `Depth24` when memory matters.
 #[derive(Clone, Copy, Debug)]
 pub struct Depth32F;
 
@@ -397,6 +517,7 @@ pub(crate) fn opengl_pixel_format(pf: PixelFormat) -> Option<(GLenum, GLenum, GL
     (Format::RGB(Size::ThirtyTwo, Size::ThirtyTwo, Size::ThirtyTwo), Type::Integral) => Some((gl::RGB_INTEGER, gl::RGB32I, gl::INT)),
     (Format::RGB(Size::ThirtyTwo, Size::ThirtyTwo, Size::ThirtyTwo), Type::Unsigned) => Some((gl::RGB_INTEGER, gl::RGB32UI, gl::UNSIGNED_INT)),
     (Format::RGB(Size::ThirtyTwo, Size::ThirtyTwo, Size::ThirtyTwo), Type::Floating) => Some((gl::RGB, gl::RGB32F, gl::FLOAT)),
+    (Format::RGB(Size::Eleven, Size::Eleven, Size::Ten), Type::Floating) => Some((gl::RGB, gl::R11F_G11F_B10F, gl::UNSIGNED_INT_10F_11F_11F_REV)),
 
     (Format::RGBA(Size::Eight, Size::Eight, Size::Eight, Size::Eight), Type::Integral) => Some((gl::RGBA_INTEGER, gl::RGBA8I, gl::BYTE)),
     (Format::RGBA(Size::Eight, Size::Eight, Size::Eight, Size::Eight), Type::Unsigned) => Some((gl::RGBA_INTEGER, gl::RGBA8UI, gl::UNSIGNED_BYTE)),
@@ -405,7 +526,12 @@ pub(crate) fn opengl_pixel_format(pf: PixelFormat) -> Option<(GLenum, GLenum, GL
     (Format::RGBA(Size::ThirtyTwo, Size::ThirtyTwo, Size::ThirtyTwo, Size::ThirtyTwo), Type::Integral) => Some((gl::RGBA_INTEGER, gl::RGBA32I, gl::INT)),
     (Format::RGBA(Size::ThirtyTwo, Size::ThirtyTwo, Size::ThirtyTwo, Size::ThirtyTwo), Type::Unsigned) => Some((gl::RGBA_INTEGER, gl::RGBA32UI, gl::UNSIGNED_INT)),
     (Format::RGBA(Size::ThirtyTwo, Size::ThirtyTwo, Size::ThirtyTwo, Size::ThirtyTwo), Type::Floating) => Some((gl::RGBA, gl::RGBA32F, gl::FLOAT)),
+    (Format::RGBA(Size::Ten, Size::Ten, Size::Ten, Size::Two), Type::NormUnsigned) => Some((gl::RGBA, gl::RGB10_A2, gl::UNSIGNED_INT_2_10_10_10_REV)),
+
+    (Format::SRGB(Size::Eight, Size::Eight, Size::Eight), Type::NormUnsigned) => Some((gl::RGB, gl::SRGB8, gl::UNSIGNED_BYTE)),
+    (Format::SRGBA(Size::Eight, Size::Eight, Size::Eight, Size::Eight), Type::NormUnsigned) => Some((gl::RGBA, gl::SRGB8_ALPHA8, gl::UNSIGNED_BYTE)),
 
+    (Format::Depth(Size::TwentyFour), Type::NormUnsigned) => Some((gl::DEPTH_COMPONENT, gl::DEPTH_COMPONENT24, gl::UNSIGNED_INT)),
     (Format::Depth(Size::ThirtyTwo), Type::Floating) => Some((gl::DEPTH_COMPONENT, gl::DEPTH_COMPONENT32F, gl::FLOAT)),
 
     _ => panic!("unsupported pixel format {:?}", pf)
@@ -415,8 +541,13 @@ pub(crate) fn opengl_pixel_format(pf: PixelFormat) -> Option<(GLenum, GLenum, GL
 // Return the number of components.
 pub(crate) fn pixel_components(pf: PixelFormat) -> usize {
   match pf.format {
+    // packed formats
+    Format::RGB(Size::Eleven, Size::Eleven, Size::Ten) => 1,
+    Format::RGBA(Size::Ten, Size::Ten, Size::Ten, Size::Two) => 1,
     Format::RGB(_, _, _) => 3,
     Format::RGBA(_, _, _, _) => 4,
+    Format::SRGB(_, _, _) => 3,
+    Format::SRGBA(_, _, _, _) => 4,
     Format::Depth(_) => 1,
     _ => panic!("unsupported pixel format")
   }
@@ -0,0 +1,20 @@
+//! Polygon mode related features.
+//!
+//! The polygon mode tells how polygons are rasterized: filled, as their edges only – also known as
+//! *wireframe* – or as their vertices only. That’s handy to debug geometry without changing the
+//! shaders.
+//!
+//! > Note: core OpenGL only supports setting the polygon mode of both the front and the back faces
+//! > at once (`GL_FRONT_AND_BACK`), so there’s no per-face control. Use face culling to hide one
+//! > side.
+
+/// Rasterization mode of polygons.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PolygonMode {
+  /// Polygons are filled. This is the default.
+  Fill,
+  /// Only the edges of polygons are rasterized, as lines.
+  Line,
+  /// Only the vertices of polygons are rasterized, as points.
+  Point
+}
@@ -0,0 +1,14 @@
+//! Provoking vertex related features.
+//!
+//! When flat shading is used (i.e. `flat` interpolation qualifier in shaders), the value of an
+//! output attribute is not interpolated: the whole primitive gets the value of a single vertex,
+//! called the *provoking vertex*.
+
+/// Vertex to use as provoking vertex for flat shading.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ProvokingVertex {
+  /// The first vertex of each primitive is the provoking vertex.
+  First,
+  /// The last vertex of each primitive is the provoking vertex. This is the default.
+  Last
+}
@@ -0,0 +1,348 @@
+//! Asynchronous readback.
+//!
+//! Reading back GPU data – with `Buffer::whole` or `Framebuffer::read_pixels`, for instance –
+//! blocks until the GPU has executed all the commands writing that data, which stalls both the CPU
+//! and the GPU. An `AsyncReadback` instead copies the data into a GPU buffer of its own and lets
+//! you poll for the result later, once the copy is done:
+//!
+//! ```ignore
+//! let mut readback = AsyncReadback::<u32>::new(&mut surface);
+//!
+//! loop {
+//!   // render the identifiers of the objects, then request the one under the cursor
+//!   readback.read_pixels::<_, _, _, _, _, R32UI>(&mut surface, &picking_buffer, 0, cursor_region);
+//!
+//!   // picks requested in previous frames, if they have arrived
+//!   while let Some(ids) = readback.try_read() {
+//!     // …
+//!   }
+//! }
+//! ```
+//!
+//! Results are handed back in the order they were requested. Two transfers can be in flight at
+//! once: requesting a third one discards the oldest one, which result is then never read. That’s
+//! what you want for per-frame data, like picking – only the latest results matter. If a frame
+//! typically takes longer than the GPU to catch up, read the results every frame and none is lost.
+
+use gl;
+use gl::types::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+
+use buffer::{Buffer, BufferError, Pod};
+use context::GraphicsContext;
+use framebuffer::{ColorSlot, DepthSlot, Framebuffer, Region};
+use pixel::{Pixel, pixel_components};
+use state::GraphicsState;
+use texture::{Dimensionable, Layerable};
+
+// Number of transfers that can be in flight at once.
+const SLOT_NB: usize = 2;
+
+/// A helper reading back GPU data without blocking.
+///
+/// The copied bytes are handed back as values of `T`, which must then be plain old data – see
+/// `Pod`. See the documentation of the module for further details.
+pub struct AsyncReadback<T> {
+  slots: [Slot; SLOT_NB],
+  queue: Queue,
+  state: Rc<RefCell<GraphicsState>>,
+  _t: PhantomData<T>
+}
+
+// A GPU buffer receiving a transfer.
+struct Slot {
+  handle: GLuint,
+  capacity: usize, // in bytes
+  len: usize, // number of values of the transfer
+  fence: GLsync // null when no transfer is in flight
+}
+
+impl<T> AsyncReadback<T> where T: Pod {
+  /// Create a new readback helper.
+  ///
+  /// No GPU memory is allocated until the first transfer.
+  pub fn new<C>(ctx: &mut C) -> Self where C: GraphicsContext {
+    let mut handles = [0; SLOT_NB];
+
+    unsafe { gl::GenBuffers(SLOT_NB as GLsizei, handles.as_mut_ptr()) };
+
+    let slot = |handle| Slot {
+      handle,
+      capacity: 0,
+      len: 0,
+      fence: ptr::null()
+    };
+
+    AsyncReadback {
+      slots: [slot(handles[0]), slot(handles[1])],
+      queue: Queue::new(),
+      state: ctx.state().clone(),
+      _t: PhantomData
+    }
+  }
+
+  /// Number of transfers in flight – i.e. requested but not read yet.
+  pub fn pending(&self) -> usize {
+    self.queue.pending.len()
+  }
+
+  /// Request a copy of the whole content of a buffer.
+  ///
+  /// Fails with `BufferError::AlreadyMapped` if a slice currently maps the buffer.
+  pub fn read_buffer(&mut self, buffer: &Buffer<T>) -> Result<(), BufferError> {
+    buffer.check_unmapped()?;
+
+    let len = buffer.len();
+    let bytes = len * mem::size_of::<T>();
+    let slot = self.reserve_slot(len);
+
+    // the copy targets are not cached in the graphics state: they don’t affect rendering and are
+    // always bound right before being used
+    unsafe {
+      gl::BindBuffer(gl::COPY_READ_BUFFER, buffer.handle());
+      gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.slots[slot].handle);
+      gl::CopyBufferSubData(gl::COPY_READ_BUFFER, gl::COPY_WRITE_BUFFER, 0, 0, bytes as GLsizeiptr);
+    }
+
+    self.submit(slot);
+    Ok(())
+  }
+
+  /// Request a copy of the raw pixels of a region of a color buffer of a framebuffer.
+  ///
+  /// The pixels are laid out as with `Framebuffer::read_pixels`, and the same rules apply to
+  /// `index` and `P`.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if there’s no color buffer at `index` or if its pixel format is not the
+  /// one of `P`.
+  pub fn read_pixels<C, L, D, CS, DS, P>(
+    &mut self,
+    ctx: &mut C,
+    framebuffer: &Framebuffer<L, D, CS, DS>,
+    index: usize,
+    region: Region
+  )
+  where C: GraphicsContext,
+        L: Layerable,
+        D: Dimensionable,
+        CS: ColorSlot<L, D>,
+        DS: DepthSlot<L, D>,
+        P: Pixel<RawEncoding = T> {
+    let len = region.width as usize * region.height as usize * pixel_components(P::pixel_format());
+    let slot = self.reserve_slot(len);
+
+    unsafe {
+      // with a pixel pack buffer bound, ReadPixels writes into it rather than into client memory;
+      // like the copy targets, that binding is not cached in the graphics state
+      gl::BindBuffer(gl::PIXEL_PACK_BUFFER, self.slots[slot].handle);
+      framebuffer.read_pixels_into::<C, P>(ctx, index, region, ptr::null_mut());
+      gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+    }
+
+    self.submit(slot);
+  }
+
+  /// Get the result of the oldest transfer in flight, if it’s done.
+  ///
+  /// This function never blocks: it returns `None` if the GPU hasn’t completed the transfer yet – or
+  /// if there’s no transfer in flight. If the result cannot be mapped, the transfer stays in flight
+  /// and the next call tries again.
+  pub fn try_read(&mut self) -> Option<Vec<T>> {
+    let slots = &mut self.slots;
+    let state = &self.state;
+
+    self.queue.poll(|slot_index| unsafe {
+      let slot = &mut slots[slot_index];
+      let mut status = gl::UNSIGNALED as GLint;
+      gl::GetSynciv(slot.fence, gl::SYNC_STATUS, 1, ptr::null_mut(), &mut status);
+
+      if status != gl::SIGNALED as GLint {
+        return None;
+      }
+
+      let len = slot.len;
+      let mut values = Vec::with_capacity(len);
+
+      if len > 0 {
+        state.borrow_mut().bind_array_buffer(slot.handle);
+        let ptr = gl::MapBufferRange(
+          gl::ARRAY_BUFFER,
+          0,
+          (len * mem::size_of::<T>()) as GLsizeiptr,
+          gl::MAP_READ_BIT
+        ) as *const T;
+
+        if ptr.is_null() {
+          return None;
+        }
+
+        // copy out of the mapped memory, which is owned by OpenGL and invalidated once unmapped
+        ptr::copy_nonoverlapping(ptr, values.as_mut_ptr(), len);
+        values.set_len(len);
+
+        let _ = gl::UnmapBuffer(gl::ARRAY_BUFFER);
+      }
+
+      gl::DeleteSync(slot.fence);
+      slot.fence = ptr::null();
+
+      Some(values)
+    })
+  }
+
+  // Pick the slot receiving the next transfer of `len` values, discarding the oldest transfer if
+  // they are all in flight, and make sure it’s large enough.
+  fn reserve_slot(&mut self, len: usize) -> usize {
+    let (slot_index, discarded) = self.queue.reserve();
+
+    if let Some(oldest) = discarded {
+      let slot = &mut self.slots[oldest];
+
+      unsafe { gl::DeleteSync(slot.fence) };
+      slot.fence = ptr::null();
+    }
+
+    let slot = &mut self.slots[slot_index];
+    let bytes = len * mem::size_of::<T>();
+
+    if bytes > slot.capacity {
+      unsafe {
+        self.state.borrow_mut().bind_array_buffer(slot.handle);
+        gl::BufferData(gl::ARRAY_BUFFER, bytes as GLsizeiptr, ptr::null(), gl::STREAM_READ);
+      }
+
+      slot.capacity = bytes;
+    }
+
+    slot.len = len;
+    slot_index
+  }
+
+  // Insert a fence after the transfer into a slot and mark it in flight.
+  fn submit(&mut self, slot_index: usize) {
+    self.slots[slot_index].fence = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+    self.queue.push(slot_index);
+  }
+}
+
+// Order of the transfers in flight, kept apart from the OpenGL objects of the slots.
+struct Queue {
+  // indices of the slots holding a transfer in flight, from the oldest to the newest
+  pending: VecDeque<usize>
+}
+
+impl Queue {
+  fn new() -> Self {
+    Queue {
+      pending: VecDeque::with_capacity(SLOT_NB)
+    }
+  }
+
+  // Pick a free slot for a new transfer. If every slot is in flight, the oldest transfer is
+  // discarded and its slot is returned as well.
+  fn reserve(&mut self) -> (usize, Option<usize>) {
+    let discarded = if self.pending.len() == SLOT_NB {
+      self.pending.pop_front()
+    } else {
+      None
+    };
+
+    let slot_index = (0..SLOT_NB).find(|i| !self.pending.contains(i)).unwrap();
+    (slot_index, discarded)
+  }
+
+  // Mark a slot in flight.
+  fn push(&mut self, slot_index: usize) {
+    self.pending.push_back(slot_index);
+  }
+
+  // Read the oldest transfer in flight with `read`, which returns `None` if the result is not
+  // available yet. The transfer leaves the queue only once it’s been read.
+  fn poll<F, R>(&mut self, read: F) -> Option<R> where F: FnOnce(usize) -> Option<R> {
+    let result = read(*self.pending.front()?)?;
+    self.pending.pop_front();
+    Some(result)
+  }
+}
+
+impl<T> Drop for AsyncReadback<T> {
+  fn drop(&mut self) {
+    unsafe {
+      for slot in &self.slots {
+        if !slot.fence.is_null() {
+          gl::DeleteSync(slot.fence);
+        }
+
+        gl::DeleteBuffers(1, &slot.handle);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn poll_until_ready() {
+    let mut queue = Queue::new();
+    let (slot, discarded) = queue.reserve();
+    queue.push(slot);
+
+    assert_eq!(discarded, None);
+
+    // the transfer is not done yet – or its result couldn’t be mapped – so it stays in flight
+    let mut polls = 0;
+    let result = loop {
+      polls += 1;
+
+      if let Some(result) = queue.poll(|i| if polls < 3 { None } else { Some(i) }) {
+        break result;
+      }
+
+      assert_eq!(queue.pending.len(), 1);
+    };
+
+    assert_eq!(result, slot);
+    assert_eq!(polls, 3);
+    assert!(queue.pending.is_empty());
+    assert_eq!(queue.poll(Some), None);
+  }
+
+  #[test]
+  fn results_in_request_order() {
+    let mut queue = Queue::new();
+    let (a, _) = queue.reserve();
+    queue.push(a);
+    let (b, _) = queue.reserve();
+    queue.push(b);
+
+    assert_ne!(a, b);
+    assert_eq!(queue.poll(Some), Some(a));
+    assert_eq!(queue.poll(Some), Some(b));
+  }
+
+  #[test]
+  fn oldest_transfer_discarded() {
+    let mut queue = Queue::new();
+
+    for _ in 0..SLOT_NB {
+      let (slot, _) = queue.reserve();
+      queue.push(slot);
+    }
+
+    let oldest = queue.pending[0];
+    let (slot, discarded) = queue.reserve();
+
+    assert_eq!(discarded, Some(oldest));
+    assert_eq!(slot, oldest);
+    assert_eq!(queue.pending.len(), SLOT_NB - 1);
+  }
+}
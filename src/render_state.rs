@@ -1,35 +1,113 @@
 //! GPU render state.
 //!
 //! Such a state controls how the GPU must operate some fixed pipeline functionality, such as the
-//! blending, depth test or face culling operations.
+//! blending, color mask, depth test, depth bias, stencil test, scissor test, face culling,
+//! provoking vertex, polygon mode, line width or point size operations.
+//!
+//! Some combinations of those operations are not obvious to get right; `RenderState` provides
+//! presets for them, such as `RenderState::skybox` or `RenderState::transparent`.
 
-use blending::{Equation, Factor};
-use depth_test::DepthTest;
-use face_culling::FaceCulling;
+use blending::{BlendingState, Equation, Factor};
+use depth_bias::DepthBias;
+use depth_test::{DepthComparison, DepthTest, DepthWrite};
+use face_culling::{FaceCulling, FaceCullingMode, FaceCullingOrder, FaceCullingState};
+use framebuffer::Region;
+use gl::types::*;
+use polygon_mode::PolygonMode;
+use provoking_vertex::ProvokingVertex;
+use state::GraphicsState;
+use stencil::{StencilTest, StencilTestState};
 
 /// GPU render state.
 ///
 /// You can get a default value with `RenderState::default` and set the operations you want with the
 /// various `RenderState::set_*` methods.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct RenderState {
   pub(crate) blending: Option<(Equation, Factor, Factor)>,
+  pub(crate) alpha_blending: Option<(Equation, Factor, Factor)>,
+  pub(crate) blending_constant: [f32; 4],
+  pub(crate) color_mask: [bool; 4],
   pub(crate) depth_test: DepthTest,
-  pub(crate) face_culling: Option<FaceCulling>
+  pub(crate) depth_comparison: DepthComparison,
+  pub(crate) depth_write: DepthWrite,
+  pub(crate) depth_bias: Option<DepthBias>,
+  pub(crate) stencil_test: Option<StencilTest>,
+  pub(crate) scissor: Option<Region>,
+  pub(crate) face_culling: Option<FaceCulling>,
+  pub(crate) provoking_vertex: ProvokingVertex,
+  pub(crate) polygon_mode: PolygonMode,
+  pub(crate) line_width: f32,
+  pub(crate) point_size: Option<f32>
 }
 
 impl RenderState {
+  /// Set the blending equation and factors, for all the channels, or disable blending with `None`.
   pub fn set_blending<B>(self, blending: B) -> Self where B: Into<Option<(Equation, Factor, Factor)>> {
+    let blending = blending.into();
+
+    RenderState {
+      blending,
+      alpha_blending: blending,
+      .. self
+    }
+  }
+
+  /// Set separate blending equations and factors for the RGB channels and the alpha channel.
+  ///
+  /// This is typically required when compositing premultiplied-alpha images, which alpha channel
+  /// must not be blended the same way as their color.
+  pub fn set_blending_separate(
+    self,
+    rgb: (Equation, Factor, Factor),
+    alpha: (Equation, Factor, Factor)
+  ) -> Self {
     RenderState {
-      blending: blending.into(),
+      blending: Some(rgb),
+      alpha_blending: Some(alpha),
       .. self
     }
   }
 
+  /// Blending equation and factors of the RGB channels.
   pub fn blending(&self) -> Option<(Equation, Factor, Factor)> {
     self.blending
   }
 
+  /// Blending equation and factors of the alpha channel.
+  pub fn alpha_blending(&self) -> Option<(Equation, Factor, Factor)> {
+    self.alpha_blending
+  }
+
+  /// Set the blending constant, used by the `Factor::Constant*` blending factors.
+  ///
+  /// The constant is only sent to the GPU when the blending factors refer to it.
+  pub fn set_blending_constant(self, blending_constant: [f32; 4]) -> Self {
+    RenderState {
+      blending_constant,
+      .. self
+    }
+  }
+
+  pub fn blending_constant(&self) -> [f32; 4] {
+    self.blending_constant
+  }
+
+  /// Set which of the red, green, blue and alpha channels are written to the color buffers.
+  ///
+  /// Masking all the channels is a *depth-only* pass: fragments still go through the depth test
+  /// and write their depth, if enabled.
+  pub fn set_color_mask(self, color_mask: [bool; 4]) -> Self {
+    RenderState {
+      color_mask,
+      .. self
+    }
+  }
+
+  pub fn color_mask(&self) -> [bool; 4] {
+    self.color_mask
+  }
+
   pub fn set_depth_test(self, depth_test: DepthTest) -> Self {
     RenderState {
       depth_test,
@@ -41,6 +119,71 @@ impl RenderState {
     self.depth_test
   }
 
+  pub fn set_depth_comparison(self, depth_comparison: DepthComparison) -> Self {
+    RenderState {
+      depth_comparison,
+      .. self
+    }
+  }
+
+  pub fn depth_comparison(&self) -> DepthComparison {
+    self.depth_comparison
+  }
+
+  /// Set whether fragments passing the depth test write their depth.
+  ///
+  /// Depth writes are independent from the depth test: disabling them while keeping the test
+  /// enabled makes fragments hidden by what’s already in the depth buffer get discarded without
+  /// hiding anything themselves. See `RenderState::transparent`.
+  pub fn set_depth_write(self, depth_write: DepthWrite) -> Self {
+    RenderState {
+      depth_write,
+      .. self
+    }
+  }
+
+  pub fn depth_write(&self) -> DepthWrite {
+    self.depth_write
+  }
+
+  pub fn set_depth_bias<DB>(self, depth_bias: DB) -> Self where DB: Into<Option<DepthBias>> {
+    RenderState {
+      depth_bias: depth_bias.into(),
+      .. self
+    }
+  }
+
+  pub fn depth_bias(&self) -> Option<DepthBias> {
+    self.depth_bias
+  }
+
+  /// Set the stencil test, or disable it with `None`.
+  pub fn set_stencil_test<ST>(self, stencil_test: ST) -> Self where ST: Into<Option<StencilTest>> {
+    RenderState {
+      stencil_test: stencil_test.into(),
+      .. self
+    }
+  }
+
+  pub fn stencil_test(&self) -> Option<StencilTest> {
+    self.stencil_test
+  }
+
+  /// Set the scissor region, or disable the scissor test with `None`.
+  ///
+  /// When enabled, only the fragments lying in the region – in pixels, from the lower-left corner
+  /// of the framebuffer – are rendered.
+  pub fn set_scissor<S>(self, scissor: S) -> Self where S: Into<Option<Region>> {
+    RenderState {
+      scissor: scissor.into(),
+      .. self
+    }
+  }
+
+  pub fn scissor(&self) -> Option<Region> {
+    self.scissor
+  }
+
   pub fn set_face_culling<FC>(self, face_culling: FC) -> Self where FC: Into<Option<FaceCulling>> {
     RenderState {
       face_culling: face_culling.into(),
@@ -51,19 +194,210 @@ impl RenderState {
   pub fn face_culling(&self) -> Option<FaceCulling> {
     self.face_culling
   }
+
+  pub fn set_provoking_vertex(self, provoking_vertex: ProvokingVertex) -> Self {
+    RenderState {
+      provoking_vertex,
+      .. self
+    }
+  }
+
+  pub fn provoking_vertex(&self) -> ProvokingVertex {
+    self.provoking_vertex
+  }
+
+  /// Set the polygon mode, for both the front and the back faces.
+  pub fn set_polygon_mode(self, polygon_mode: PolygonMode) -> Self {
+    RenderState {
+      polygon_mode,
+      .. self
+    }
+  }
+
+  pub fn polygon_mode(&self) -> PolygonMode {
+    self.polygon_mode
+  }
+
+  /// Set the width of lines, in pixels.
+  ///
+  /// The width is clamped to the range of widths supported by the implementation
+  /// (`GL_ALIASED_LINE_WIDTH_RANGE`). Keep in mind that many core-profile implementations only
+  /// support a width of `1.`, in which case wider lines are silently rendered `1.` wide.
+  pub fn set_line_width(self, line_width: f32) -> Self {
+    RenderState {
+      line_width,
+      .. self
+    }
+  }
+
+  pub fn line_width(&self) -> f32 {
+    self.line_width
+  }
+
+  /// Set the size of points, in pixels, or let the shaders set it through `gl_PointSize` with
+  /// `None`.
+  ///
+  /// The size is clamped to the range of sizes supported by the implementation
+  /// (`GL_POINT_SIZE_RANGE`).
+  pub fn set_point_size<P>(self, point_size: P) -> Self where P: Into<Option<f32>> {
+    RenderState {
+      point_size: point_size.into(),
+      .. self
+    }
+  }
+
+  pub fn point_size(&self) -> Option<f32> {
+    self.point_size
+  }
+
+  /// Render state for skyboxes.
+  ///
+  /// A skybox is an inside-out cube, rendered at the far plane around the camera. This preset:
+  ///
+  ///   - Enables the depth test with `DepthComparison::Always`, so that the skybox is rendered
+  ///     whatever is already in the depth buffer. Disabling the depth test instead would also
+  ///     disable depth writes.
+  ///   - Enables depth writes, so that the skybox writes its far depth.
+  ///   - Culls the front faces, as the camera is inside the cube.
+  ///
+  /// The other operations are the same as `RenderState::default`.
+  pub fn skybox() -> Self {
+    RenderState::default()
+      .set_depth_test(DepthTest::Enabled)
+      .set_depth_comparison(DepthComparison::Always)
+      .set_depth_write(DepthWrite::Enabled)
+      .set_face_culling(FaceCulling::new(FaceCullingOrder::CCW, FaceCullingMode::Front))
+  }
+
+  /// Render state for transparent objects.
+  ///
+  /// Transparent objects are rendered after the opaque ones, which are rendered with depth writes
+  /// enabled – `RenderState::default`, for instance. This preset:
+  ///
+  ///   - Enables the depth test, so that transparent fragments behind opaque objects are
+  ///     discarded.
+  ///   - Disables depth writes, so that transparent objects don’t hide each other.
+  ///   - Blends the fragments with their alpha channel, over what’s already rendered.
+  ///
+  /// Transparent objects are blended in the order they’re rendered: render them from back to front
+  /// for a correct result. Nothing needs to be done afterwards: the next render enables depth writes
+  /// again if its render state asks for it.
+  ///
+  /// The other operations are the same as `RenderState::default`.
+  pub fn transparent() -> Self {
+    RenderState::default()
+      .set_depth_test(DepthTest::Enabled)
+      .set_depth_write(DepthWrite::Disabled)
+      .set_blending((Equation::Additive, Factor::SrcAlpha, Factor::SrcAlphaComplement))
+  }
+}
+
+impl RenderState {
+  // Apply the render state to the graphics state.
+  pub(crate) unsafe fn apply(&self, gfx_state: &mut GraphicsState) {
+    match self.blending {
+      Some((equation, src_factor, dst_factor)) => {
+        let (alpha_equation, alpha_src_factor, alpha_dst_factor) = self.alpha_blending.unwrap_or((equation, src_factor, dst_factor));
+
+        gfx_state.set_blending_state(BlendingState::Enabled);
+        gfx_state.set_blending_equation(equation, alpha_equation);
+        gfx_state.set_blending_func(src_factor, dst_factor, alpha_src_factor, alpha_dst_factor);
+
+        let factors = [src_factor, dst_factor, alpha_src_factor, alpha_dst_factor];
+        if factors.iter().any(|factor| factor.is_constant()) {
+          gfx_state.set_blending_constant(self.blending_constant);
+        }
+      },
+      None => {
+        gfx_state.set_blending_state(BlendingState::Disabled);
+      }
+    }
+
+    gfx_state.set_color_mask(self.color_mask);
+    gfx_state.set_depth_test(self.depth_test);
+    gfx_state.set_depth_comparison(self.depth_comparison);
+    gfx_state.set_depth_write(self.depth_write);
+    gfx_state.set_depth_bias(self.depth_bias);
+
+    match self.stencil_test {
+      Some(stencil_test) => {
+        gfx_state.set_stencil_test_state(StencilTestState::Enabled);
+        gfx_state.set_stencil_func(
+          stencil_test.comparison,
+          GLint::from(stencil_test.reference),
+          GLuint::from(stencil_test.read_mask)
+        );
+        gfx_state.set_stencil_operations(
+          stencil_test.on_stencil_fail,
+          stencil_test.on_depth_fail,
+          stencil_test.on_pass
+        );
+        gfx_state.set_stencil_write_mask(GLuint::from(stencil_test.write_mask));
+      },
+      None => {
+        gfx_state.set_stencil_test_state(StencilTestState::Disabled);
+        // the write mask also applies when clearing the stencil buffer; unmask all the bits so
+        // that the next pipeline fully clears it
+        gfx_state.set_stencil_write_mask(GLuint::MAX);
+      }
+    }
+
+    let scissor = self.scissor.map(|r| [r.x as GLint, r.y as GLint, r.width as GLint, r.height as GLint]);
+    gfx_state.set_scissor(scissor);
+
+    match self.face_culling {
+      Some(face_culling) => {
+        gfx_state.set_face_culling_state(FaceCullingState::Enabled);
+        gfx_state.set_face_culling_order(face_culling.order);
+        gfx_state.set_face_culling_mode(face_culling.mode);
+      },
+      None => {
+        gfx_state.set_face_culling_state(FaceCullingState::Disabled);
+      }
+    }
+
+    gfx_state.set_provoking_vertex(self.provoking_vertex);
+    gfx_state.set_polygon_mode(self.polygon_mode);
+    gfx_state.set_line_width(self.line_width);
+    gfx_state.set_point_size(self.point_size);
+  }
 }
 
 impl Default for RenderState {
   /// The default `RenderState`.
   ///
   ///   - `blending`: `None`
+  ///   - `alpha_blending`: `None`
+  ///   - `blending_constant`: `[0., 0., 0., 0.]`
+  ///   - `color_mask`: `[true; 4]`
   ///   - `depth_test`: `DepthTest::Enabled`
+  ///   - `depth_comparison`: `DepthComparison::Less`
+  ///   - `depth_write`: `DepthWrite::Enabled`
+  ///   - `depth_bias`: `None`
+  ///   - `stencil_test`: `None`
+  ///   - `scissor`: `None`
   ///   - `face_culling`: `None`
+  ///   - `provoking_vertex`: `ProvokingVertex::Last`
+  ///   - `polygon_mode`: `PolygonMode::Fill`
+  ///   - `line_width`: `1.`
+  ///   - `point_size`: `Some(1.)`
   fn default() -> Self {
     RenderState {
       blending: None,
+      alpha_blending: None,
+      blending_constant: [0., 0., 0., 0.],
+      color_mask: [true; 4],
       depth_test: DepthTest::Enabled,
+      depth_comparison: DepthComparison::Less,
+      depth_write: DepthWrite::Enabled,
+      depth_bias: None,
+      stencil_test: None,
+      scissor: None,
       face_culling: None,
+      provoking_vertex: ProvokingVertex::Last,
+      polygon_mode: PolygonMode::Fill,
+      line_width: 1.,
+      point_size: Some(1.),
     }
   }
 }
@@ -0,0 +1,177 @@
+//! Asynchronous shader program compilation.
+//!
+//! Compiling and linking shader programs might take a lot of time, especially when you have a lot
+//! of them. A `ProgramBuilder` lets you issue the compilation of several programs at once, do
+//! something else while the driver compiles them and get them back when they’re ready.
+//!
+//! ```ignore
+//! let mut builder = ProgramBuilder::new();
+//! builder.add(None, VS_A, None, FS_A);
+//! builder.add(None, VS_B, None, FS_B);
+//!
+//! while builder.poll() < builder.len() {
+//!   // load textures, meshes, etc.
+//! }
+//!
+//! let mut programs = builder.finish().into_iter();
+//! let (program_a, _) = Program::<Vertex, (), ()>::from_raw(programs.next().unwrap()?)?;
+//! let (program_b, _) = Program::<Vertex, (), ()>::from_raw(programs.next().unwrap()?)?;
+//! ```
+//!
+//! Compilation is only really asynchronous if the driver supports the
+//! `GL_KHR_parallel_shader_compile` or `GL_ARB_parallel_shader_compile` extension. If it doesn’t,
+//! `ProgramBuilder::poll` considers all the programs done and `ProgramBuilder::finish` waits for
+//! them to be compiled and linked.
+
+use gl;
+use gl::types::*;
+
+use shader::program::{ProgramError, RawProgram};
+use shader::stage::{self, Stage};
+use state::has_extension;
+
+// COMPLETION_STATUS_KHR / COMPLETION_STATUS_ARB; not part of the core bindings.
+const COMPLETION_STATUS: GLenum = 0x91B1;
+
+/// Builder of shader programs compiling them asynchronously.
+pub struct ProgramBuilder {
+  pending: Vec<Result<PendingProgram, ProgramError>>,
+  parallel: bool
+}
+
+// A program which compilation and link have been issued.
+struct PendingProgram {
+  handle: GLuint,
+  stages: Vec<Stage>
+}
+
+impl ProgramBuilder {
+  /// Create a new, empty builder.
+  pub fn new() -> Self {
+    ProgramBuilder {
+      pending: Vec::new(),
+      parallel: has_parallel_shader_compile()
+    }
+  }
+
+  /// Whether the driver compiles the programs asynchronously.
+  pub fn is_parallel(&self) -> bool {
+    self.parallel
+  }
+
+  /// Number of programs added to the builder.
+  pub fn len(&self) -> usize {
+    self.pending.len()
+  }
+
+  /// Whether no program was added to the builder.
+  pub fn is_empty(&self) -> bool {
+    self.pending.is_empty()
+  }
+
+  /// Issue the compilation and link of a new program.
+  ///
+  /// The arguments are the same as in `Program::from_strings`. The returned value is the index of
+  /// the program in the list returned by `ProgramBuilder::finish`.
+  pub fn add<'a, T, G>(
+    &mut self,
+    tess: T,
+    vertex: &str,
+    geometry: G,
+    fragment: &str
+  ) -> usize
+  where T: Into<Option<(&'a str, &'a str)>>,
+        G: Into<Option<&'a str>> {
+    let pending = compile(tess.into(), vertex, geometry.into(), fragment);
+    self.pending.push(pending);
+    self.pending.len() - 1
+  }
+
+  /// Get the number of programs which compilation and link are complete.
+  ///
+  /// This function never blocks.
+  pub fn poll(&self) -> usize {
+    if !self.parallel {
+      return self.pending.len();
+    }
+
+    self.pending.iter().filter(|pending| {
+      match **pending {
+        Ok(ref pending) => {
+          let mut done: GLint = gl::FALSE as GLint;
+          unsafe { gl::GetProgramiv(pending.handle, COMPLETION_STATUS, &mut done) };
+          done == gl::TRUE as GLint
+        }
+
+        Err(_) => true
+      }
+    }).count()
+  }
+
+  /// Get the programs back, in the order they were added.
+  ///
+  /// This function blocks until all the programs are compiled and linked.
+  pub fn finish(self) -> Vec<Result<RawProgram, ProgramError>> {
+    self.pending.into_iter().map(|pending| {
+      let pending = pending?;
+      let handle = pending.handle;
+
+      // check the stages first to get a meaningful error
+      for stage in pending.stages {
+        if let Err(e) = stage.check() {
+          unsafe { gl::DeleteProgram(handle) };
+          return Err(ProgramError::StageError(e));
+        }
+      }
+
+      RawProgram::check(handle)
+    }).collect()
+  }
+}
+
+impl Default for ProgramBuilder {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+fn compile(
+  tess: Option<(&str, &str)>,
+  vertex: &str,
+  geometry: Option<&str>,
+  fragment: &str
+) -> Result<PendingProgram, ProgramError> {
+  let tess = match tess {
+    Some((tcs_str, tes_str)) => {
+      let tcs = Stage::compile(stage::Type::TessellationControlShader, tcs_str).map_err(ProgramError::StageError)?;
+      let tes = Stage::compile(stage::Type::TessellationEvaluationShader, tes_str).map_err(ProgramError::StageError)?;
+      Some((tcs, tes))
+    },
+    None => None
+  };
+
+  let gs = match geometry {
+    Some(gs_str) => Some(Stage::compile(stage::Type::GeometryShader, gs_str).map_err(ProgramError::StageError)?),
+    None => None
+  };
+
+  let vs = Stage::compile(stage::Type::VertexShader, vertex).map_err(ProgramError::StageError)?;
+  let fs = Stage::compile(stage::Type::FragmentShader, fragment).map_err(ProgramError::StageError)?;
+
+  let handle = RawProgram::link(tess.as_ref().map(|(tcs, tes)| (tcs, tes)), &vs, gs.as_ref(), &fs, &[]);
+
+  let mut stages = vec![vs, fs];
+  stages.extend(gs);
+
+  if let Some((tcs, tes)) = tess {
+    stages.push(tcs);
+    stages.push(tes);
+  }
+
+  Ok(PendingProgram { handle, stages })
+}
+
+// Check whether the driver supports parallel shader compilation.
+fn has_parallel_shader_compile() -> bool {
+  has_extension(&[b"GL_KHR_parallel_shader_compile", b"GL_ARB_parallel_shader_compile"])
+}
@@ -0,0 +1,143 @@
+//! Compute programs.
+//!
+//! A compute program runs a single compute shader stage on the GPU, outside of any rendering
+//! pipeline. Work is issued as a grid of *workgroups*, which size is declared in the shader with
+//! the `local_size_*` layout qualifiers.
+//!
+//! ```ignore
+//! let (program, _) = ComputeProgram::<ParticleInterface>::from_string(CS)?;
+//!
+//! program.dispatch(&mut ctx, [particle_nb / 64, 1, 1], |iface| {
+//!   iface.dt.update(dt);
+//! });
+//! ```
+//!
+//! # Indirect dispatch
+//!
+//! When the number of workgroups is computed on the GPU – by a previous compute pass, for
+//! instance – you can dispatch with `ComputeProgram::dispatch_indirect`, which reads the number of
+//! workgroups from a buffer of `DispatchIndirect` commands without any CPU readback.
+
+use gl;
+use gl::types::*;
+use std::mem::size_of;
+use std::ops::Deref;
+
+use buffer::{Buffer, Pod};
+use context::GraphicsContext;
+use shader::program::{ProgramError, RawProgram, UniformInterface, UniformWarning, create_uniform_interface};
+use shader::stage::{self, Stage};
+
+/// A compute program.
+///
+/// `Uni` is the uniform interface of the program.
+pub struct ComputeProgram<Uni> {
+  raw: RawProgram,
+  uni_iface: Uni
+}
+
+impl<Uni> ComputeProgram<Uni> {
+  /// Create a new compute program from a compute shader stage.
+  pub fn from_stage(stage: &Stage) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface {
+    Self::from_stage_env(stage, ())
+  }
+
+  /// Create a new compute program from the source of a compute shader.
+  pub fn from_string(src: &str) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface {
+    Self::from_string_env(src, ())
+  }
+
+  /// Create a new compute program from a compute shader stage and by looking up an environment.
+  pub fn from_stage_env<E>(stage: &Stage, env: E) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface<E> {
+    let raw = unsafe {
+      let handle = gl::CreateProgram();
+      gl::AttachShader(handle, stage.handle());
+      gl::LinkProgram(handle);
+
+      RawProgram::check(handle)?
+    };
+
+    let (uni_iface, warnings) = create_uniform_interface(&raw, env)?;
+
+    Ok((ComputeProgram { raw, uni_iface }, warnings))
+  }
+
+  /// Create a new compute program from the source of a compute shader and by looking up an
+  /// environment.
+  pub fn from_string_env<E>(src: &str, env: E) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface<E> {
+    let stage = Stage::new(stage::Type::ComputeShader, src).map_err(ProgramError::StageError)?;
+    Self::from_stage_env(&stage, env)
+  }
+
+  /// Dispatch a grid of `groups` workgroups.
+  ///
+  /// `f` is called before the dispatch with the uniform interface of the program so that you can
+  /// update the uniforms.
+  pub fn dispatch<C, F>(&self, ctx: &mut C, groups: [u32; 3], f: F)
+  where C: GraphicsContext,
+        F: FnOnce(&Uni) {
+    self.bind(ctx, f);
+
+    unsafe { gl::DispatchCompute(groups[0], groups[1], groups[2]) }
+  }
+
+  /// Dispatch a grid of workgroups which size is read from the `i`-th command of a buffer.
+  ///
+  /// `f` is called before the dispatch with the uniform interface of the program so that you can
+  /// update the uniforms.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if `i` is out of the bounds of `commands`.
+  pub fn dispatch_indirect<C, F>(&self, ctx: &mut C, commands: &Buffer<DispatchIndirect>, i: usize, f: F)
+  where C: GraphicsContext,
+        F: FnOnce(&Uni) {
+    if i >= commands.len() {
+      panic!("cannot dispatch indirect command {} out of a buffer of {} commands", i, commands.len());
+    }
+
+    self.bind(ctx, f);
+
+    unsafe {
+      // make sure the writes to the commands made by shaders are visible to the dispatch
+      gl::MemoryBarrier(gl::COMMAND_BARRIER_BIT);
+      gl::BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, commands.handle());
+      gl::DispatchComputeIndirect((i * size_of::<DispatchIndirect>()) as GLintptr);
+    }
+  }
+
+  // Use the program and let the user update the uniforms.
+  fn bind<C, F>(&self, ctx: &mut C, f: F) where C: GraphicsContext, F: FnOnce(&Uni) {
+    unsafe { ctx.state().borrow_mut().use_program(self.raw.handle(), self.raw.has_tessellation()) };
+    f(&self.uni_iface);
+  }
+}
+
+impl<Uni> Deref for ComputeProgram<Uni> {
+  type Target = RawProgram;
+
+  fn deref(&self) -> &Self::Target {
+    &self.raw
+  }
+}
+
+/// An indirect dispatch command.
+///
+/// Fill a `Buffer<DispatchIndirect>` – from the CPU or from a shader – with such commands and pass
+/// it to `ComputeProgram::dispatch_indirect`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct DispatchIndirect {
+  /// Number of workgroups along the X axis.
+  pub x: u32,
+  /// Number of workgroups along the Y axis.
+  pub y: u32,
+  /// Number of workgroups along the Z axis.
+  pub z: u32
+}
+
+unsafe impl Pod for DispatchIndirect {}
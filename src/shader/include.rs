@@ -0,0 +1,217 @@
+//! GLSL `#include` preprocessing.
+//!
+//! GLSL has no way to share code between shader sources. This module resolves
+//! `#include "path"` directives by inlining the text handed back by a *resolver* – a closure that
+//! maps a path to the content of the file, typically reading it from disk or from a set of
+//! `include_str!`:
+//!
+//! ```ignore
+//! let src = preprocess(FS, |path| match path {
+//!   "lighting.glsl" => Some(LIGHTING.to_owned()),
+//!   _ => None
+//! })?;
+//! ```
+//!
+//! `Program::from_strings_with_includes` preprocesses all the stages of a program that way.
+//!
+//! # Line numbers
+//!
+//! `#line` directives are inserted around the included text so that compilation errors point at
+//! the right line. Because GLSL identifies sources by numbers rather than names, each included
+//! file is given a *source string number*: `0` is the source being preprocessed and the included
+//! files are numbered from `1`, in the order the resolver is first called with them. A compilation
+//! error reported at `2(14)` is then at line `14` of the second file passed to the resolver.
+//!
+//! A file can be included several times; it’s inlined each time. Cyclic includes are an error.
+
+use std::error::Error;
+use std::fmt;
+
+/// Errors that can occur while preprocessing includes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IncludeError {
+  /// The resolver didn’t find the included path.
+  NotFound(String),
+  /// A file includes itself, directly or through other files. The `Vec` holds the chain of
+  /// includes, from the first file of the cycle to the repeated one.
+  Cyclic(Vec<String>),
+  /// An `#include` directive is not followed by a quoted path. The `String` is the directive.
+  Malformed(String)
+}
+
+impl fmt::Display for IncludeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match *self {
+      IncludeError::NotFound(ref path) => write!(f, "cannot find included file {}", path),
+      IncludeError::Cyclic(ref chain) => write!(f, "cyclic include: {}", chain.join(" -> ")),
+      IncludeError::Malformed(ref directive) => write!(f, "malformed include directive: {}", directive)
+    }
+  }
+}
+
+impl Error for IncludeError {}
+
+/// Resolve the `#include` directives of a GLSL source.
+///
+/// See the documentation of the module for further details.
+pub fn preprocess<R>(src: &str, mut resolver: R) -> Result<String, IncludeError>
+where R: FnMut(&str) -> Option<String> {
+  let mut preprocessor = Preprocessor {
+    resolver: &mut resolver,
+    files: Vec::new(),
+    stack: Vec::new(),
+    output: String::new()
+  };
+
+  // the version pragma is inserted before the source; reset the line numbers after it
+  preprocessor.output.push_str("#line 1 0\n");
+  preprocessor.process(src, 0)?;
+
+  Ok(preprocessor.output)
+}
+
+struct Preprocessor<'a> {
+  resolver: &'a mut dyn FnMut(&str) -> Option<String>,
+  // source string numbers of the files included so far: the number of a file is its index + 1
+  files: Vec<String>,
+  // paths of the files being processed
+  stack: Vec<String>,
+  output: String
+}
+
+impl<'a> Preprocessor<'a> {
+  fn process(&mut self, src: &str, source_nb: usize) -> Result<(), IncludeError> {
+    for (i, line) in src.lines().enumerate() {
+      let path = match include_path(line)? {
+        Some(path) => path,
+        None => {
+          self.output.push_str(line);
+          self.output.push('\n');
+          continue;
+        }
+      };
+
+      if let Some(start) = self.stack.iter().position(|p| p == path) {
+        let mut chain = self.stack[start..].to_vec();
+        chain.push(path.to_owned());
+        return Err(IncludeError::Cyclic(chain));
+      }
+
+      let included_nb = match self.files.iter().position(|p| p == path) {
+        Some(index) => index + 1,
+        None => {
+          self.files.push(path.to_owned());
+          self.files.len()
+        }
+      };
+
+      let included = (self.resolver)(path).ok_or_else(|| IncludeError::NotFound(path.to_owned()))?;
+
+      self.stack.push(path.to_owned());
+      self.output.push_str(&format!("#line 1 {}\n", included_nb));
+      self.process(&included, included_nb)?;
+      self.stack.pop();
+
+      // resume at the line following the directive
+      self.output.push_str(&format!("#line {} {}\n", i + 2, source_nb));
+    }
+
+    Ok(())
+  }
+}
+
+// Get the path of an include directive, if the line is one.
+fn include_path(line: &str) -> Result<Option<&str>, IncludeError> {
+  let directive = line.trim_start();
+
+  let rest = match directive.strip_prefix('#') {
+    Some(rest) => rest.trim_start(),
+    None => return Ok(None)
+  };
+
+  let rest = match rest.strip_prefix("include") {
+    Some(rest) => rest.trim(),
+    None => return Ok(None)
+  };
+
+  if rest.len() >= 2 && rest.starts_with('"') && rest.ends_with('"') {
+    Ok(Some(&rest[1..rest.len() - 1]))
+  } else {
+    Err(IncludeError::Malformed(directive.to_owned()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn resolver(path: &str) -> Option<String> {
+    let src = match path {
+      "common.glsl" => "#include \"math.glsl\"\nvec3 common();",
+      "math.glsl" => "float pi();",
+      "self.glsl" => "#include \"self.glsl\"",
+      "a.glsl" => "// a\n#include \"b.glsl\"",
+      "b.glsl" => "#include \"a.glsl\"",
+      "missing.glsl" => "#include \"nowhere.glsl\"",
+      _ => return None
+    };
+
+    Some(src.to_owned())
+  }
+
+  #[test]
+  fn no_include() {
+    let src = "void main() {\n}";
+    assert_eq!(preprocess(src, resolver).unwrap(), "#line 1 0\nvoid main() {\n}\n");
+  }
+
+  #[test]
+  fn nested_includes() {
+    let src = "float x;\n#include \"common.glsl\"\nvoid main() {}\n  #  include \"math.glsl\"  ";
+    let expected = "\
+#line 1 0
+float x;
+#line 1 1
+#line 1 2
+float pi();
+#line 2 1
+vec3 common();
+#line 3 0
+void main() {}
+#line 1 2
+float pi();
+#line 5 0
+";
+
+    assert_eq!(preprocess(src, resolver).unwrap(), expected);
+  }
+
+  #[test]
+  fn direct_cycle() {
+    let err = preprocess("#include \"self.glsl\"", resolver).unwrap_err();
+    assert_eq!(err, IncludeError::Cyclic(vec!["self.glsl".to_owned(), "self.glsl".to_owned()]));
+  }
+
+  #[test]
+  fn indirect_cycle() {
+    let err = preprocess("#include \"a.glsl\"", resolver).unwrap_err();
+    let chain = vec!["a.glsl".to_owned(), "b.glsl".to_owned(), "a.glsl".to_owned()];
+
+    assert_eq!(err, IncludeError::Cyclic(chain));
+  }
+
+  #[test]
+  fn not_found() {
+    let err = preprocess("#include \"missing.glsl\"", resolver).unwrap_err();
+    assert_eq!(err, IncludeError::NotFound("nowhere.glsl".to_owned()));
+  }
+
+  #[test]
+  fn malformed() {
+    let err = preprocess("#include foo", resolver).unwrap_err();
+    assert_eq!(err, IncludeError::Malformed("#include foo".to_owned()));
+
+    let err = preprocess("  #include \"", resolver).unwrap_err();
+    assert_eq!(err, IncludeError::Malformed("#include \"".to_owned()));
+  }
+}
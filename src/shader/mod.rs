@@ -4,5 +4,8 @@
 //! GPU. Everything you need to know can be found in the `program` module. The `stage` modules
 //! contains types and functions that you’ll be linked from the `program` module.
 
+pub mod builder;
+pub mod compute;
+pub mod include;
 pub mod program;
 pub mod stage;
@@ -38,7 +38,9 @@ use std::ops::Deref;
 use std::ptr::null_mut;
 
 use linear::{M22, M33, M44};
+use shader::include::{IncludeError, preprocess};
 use shader::stage::{self, Stage, StageError};
+use state::{gl_version, has_extension};
 use vertex::Vertex;
 
 /// A raw shader program.
@@ -46,7 +48,32 @@ use vertex::Vertex;
 /// This is a type-erased version of a `Program`.
 #[derive(Debug)]
 pub struct RawProgram {
-  handle: GLuint
+  handle: GLuint,
+  tessellation: bool // whether patches can be rendered with the program
+}
+
+// Whether the program which handle is passed as argument has tessellation stages. Only the
+// tessellation evaluation stage is required to render patches.
+//
+// This queries the attached shaders; it’s done once, when the program is linked.
+pub(crate) fn has_tessellation_stages(handle: GLuint) -> bool {
+  if handle == 0 {
+    return false;
+  }
+
+  unsafe {
+    let mut shader_nb: GLint = 0;
+    gl::GetProgramiv(handle, gl::ATTACHED_SHADERS, &mut shader_nb);
+
+    let mut shaders = vec![0; shader_nb as usize];
+    gl::GetAttachedShaders(handle, shader_nb, null_mut(), shaders.as_mut_ptr());
+
+    shaders.into_iter().any(|shader| {
+      let mut ty: GLint = 0;
+      gl::GetShaderiv(shader, gl::SHADER_TYPE, &mut ty);
+      ty as GLenum == gl::TESS_EVALUATION_SHADER
+    })
+  }
 }
 
 impl RawProgram {
@@ -55,13 +82,33 @@ impl RawProgram {
     tess: T,
     vertex: &Stage,
     geometry: G,
-    fragment: &Stage)
+    fragment: &Stage,
+    varyings: &[&str])
   -> Result<Self, ProgramError>
+  where T: Into<Option<(&'a Stage, &'a Stage)>>,
+        G: Into<Option<&'a Stage>> {
+    Self::check(Self::link(tess, vertex, geometry, fragment, varyings))
+  }
+
+  // Issue the link of shader stages without waiting for it to complete.
+  //
+  // The varyings, if any, are captured by transform feedback, interleaved in a single buffer.
+  pub(crate) fn link<'a, T, G>(
+    tess: T,
+    vertex: &Stage,
+    geometry: G,
+    fragment: &Stage,
+    varyings: &[&str])
+  -> GLuint
   where T: Into<Option<(&'a Stage, &'a Stage)>>,
         G: Into<Option<&'a Stage>> {
     unsafe {
       let handle = gl::CreateProgram();
 
+      if binary_supported() {
+        gl::ProgramParameteri(handle, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as GLint);
+      }
+
       if let Some((tcs, tes)) = tess.into() {
         gl::AttachShader(handle, tcs.handle());
         gl::AttachShader(handle, tes.handle());
@@ -75,13 +122,32 @@ impl RawProgram {
 
       gl::AttachShader(handle, fragment.handle());
 
+      if !varyings.is_empty() {
+        let names: Vec<_> = varyings.iter().map(|name| CString::new(*name).unwrap()).collect();
+        let name_ptrs: Vec<_> = names.iter().map(|name| name.as_ptr()).collect();
+
+        gl::TransformFeedbackVaryings(
+          handle,
+          name_ptrs.len() as GLsizei,
+          name_ptrs.as_ptr(),
+          gl::INTERLEAVED_ATTRIBS
+        );
+      }
+
       gl::LinkProgram(handle);
 
+      handle
+    }
+  }
+
+  // Wait for the link of a program to complete and check whether it succeeded.
+  pub(crate) fn check(handle: GLuint) -> Result<Self, ProgramError> {
+    unsafe {
       let mut linked: GLint = gl::FALSE as GLint;
       gl::GetProgramiv(handle, gl::LINK_STATUS, &mut linked);
 
       if linked == (gl::TRUE as GLint) {
-        Ok(RawProgram { handle: handle })
+        Ok(RawProgram { handle, tessellation: has_tessellation_stages(handle) })
       } else {
         let mut log_len: GLint = 0;
         gl::GetProgramiv(handle, gl::INFO_LOG_LENGTH, &mut log_len);
@@ -98,10 +164,134 @@ impl RawProgram {
     }
   }
 
+  /// Create a new program from a binary previously retrieved with `RawProgram::to_binary`.
+  ///
+  /// Drivers are free to reject a binary – typically after a driver update or on another GPU. In
+  /// that case, `ProgramError::BinaryRejected` is returned and you must recreate the program from
+  /// its sources. `ProgramError::BinaryUnsupported` is returned if the implementation doesn’t
+  /// support program binaries.
+  pub fn from_binary(format: GLenum, binary: &[u8]) -> Result<Self, ProgramError> {
+    if !binary_supported() {
+      return Err(ProgramError::BinaryUnsupported);
+    }
+
+    unsafe {
+      let handle = gl::CreateProgram();
+      gl::ProgramBinary(handle, format, binary.as_ptr() as *const _, binary.len() as GLsizei);
+
+      // a rejected binary leaves the program unlinked
+      Self::check(handle).map_err(|e| match e {
+        ProgramError::LinkFailed(log) => ProgramError::BinaryRejected(log),
+        e => e
+      })
+    }
+  }
+
+  /// List the active uniforms of the program.
+  ///
+  /// Only the uniforms participating to the output of the program are listed. Uniforms declared in
+  /// uniform blocks are listed too, with a location of `-1`.
+  pub fn active_uniforms(&self) -> Vec<UniformInfo> {
+    unsafe {
+      let mut nb: GLint = 0;
+      gl::GetProgramiv(self.handle, gl::ACTIVE_UNIFORMS, &mut nb);
+
+      let mut max_len: GLint = 0;
+      gl::GetProgramiv(self.handle, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_len);
+
+      (0..nb as GLuint).map(|index| {
+        let (name, ty, size) = get_active(self.handle, index, max_len, gl::GetActiveUniform);
+        let c_name = CString::new(name.as_bytes()).unwrap();
+        let location = gl::GetUniformLocation(self.handle, c_name.as_ptr() as *const GLchar);
+
+        UniformInfo { name, ty, size, location }
+      }).collect()
+    }
+  }
+
+  /// List the active vertex attributes of the program.
+  ///
+  /// Built-in attributes, such as `gl_VertexID`, are listed with a location of `-1`.
+  pub fn active_attributes(&self) -> Vec<AttributeInfo> {
+    unsafe {
+      let mut nb: GLint = 0;
+      gl::GetProgramiv(self.handle, gl::ACTIVE_ATTRIBUTES, &mut nb);
+
+      let mut max_len: GLint = 0;
+      gl::GetProgramiv(self.handle, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, &mut max_len);
+
+      (0..nb as GLuint).map(|index| {
+        let (name, ty, size) = get_active(self.handle, index, max_len, gl::GetActiveAttrib);
+        let c_name = CString::new(name.as_bytes()).unwrap();
+        let location = gl::GetAttribLocation(self.handle, c_name.as_ptr() as *const GLchar);
+
+        AttributeInfo { name, ty, size, location }
+      }).collect()
+    }
+  }
+
+  /// Retrieve the binary of the program, along with its format.
+  ///
+  /// Store both – in a cache on disk, for instance – and pass them to `RawProgram::from_binary` or
+  /// `Program::from_binary` to recreate the program without compiling its stages again. The binary
+  /// is only valid for the same driver and GPU.
+  ///
+  /// Program binaries require OpenGL 4.1 or the `GL_ARB_get_program_binary` extension. If neither
+  /// is available or if the driver doesn’t provide a binary, this function returns `None`.
+  pub fn to_binary(&self) -> Option<(GLenum, Vec<u8>)> {
+    if !binary_supported() {
+      return None;
+    }
+
+    unsafe {
+      let mut len: GLint = 0;
+      gl::GetProgramiv(self.handle, gl::PROGRAM_BINARY_LENGTH, &mut len);
+
+      if len <= 0 {
+        return None;
+      }
+
+      let mut binary: Vec<u8> = vec![0; len as usize];
+      let mut written: GLsizei = 0;
+      let mut format: GLenum = 0;
+      gl::GetProgramBinary(self.handle, len, &mut written, &mut format, binary.as_mut_ptr() as *mut _);
+
+      binary.truncate(written as usize);
+      Some((format, binary))
+    }
+  }
+
+  /// Assign a binding point to the shader storage block which name is `name`.
+  ///
+  /// That enables you to bind a buffer to a storage block without hardcoding its binding point in
+  /// the shader sources (i.e. `layout (binding = N)`). Bind the buffer with
+  /// `Pipeline::bind_storage_buffer` and pass the binding point of the returned buffer – see
+  /// `BoundStorageBuffer::binding`.
+  pub fn storage_block_binding(&self, name: &str, binding: u32) -> Result<(), UniformWarning> {
+    let c_name = CString::new(name.as_bytes()).unwrap();
+
+    unsafe {
+      let index = gl::GetProgramResourceIndex(self.handle, gl::SHADER_STORAGE_BLOCK, c_name.as_ptr() as *const GLchar);
+
+      if index == gl::INVALID_INDEX {
+        return Err(UniformWarning::Inactive(name.to_owned()));
+      }
+
+      gl::ShaderStorageBlockBinding(self.handle, index, binding as GLuint);
+    }
+
+    Ok(())
+  }
+
   #[inline]
   pub(crate) fn handle(&self) -> GLuint {
     self.handle
   }
+
+  #[inline]
+  pub(crate) fn has_tessellation(&self) -> bool {
+    self.tessellation
+  }
 }
 
 impl Drop for RawProgram {
@@ -110,6 +300,106 @@ impl Drop for RawProgram {
   }
 }
 
+// Get the name, type and size of an active uniform or attribute with `get`, which is either
+// glGetActiveUniform or glGetActiveAttrib.
+unsafe fn get_active(
+  program: GLuint,
+  index: GLuint,
+  max_len: GLint,
+  get: unsafe fn(GLuint, GLuint, GLsizei, *mut GLsizei, *mut GLint, *mut GLenum, *mut GLchar)
+) -> (String, GLenum, usize) {
+  let mut name: Vec<u8> = vec![0; max_len.max(1) as usize];
+  let mut len: GLsizei = 0;
+  let mut size: GLint = 0;
+  let mut ty: GLenum = 0;
+
+  get(program, index, max_len, &mut len, &mut size, &mut ty, name.as_mut_ptr() as *mut GLchar);
+  name.truncate(len as usize);
+
+  (String::from_utf8_lossy(&name).into_owned(), ty, size as usize)
+}
+
+/// Information about an active uniform of a program.
+///
+/// See `RawProgram::active_uniforms`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UniformInfo {
+  /// Name of the uniform. Arrays are named after their first element – `lights[0]`, for instance.
+  pub name: String,
+  /// OpenGL type of the uniform – of its elements, for arrays.
+  pub ty: GLenum,
+  /// Number of elements of the uniform; `1` if it’s not an array.
+  pub size: usize,
+  /// Location of the uniform; `-1` for uniforms declared in uniform blocks.
+  pub location: GLint
+}
+
+impl UniformInfo {
+  /// Name of the GLSL type of the uniform – `vec3`, `sampler2D`, etc.
+  pub fn type_name(&self) -> String {
+    glsl_type_name(self.ty)
+  }
+}
+
+/// Information about an active vertex attribute of a program.
+///
+/// See `RawProgram::active_attributes`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttributeInfo {
+  /// Name of the attribute.
+  pub name: String,
+  /// OpenGL type of the attribute – of its elements, for arrays.
+  pub ty: GLenum,
+  /// Number of elements of the attribute; `1` if it’s not an array.
+  pub size: usize,
+  /// Location of the attribute; `-1` for built-in attributes.
+  pub location: GLint
+}
+
+impl AttributeInfo {
+  /// Name of the GLSL type of the attribute – `vec3`, `ivec2`, etc.
+  pub fn type_name(&self) -> String {
+    glsl_type_name(self.ty)
+  }
+}
+
+// Compiled tessellation, vertex, geometry and fragment stages.
+type Stages = (Option<(Stage, Stage)>, Stage, Option<Stage>, Stage);
+
+// Compile the stages of a program from their sources.
+fn compile_stages<'a, T, G>(
+  tess: T,
+  vertex: &str,
+  geometry: G,
+  fragment: &str
+) -> Result<Stages, ProgramError>
+where T: Into<Option<(&'a str, &'a str)>>,
+      G: Into<Option<&'a str>> {
+  let tess = match tess.into() {
+    Some((tcs_str, tes_str)) => {
+      let tcs = Stage::new(stage::Type::TessellationControlShader, tcs_str).map_err(ProgramError::StageError)?;
+      let tes = Stage::new(stage::Type::TessellationEvaluationShader, tes_str).map_err(ProgramError::StageError)?;
+      Some((tcs, tes))
+    },
+    None => None
+  };
+
+  let gs = match geometry.into() {
+    Some(gs_str) => Some(Stage::new(stage::Type::GeometryShader, gs_str).map_err(ProgramError::StageError)?),
+    None => None
+  };
+
+  let vs = Stage::new(stage::Type::VertexShader, vertex).map_err(ProgramError::StageError)?;
+  let fs = Stage::new(stage::Type::FragmentShader, fragment).map_err(ProgramError::StageError)?;
+
+  Ok((tess, vs, gs, fs))
+}
+
+// Whether program binaries are supported.
+fn binary_supported() -> bool {
+  gl_version() >= (4, 1) || has_extension(&[b"GL_ARB_get_program_binary"])
+}
+
 /// A typed shader program.
 ///
 /// Typed shader programs represent their inputs, outputs and environment (uniforms) directly in
@@ -137,6 +427,21 @@ impl<In, Out, Uni> Program<In, Out, Uni> where In: Vertex {
   }
 
   /// Create a new program by consuming strings.
+  ///
+  /// The arguments are the sources of the stages, in pipeline order:
+  ///
+  ///   - `tess`: the optional tessellation control and evaluation shaders, as a pair. Both are
+  ///     required to use tessellation: the control shader sets the tessellation levels of each
+  ///     patch and the evaluation shader computes the generated vertices. Render such programs
+  ///     with `Mode::Patch` tessellations.
+  ///   - `vertex`: the vertex shader.
+  ///   - `geometry`: the optional geometry shader, run between the vertex – or tessellation – and
+  ///     fragment stages. It can emit primitives of another kind than its input – expanding points
+  ///     into quads, for instance.
+  ///   - `fragment`: the fragment shader.
+  ///
+  /// A stage failing to compile is reported as a `ProgramError::StageError`, which tells the type
+  /// of the stage. A failing link is reported as a `ProgramError::LinkFailed`.
   pub fn from_strings<'a, T, G>(
     tess: T,
     vertex: &str,
@@ -160,7 +465,83 @@ impl<In, Out, Uni> Program<In, Out, Uni> where In: Vertex {
   where Uni: UniformInterface<E>,
         T: Into<Option<(&'a Stage, &'a Stage)>>,
         G: Into<Option<&'a Stage>> {
-    let raw = RawProgram::new(tess, vertex, geometry, fragment)?;
+    let raw = RawProgram::new(tess, vertex, geometry, fragment, &[])?;
+    Self::from_raw_env(raw, env)
+  }
+
+  /// Create a new program by consuming strings, capturing the given outputs with transform
+  /// feedback.
+  ///
+  /// The arguments are the same as in `Program::from_strings`. `varyings` names the outputs of the
+  /// last vertex processing stage – vertex, tessellation evaluation or geometry shader – to
+  /// capture, in the order they are laid out in the captured vertices: they must match the
+  /// attributes of the vertex type of the `TransformFeedback`. This is an alternative to the
+  /// `xfb_offset` layout qualifiers, which require GLSL 4.40.
+  pub fn from_strings_with_feedback<'a, T, G>(
+    tess: T,
+    vertex: &str,
+    geometry: G,
+    fragment: &str,
+    varyings: &[&str]
+  ) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface,
+        T: Into<Option<(&'a str, &'a str)>>,
+        G: Into<Option<&'a str>> {
+    Self::from_strings_with_feedback_env(tess, vertex, geometry, fragment, varyings, ())
+  }
+
+  /// Create a new program by consuming strings, capturing the given outputs with transform
+  /// feedback and by looking up an environment.
+  pub fn from_strings_with_feedback_env<'a, E, T, G>(
+    tess: T,
+    vertex: &str,
+    geometry: G,
+    fragment: &str,
+    varyings: &[&str],
+    env: E
+  ) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface<E>,
+        T: Into<Option<(&'a str, &'a str)>>,
+        G: Into<Option<&'a str>> {
+    let (tess, vs, gs, fs) = compile_stages(tess, vertex, geometry, fragment)?;
+    let raw = RawProgram::new(tess.as_ref().map(|(tcs, tes)| (tcs, tes)), &vs, gs.as_ref(), &fs, varyings)?;
+
+    Self::from_raw_env(raw, env)
+  }
+
+  /// Create a new program from a linked `RawProgram`.
+  ///
+  /// This is typically used with the programs handed back by a `ProgramBuilder`.
+  pub fn from_raw(raw: RawProgram) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface {
+    Self::from_raw_env(raw, ())
+  }
+
+  /// Create a new program from a binary retrieved with `RawProgram::to_binary`.
+  ///
+  /// The uniform interface is looked up again on the loaded program: use the same type parameters
+  /// as the program the binary was retrieved from. See `RawProgram::from_binary` for further
+  /// details about the errors.
+  pub fn from_binary(format: GLenum, binary: &[u8]) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface {
+    Self::from_binary_env(format, binary, ())
+  }
+
+  /// Create a new program from a binary retrieved with `RawProgram::to_binary` and by looking up
+  /// an environment.
+  pub fn from_binary_env<E>(
+    format: GLenum,
+    binary: &[u8],
+    env: E
+  ) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface<E> {
+    let raw = RawProgram::from_binary(format, binary)?;
+    Self::from_raw_env(raw, env)
+  }
+
+  /// Create a new program from a linked `RawProgram` and by looking up an environment.
+  pub fn from_raw_env<E>(raw: RawProgram, env: E) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface<E> {
     let (uni_iface, warnings) = create_uniform_interface(&raw, env)?;
 
     let program = Program {
@@ -184,24 +565,66 @@ impl<In, Out, Uni> Program<In, Out, Uni> where In: Vertex {
   where Uni: UniformInterface<E>,
         T: Into<Option<(&'a str, &'a str)>>,
         G: Into<Option<&'a str>> {
+    let (tess, vs, gs, fs) = compile_stages(tess, vertex, geometry, fragment)?;
+
+    Self::from_stages_env(tess.as_ref().map(|&(ref tcs, ref tes)| (tcs, tes)), &vs, gs.as_ref(), &fs, env)
+  }
+
+  /// Create a new program by consuming strings and resolving their `#include` directives.
+  ///
+  /// The arguments are the same as in `Program::from_strings`. `resolver` is called with the path
+  /// of every included file and must hand back its content, or `None` if it cannot be found. See
+  /// the `include` module for further details.
+  pub fn from_strings_with_includes<'a, T, G, R>(
+    tess: T,
+    vertex: &str,
+    geometry: G,
+    fragment: &str,
+    resolver: R
+  ) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface,
+        T: Into<Option<(&'a str, &'a str)>>,
+        G: Into<Option<&'a str>>,
+        R: FnMut(&str) -> Option<String> {
+    Self::from_strings_with_includes_env(tess, vertex, geometry, fragment, resolver, ())
+  }
+
+  /// Create a new program by consuming strings, resolving their `#include` directives and by
+  /// looking up an environment.
+  pub fn from_strings_with_includes_env<'a, E, T, G, R>(
+    tess: T,
+    vertex: &str,
+    geometry: G,
+    fragment: &str,
+    mut resolver: R,
+    env: E
+  ) -> Result<(Self, Vec<UniformWarning>), ProgramError>
+  where Uni: UniformInterface<E>,
+        T: Into<Option<(&'a str, &'a str)>>,
+        G: Into<Option<&'a str>>,
+        R: FnMut(&str) -> Option<String> {
+    let mut preprocess = |src| preprocess(src, &mut resolver).map_err(ProgramError::IncludeError);
+
     let tess = match tess.into() {
-      Some((tcs_str, tes_str)) => {
-        let tcs = Stage::new(stage::Type::TessellationControlShader, tcs_str).map_err(ProgramError::StageError)?;
-        let tes = Stage::new(stage::Type::TessellationControlShader, tes_str).map_err(ProgramError::StageError)?;
-        Some((tcs, tes))
-      },
+      Some((tcs, tes)) => Some((preprocess(tcs)?, preprocess(tes)?)),
       None => None
     };
 
-    let gs = match geometry.into() {
-      Some(gs_str) => Some(Stage::new(stage::Type::GeometryShader, gs_str).map_err(ProgramError::StageError)?),
+    let geometry = match geometry.into() {
+      Some(gs) => Some(preprocess(gs)?),
       None => None
     };
 
-    let vs = Stage::new(stage::Type::VertexShader, vertex).map_err(ProgramError::StageError)?;
-    let fs = Stage::new(stage::Type::FragmentShader, fragment).map_err(ProgramError::StageError)?;
+    let vertex = preprocess(vertex)?;
+    let fragment = preprocess(fragment)?;
 
-    Self::from_stages_env(tess.as_ref().map(|&(ref tcs, ref tes)| (tcs, tes)), &vs, gs.as_ref(), &fs, env)
+    Self::from_strings_env(
+      tess.as_ref().map(|(tcs, tes)| (tcs.as_str(), tes.as_str())),
+      &vertex,
+      geometry.as_deref(),
+      &fragment,
+      env
+    )
   }
 
   /// Get the uniform interface associated with this program.
@@ -326,10 +749,11 @@ impl<'a> UniformBuilder<'a> {
   pub fn ask<T>(&self, name: &str) -> Result<Uniform<T>, UniformWarning> where T: Uniformable {
     let uniform = match T::ty() {
       Type::BufferBinding => self.ask_uniform_block(name)?,
+      Type::StorageBinding => self.ask_storage_block(name)?,
       _ => self.ask_uniform(name)?
     };
 
-    uniform_type_match(self.raw.handle, name, T::ty()).map_err(|err| UniformWarning::TypeMismatch(name.to_owned(), err))?;
+    uniform_type_match(self.raw.handle, name, T::ty(), T::array_len()).map_err(|err| UniformWarning::TypeMismatch(name.to_owned(), err))?;
 
     Ok(uniform)
   }
@@ -344,6 +768,17 @@ impl<'a> UniformBuilder<'a> {
     }
   }
 
+  /// Have the builder hand you a `Uniform` that might be absent from the shader.
+  ///
+  /// If the uniform is inactive, an *unbound* uniform is returned and no warning is emitted. Any
+  /// other error – a type mismatch, for instance – is still reported.
+  pub fn ask_optional<T>(&self, name: &str) -> Result<Uniform<T>, UniformWarning> where T: Uniformable {
+    match self.ask(name) {
+      Err(UniformWarning::Inactive(_)) => Ok(self.unbound()),
+      r => r
+    }
+  }
+
   fn ask_uniform<T>(&self, name: &str) -> Result<Uniform<T>, UniformWarning> where T: Uniformable {
     let c_name = CString::new(name.as_bytes()).unwrap();
     let location = unsafe { gl::GetUniformLocation(self.raw.handle, c_name.as_ptr() as *const GLchar) };
@@ -366,6 +801,19 @@ impl<'a> UniformBuilder<'a> {
     }
   }
 
+  fn ask_storage_block<T>(&self, name: &str) -> Result<Uniform<T>, UniformWarning> where T: Uniformable {
+    let c_name = CString::new(name.as_bytes()).unwrap();
+    let location = unsafe {
+      gl::GetProgramResourceIndex(self.raw.handle, gl::SHADER_STORAGE_BLOCK, c_name.as_ptr() as *const GLchar)
+    };
+
+    if location == gl::INVALID_INDEX {
+      Err(UniformWarning::Inactive(name.to_owned()))
+    } else {
+      Ok(Uniform::new(self.raw.handle, location as GLint))
+    }
+  }
+
   /// Special uniform that won’t do anything.
   ///
   /// Use that function when you need a uniform to complete a uniform interface but you’re sure you
@@ -383,7 +831,19 @@ pub enum ProgramError {
   LinkFailed(String),
   /// Some uniform configuration is ill-formed. It can be a problem of inactive uniform, mismatch
   /// type, etc. Check the `UniformWarning` type for more information.
-  UniformWarning(UniformWarning)
+  UniformWarning(UniformWarning),
+  /// A uniform of a uniform interface is ill-formed. The first `String` is the name of the field
+  /// of the uniform interface; the `UniformWarning` gives the name of the uniform in the shader
+  /// program and what’s wrong with it.
+  ///
+  /// This is the error emitted by the interfaces declared with `uniform_interface!`.
+  UniformFieldWarning(String, UniformWarning),
+  /// The `#include` directives of a stage could not be resolved.
+  IncludeError(IncludeError),
+  /// Program binaries are not supported by the implementation.
+  BinaryUnsupported,
+  /// The driver rejected a program binary. The contained `String` is the link log, if any.
+  BinaryRejected(String)
 }
 
 impl fmt::Display for ProgramError {
@@ -400,15 +860,33 @@ impl fmt::Display for ProgramError {
       ProgramError::UniformWarning(ref e) => {
         write!(f, "shader program contains warning(s): {}", e)
       }
+
+      ProgramError::UniformFieldWarning(ref field, ref e) => {
+        write!(f, "shader program uniform interface field {} is ill-formed: {}", field, e)
+      }
+
+      ProgramError::IncludeError(ref e) => {
+        write!(f, "shader program has include error: {}", e)
+      }
+
+      ProgramError::BinaryUnsupported => {
+        f.write_str("shader program binaries are not supported")
+      }
+
+      ProgramError::BinaryRejected(ref s) => {
+        write!(f, "shader program binary was rejected: {}", s)
+      }
     }
   }
 }
 
 impl Error for ProgramError {
-  fn cause(&self) -> Option<&Error> {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
     match *self {
       ProgramError::StageError(ref e) => Some(e),
       ProgramError::UniformWarning(ref e) => Some(e),
+      ProgramError::UniformFieldWarning(_, ref e) => Some(e),
+      ProgramError::IncludeError(ref e) => Some(e),
       _ => None
     }
   }
@@ -476,9 +954,40 @@ impl<T> Uniform<T> where T: Uniformable {
     self.index
   }
 
+  /// Whether this uniform is bound to a variable in the shader program.
+  ///
+  /// Unbound uniforms are obtained with `UniformBuilder::unbound`, `UniformBuilder::ask_unbound` and
+  /// `UniformBuilder::ask_optional`.
+  pub fn is_bound(&self) -> bool {
+    self.index >= 0
+  }
+
   /// Update the value pointed by this uniform.
+  ///
+  /// This is a no-op if the uniform is unbound.
   pub fn update(&self, x: T) {
-    x.update(self);
+    if self.is_bound() {
+      x.update(self);
+    }
+  }
+}
+
+impl<T, const N: usize> Uniform<UniformArray<T, N>> where for<'a> &'a [T]: Uniformable {
+  /// Update the first elements of an array uniform from a slice.
+  ///
+  /// This is a no-op if the uniform is unbound.
+  ///
+  /// # Panic
+  ///
+  /// Panic if `values` has more than `N` elements.
+  pub fn update_slice(&self, values: &[T]) {
+    if values.len() > N {
+      panic!("cannot update a uniform array of {} elements with {} values", N, values.len());
+    }
+
+    if self.is_bound() {
+      Uniformable::update(values, &Uniform::new(self.program, self.index));
+    }
   }
 }
 
@@ -521,7 +1030,8 @@ pub enum Type {
   UICubemap,
   Cubemap,
   // buffer
-  BufferBinding
+  BufferBinding,
+  StorageBinding
 }
 
 /// Types that can behave as `Uniform`.
@@ -529,7 +1039,17 @@ pub unsafe trait Uniformable: Sized {
   /// Update the uniform with a new value.
   fn update(self, u: &Uniform<Self>);
   /// Retrieve the `Type` of the uniform.
+  ///
+  /// For arrays, this is the type of the elements.
   fn ty() -> Type;
+
+  /// Number of elements of the uniform, if it’s a fixed-size array.
+  ///
+  /// If set, the length of the array declared in the shader must match. Slices don’t have a fixed
+  /// length and return `None`, as do non-array types.
+  fn array_len() -> Option<usize> {
+    None
+  }
 }
 
 unsafe impl Uniformable for i32 {
@@ -846,8 +1366,42 @@ unsafe impl<'a> Uniformable for &'a [[bool; 4]] {
   fn ty() -> Type { Type::BVec4 }
 }
 
+/// A fixed-size array uniform.
+///
+/// Use it to map a uniform array declared in a shader – `uniform vec3 light_positions[8];` – to a
+/// `Uniform<UniformArray<[f32; 3], 8>>`. The length of the array declared in the shader must be
+/// `N`.
+///
+/// Update the whole array with `Uniform::update` or only its first elements from a slice with
+/// `Uniform::update_slice`.
+///
+/// > Note: arrays are not directly `Uniformable` because their types collide with vectors and
+/// > matrices – `[[f32; 2]; 2]` is a `M22`, for instance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UniformArray<T, const N: usize>(pub [T; N]);
+
+impl<T, const N: usize> From<[T; N]> for UniformArray<T, N> {
+  fn from(values: [T; N]) -> Self {
+    UniformArray(values)
+  }
+}
+
+unsafe impl<T, const N: usize> Uniformable for UniformArray<T, N> where for<'a> &'a [T]: Uniformable {
+  fn update(self, u: &Uniform<Self>) {
+    Uniformable::update(&self.0[..], &Uniform::new(u.program, u.index));
+  }
+
+  fn ty() -> Type {
+    <&[T] as Uniformable>::ty()
+  }
+
+  fn array_len() -> Option<usize> {
+    Some(N)
+  }
+}
+
 // Check whether a shader program’s uniform type matches the type we have chosen.
-fn uniform_type_match(program: GLuint, name: &str, ty: Type) -> Result<(), String> {
+fn uniform_type_match(program: GLuint, name: &str, ty: Type, array_len: Option<usize>) -> Result<(), String> {
   let mut size: GLint = 0;
   let mut typ: GLuint = 0;
   let c_name = CString::new(name.as_bytes()).unwrap();
@@ -866,14 +1420,15 @@ fn uniform_type_match(program: GLuint, name: &str, ty: Type) -> Result<(), Strin
     gl::GetActiveUniform(program, index, max_len, null_mut(), &mut size, &mut typ, name_.as_mut_ptr());
   }
 
-  // FIXME
-  // early-return if array – we don’t support them yet
-  if size != 1 {
-    return Ok(());
+  // arrays report the type of their elements; only fixed-size arrays have their length checked
+  if let Some(len) = array_len {
+    if size as usize != len {
+      return Err(format!("requested array of {} elements doesn’t match the {} elements declared in the shader", len, size));
+    }
   }
 
   // helper function for error reporting
-  let type_mismatch = |t| Err(format!("requested {} doesn’t match", t));
+  let type_mismatch = |t| Err(format!("requested {}, found {}", t, glsl_type_name(typ)));
 
   match ty {
     // scalars
@@ -905,18 +1460,70 @@ fn uniform_type_match(program: GLuint, name: &str, ty: Type) -> Result<(), Strin
     Type::UISampler1D if typ != gl::UNSIGNED_INT_SAMPLER_1D => type_mismatch("usampler1D"),
     Type::UISampler2D if typ != gl::UNSIGNED_INT_SAMPLER_2D => type_mismatch("usampler2D"),
     Type::UISampler3D if typ != gl::UNSIGNED_INT_SAMPLER_3D => type_mismatch("usampler3D"),
-    Type::Sampler1D if typ != gl::SAMPLER_1D => type_mismatch("sampler1D"),
-    Type::Sampler2D if typ != gl::SAMPLER_2D => type_mismatch("sampler2D"),
+    // depth textures can be sampled with a depth comparison, through shadow samplers
+    Type::Sampler1D if typ != gl::SAMPLER_1D && typ != gl::SAMPLER_1D_SHADOW => type_mismatch("sampler1D"),
+    Type::Sampler2D if typ != gl::SAMPLER_2D && typ != gl::SAMPLER_2D_SHADOW => type_mismatch("sampler2D"),
     Type::Sampler3D if typ != gl::SAMPLER_3D => type_mismatch("sampler3D"),
     Type::ICubemap if typ != gl::INT_SAMPLER_CUBE => type_mismatch("isamplerCube"),
     Type::UICubemap if typ != gl::UNSIGNED_INT_SAMPLER_CUBE => type_mismatch("usamplerCube"),
-    Type::Cubemap if typ != gl::SAMPLER_CUBE => type_mismatch("samplerCube"),
+    Type::Cubemap if typ != gl::SAMPLER_CUBE && typ != gl::SAMPLER_CUBE_SHADOW => type_mismatch("samplerCube"),
     _ => Ok(())
   }
 }
 
+// Name of the GLSL type of an active uniform, as reported by glGetActiveUniform.
+fn glsl_type_name(typ: GLenum) -> String {
+  let name = match typ {
+    gl::INT => "int",
+    gl::UNSIGNED_INT => "uint",
+    gl::FLOAT => "float",
+    gl::DOUBLE => "double",
+    gl::BOOL => "bool",
+    gl::INT_VEC2 => "ivec2",
+    gl::INT_VEC3 => "ivec3",
+    gl::INT_VEC4 => "ivec4",
+    gl::UNSIGNED_INT_VEC2 => "uvec2",
+    gl::UNSIGNED_INT_VEC3 => "uvec3",
+    gl::UNSIGNED_INT_VEC4 => "uvec4",
+    gl::FLOAT_VEC2 => "vec2",
+    gl::FLOAT_VEC3 => "vec3",
+    gl::FLOAT_VEC4 => "vec4",
+    gl::BOOL_VEC2 => "bvec2",
+    gl::BOOL_VEC3 => "bvec3",
+    gl::BOOL_VEC4 => "bvec4",
+    gl::FLOAT_MAT2 => "mat2",
+    gl::FLOAT_MAT3 => "mat3",
+    gl::FLOAT_MAT4 => "mat4",
+    gl::FLOAT_MAT2x3 => "mat2x3",
+    gl::FLOAT_MAT2x4 => "mat2x4",
+    gl::FLOAT_MAT3x2 => "mat3x2",
+    gl::FLOAT_MAT3x4 => "mat3x4",
+    gl::FLOAT_MAT4x2 => "mat4x2",
+    gl::FLOAT_MAT4x3 => "mat4x3",
+    gl::INT_SAMPLER_1D => "isampler1D",
+    gl::INT_SAMPLER_2D => "isampler2D",
+    gl::INT_SAMPLER_3D => "isampler3D",
+    gl::UNSIGNED_INT_SAMPLER_1D => "usampler1D",
+    gl::UNSIGNED_INT_SAMPLER_2D => "usampler2D",
+    gl::UNSIGNED_INT_SAMPLER_3D => "usampler3D",
+    gl::SAMPLER_1D => "sampler1D",
+    gl::SAMPLER_2D => "sampler2D",
+    gl::SAMPLER_3D => "sampler3D",
+    gl::INT_SAMPLER_CUBE => "isamplerCube",
+    gl::UNSIGNED_INT_SAMPLER_CUBE => "usamplerCube",
+    gl::SAMPLER_CUBE => "samplerCube",
+    gl::SAMPLER_2D_ARRAY => "sampler2DArray",
+    gl::SAMPLER_1D_SHADOW => "sampler1DShadow",
+    gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+    gl::SAMPLER_CUBE_SHADOW => "samplerCubeShadow",
+    _ => return format!("unknown type 0x{:x}", typ)
+  };
+
+  name.to_owned()
+}
+
 // Generate a uniform interface and collect warnings.
-fn create_uniform_interface<Uni, E>(raw: &RawProgram, env: E) -> Result<(Uni, Vec<UniformWarning>), ProgramError>
+pub(crate) fn create_uniform_interface<Uni, E>(raw: &RawProgram, env: E) -> Result<(Uni, Vec<UniformWarning>), ProgramError>
 where Uni: UniformInterface<E> {
   let mut builder = UniformBuilder::new(raw);
   let iface = Uni::uniform_interface(&mut builder, env)?;
@@ -983,6 +1590,64 @@ where Uni: UniformInterface<E> {
 /// }
 /// ```
 ///
+/// If you want to share the same interface between several shader programs that only declare a
+/// subset of its uniforms, use the `#[optional]` attribute. An optional uniform that is absent
+/// from the shader program is silently unbound – updating it does nothing – while a type mismatch
+/// still makes the interface fail to build. Use `Uniform::is_bound` to know whether it was found.
+///
+/// ```ignore
+/// uniform_interface! {
+///   struct MyIface {
+///     time: f32,
+///     #[optional]
+///     roughness: f32, // might not be declared by all the shader programs using this interface
+///     #[as("metal"), optional]
+///     metalness: f32
+///   }
+/// }
+/// ```
+///
+/// Uniform arrays are declared with `UniformArray`. The length of the array declared in the shader
+/// program must match, or the interface fails to build with a type mismatch:
+///
+/// ```ignore
+/// uniform_interface! {
+///   struct MyIface {
+///     light_positions: UniformArray<[f32; 3], 8> // uniform vec3 light_positions[8];
+///   }
+/// }
+///
+/// iface.light_positions.update_slice(&positions[..]);
+/// ```
+///
+/// Uniform blocks are declared with a bound buffer – `BoundBuffer` – and mapped by their block
+/// name. Updating such a field with a buffer bound by `Pipeline::bind_uniform_buffer` points the
+/// block to the binding index of the buffer:
+///
+/// ```ignore
+/// uniform_interface! {
+///   struct MyIface {
+///     #[as("Lights")]
+///     lights: &'static BoundBuffer<'static, Buffer<Lights>> // layout (std140) uniform Lights { … };
+///   }
+/// }
+///
+/// let lights = pipeline.bind_uniform_buffer(&lights_buffer);
+/// iface.lights.update(&lights);
+/// ```
+///
+/// Shader storage blocks work the same way, with a `BoundStorageBuffer` bound by
+/// `Pipeline::bind_storage_buffer`:
+///
+/// ```ignore
+/// uniform_interface! {
+///   struct MyIface {
+///     #[as("Particles")]
+///     particles: &'static BoundStorageBuffer<'static, Buffer<Particle>> // buffer Particles { … };
+///   }
+/// }
+/// ```
+///
 /// > Note: this macro doesn’t allow you to do a *value-driven* implementation of
 /// > `UniformInterface`. If this is what you want, you’ll need to implement the trait by hand
 /// > and provide an environment type as in `impl UniformInterface<E = YourTypeHere> for …`.
@@ -1047,10 +1712,21 @@ macro_rules! uniform_interface_impl_trait_map {
     let $field_name = $builder.ask_unbound($field_mapping);
   };
 
+  // this form authorizes to specify the mapping
+  // this form authorizes absent uniforms by overriding them with an unbound uniform
+  ($builder:ident, $field_name:ident #[as($field_mapping:expr), optional]) => {
+    let $field_name = $builder.ask_optional($field_mapping).map_err(|e| $crate::shader::program::ProgramError::UniformFieldWarning(stringify!($field_name).to_owned(), e))?;
+  };
+
+  // same form as above but with flipped annotations
+  ($builder:ident, $field_name:ident #[optional, as($field_mapping:expr)]) => {
+    let $field_name = $builder.ask_optional($field_mapping).map_err(|e| $crate::shader::program::ProgramError::UniformFieldWarning(stringify!($field_name).to_owned(), e))?;
+  };
+
   // this form authorizes to specify the mapping
   // this form will make the whole uniform interface not to build on any error
   ($builder:ident, $field_name:ident #[as($field_mapping:expr)]) => {
-    let $field_name = $builder.ask($field_mapping).map_err($crate::shader::program::ProgramError::UniformWarning)?;
+    let $field_name = $builder.ask($field_mapping).map_err(|e| $crate::shader::program::ProgramError::UniformFieldWarning(stringify!($field_name).to_owned(), e))?;
   };
 
   // this form authorizes unmapped uniforms by overriding them with an unbound uniform
@@ -1058,8 +1734,13 @@ macro_rules! uniform_interface_impl_trait_map {
     let $field_name = $builder.ask_unbound(stringify!($field_name));
   };
 
+  // this form authorizes absent uniforms by overriding them with an unbound uniform
+  ($builder:ident, $field_name:ident #[optional]) => {
+    let $field_name = $builder.ask_optional(stringify!($field_name)).map_err(|e| $crate::shader::program::ProgramError::UniformFieldWarning(stringify!($field_name).to_owned(), e))?;
+  };
+
   // this form will make the whole uniform interface not to build on any error
   ($builder:ident, $field_name:ident) => {
-    let $field_name = $builder.ask(stringify!($field_name)).map_err($crate::shader::program::ProgramError::UniformWarning)?;
+    let $field_name = $builder.ask(stringify!($field_name)).map_err(|e| $crate::shader::program::ProgramError::UniformFieldWarning(stringify!($field_name).to_owned(), e))?;
   }
 }
@@ -5,6 +5,8 @@ use std::ffi::CString;
 use std::fmt;
 use std::ptr::{null, null_mut};
 
+use state::{gl_version, has_extension};
+
 /// A shader stage type.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Type {
@@ -12,7 +14,8 @@ pub enum Type {
   TessellationEvaluationShader,
   VertexShader,
   GeometryShader,
-  FragmentShader
+  FragmentShader,
+  ComputeShader
 }
 
 impl fmt::Display for Type {
@@ -22,7 +25,8 @@ impl fmt::Display for Type {
       Type::TessellationEvaluationShader => f.write_str("tessellation evaluation shader"),
       Type::VertexShader => f.write_str("vertex shader"),
       Type::GeometryShader => f.write_str("geometry shader"),
-      Type::FragmentShader => f.write_str("fragment shader")
+      Type::FragmentShader => f.write_str("fragment shader"),
+      Type::ComputeShader => f.write_str("compute shader")
     }
   }
 }
@@ -37,8 +41,17 @@ pub struct Stage {
 impl Stage {
   /// Create a new shader stage.
   pub fn new(ty: Type, src: &str) -> Result<Self, StageError> {
+    Self::compile(ty, src)?.check()
+  }
+
+  // Issue the compilation of a shader stage without waiting for it to complete.
+  pub(crate) fn compile(ty: Type, src: &str) -> Result<Self, StageError> {
+    if !is_supported(ty) {
+      return Err(StageError::UnsupportedType(ty));
+    }
+
     unsafe {
-      let src = CString::new(glsl_pragma_src(src).as_bytes()).unwrap();
+      let src = CString::new(glsl_pragma_src(ty, src).as_bytes()).unwrap();
       let handle = gl::CreateShader(opengl_shader_type(ty));
 
       if handle == 0 {
@@ -48,26 +61,32 @@ impl Stage {
       gl::ShaderSource(handle, 1, [src.as_ptr()].as_ptr(), null());
       gl::CompileShader(handle);
 
+      Ok(Stage {
+        handle: handle,
+        ty: ty
+      })
+    }
+  }
+
+  // Wait for the compilation of the shader stage to complete and check whether it succeeded.
+  pub(crate) fn check(self) -> Result<Self, StageError> {
+    unsafe {
       let mut compiled: GLint = gl::FALSE as GLint;
-      gl::GetShaderiv(handle, gl::COMPILE_STATUS, &mut compiled);
+      gl::GetShaderiv(self.handle, gl::COMPILE_STATUS, &mut compiled);
 
       if compiled == (gl::TRUE as GLint) {
-        Ok(Stage {
-          handle: handle,
-          ty: ty
-        })
+        Ok(self)
       } else {
         let mut log_len: GLint = 0;
-        gl::GetShaderiv(handle, gl::INFO_LOG_LENGTH, &mut log_len);
+        gl::GetShaderiv(self.handle, gl::INFO_LOG_LENGTH, &mut log_len);
 
         let mut log: Vec<u8> = Vec::with_capacity(log_len as usize);
-        gl::GetShaderInfoLog(handle, log_len, null_mut(), log.as_mut_ptr() as *mut GLchar);
-
-        gl::DeleteShader(handle);
+        gl::GetShaderInfoLog(self.handle, log_len, null_mut(), log.as_mut_ptr() as *mut GLchar);
 
         log.set_len(log_len as usize);
 
-        Err(StageError::CompilationFailed(ty, String::from_utf8(log).unwrap()))
+        // the shader gets deleted when self is dropped
+        Err(StageError::CompilationFailed(self.ty, String::from_utf8(log).unwrap()))
       }
     }
   }
@@ -109,8 +128,27 @@ impl fmt::Display for StageError {
 
 impl Error for StageError {}
 
-fn glsl_pragma_src(src: &str) -> String {
-  let mut pragma = String::from(GLSL_PRAGMA);
+// Whether the current implementation supports a given stage type.
+fn is_supported(ty: Type) -> bool {
+  match ty {
+    Type::TessellationControlShader | Type::TessellationEvaluationShader => {
+      gl_version() >= (4, 0) || has_extension(&[b"GL_ARB_tessellation_shader"])
+    }
+
+    Type::ComputeShader => gl_version() >= (4, 3),
+
+    _ => true
+  }
+}
+
+fn glsl_pragma_src(ty: Type, src: &str) -> String {
+  // tessellation shaders require GLSL 4.00 and compute shaders require GLSL 4.30
+  let pragma = match ty {
+    Type::TessellationControlShader | Type::TessellationEvaluationShader => TESS_GLSL_PRAGMA,
+    Type::ComputeShader => COMPUTE_GLSL_PRAGMA,
+    _ => GLSL_PRAGMA
+  };
+  let mut pragma = String::from(pragma);
   pragma.push_str(src);
   pragma
 }
@@ -119,12 +157,19 @@ const GLSL_PRAGMA: &'static str = "\
 #version 330 core\n\
 #extension GL_ARB_separate_shader_objects : require\n";
 
+const TESS_GLSL_PRAGMA: &str = "\
+#version 400 core\n\
+#extension GL_ARB_separate_shader_objects : require\n";
+
+const COMPUTE_GLSL_PRAGMA: &str = "#version 430 core\n";
+
 fn opengl_shader_type(t: Type) -> GLenum {
   match t {
     Type::TessellationControlShader => gl::TESS_CONTROL_SHADER,
     Type::TessellationEvaluationShader => gl::TESS_EVALUATION_SHADER,
     Type::VertexShader => gl::VERTEX_SHADER,
     Type::GeometryShader => gl::GEOMETRY_SHADER,
-    Type::FragmentShader => gl::FRAGMENT_SHADER
+    Type::FragmentShader => gl::FRAGMENT_SHADER,
+    Type::ComputeShader => gl::COMPUTE_SHADER
   }
 }
@@ -4,12 +4,25 @@ use gl;
 use gl::types::*;
 use std::cell::RefCell;
 use std::error::Error;
+use std::ffi::CStr;
 use std::fmt;
 use std::marker::PhantomData;
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use std::rc::Rc;
 
 use blending::{BlendingState, Equation, Factor};
-use depth_test::DepthTest;
+use context::Capabilities;
+use debug::{DebugCallback, debug_message_callback};
+use depth_bias::{DepthBias, POLYGON_OFFSET_CLAMP, polygon_offset_clamp,
+                 polygon_offset_clamp_available};
+use depth_test::{DepthComparison, DepthTest, DepthWrite};
 use face_culling::{FaceCullingMode, FaceCullingOrder, FaceCullingState};
+use framebuffer::{ClampColor, SRGBEncoding};
+use polygon_mode::PolygonMode;
+use provoking_vertex::ProvokingVertex;
+use shader::program::has_tessellation_stages;
+use stencil::{StencilComparison, StencilOperation, StencilTestState};
 
 // TLS synchronization barrier for `GraphicsState`.
 thread_local!(static TLS_ACQUIRE_GFX_STATE: RefCell<Option<()>> = RefCell::new(Some(())));
@@ -20,40 +33,117 @@ thread_local!(static TLS_ACQUIRE_GFX_STATE: RefCell<Option<()>> = RefCell::new(S
 /// as a forward-gate to all the exposed features from the low-level API but
 /// adds a small cache layer over it to prevent from issuing the same API call (with
 /// the same parameters).
+///
+/// A `GraphicsState` caches the state of a single OpenGL context and must only be used with that
+/// context. Only one `GraphicsState` can be created per thread; if you need several contexts, you
+/// need several threads, each holding its own, independent `GraphicsState`.
 pub struct GraphicsState {
   _a: PhantomData<*const ()>, // !Send and !Sync
   
   // blending
   blending_state: BlendingState,
-  blending_equation: Equation,
-  blending_func: (Factor, Factor),
+  blending_equation: (Equation, Equation), // RGB, alpha
+  blending_func: (Factor, Factor, Factor, Factor), // RGB source and destination, alpha source and destination
+  blending_constant: [GLfloat; 4],
+
+  // color mask
+  color_mask: [bool; 4],
 
   // depth test
   depth_test: DepthTest,
+  depth_comparison: DepthComparison,
+  depth_write: DepthWrite,
+
+  // depth bias
+  depth_bias: Option<DepthBias>,
+
+  // stencil test
+  stencil_test_state: StencilTestState,
+  stencil_func: (StencilComparison, GLint, GLuint), // comparison, reference, read mask
+  stencil_operations: (StencilOperation, StencilOperation, StencilOperation), // sfail, dpfail, dppass
+  stencil_write_mask: GLuint,
 
   // face culling
   face_culling_state: FaceCullingState,
   face_culling_order: FaceCullingOrder,
   face_culling_mode: FaceCullingMode,
 
+  // provoking vertex
+  provoking_vertex: ProvokingVertex,
+
+  // polygon mode
+  polygon_mode: PolygonMode,
+
+  // line width and point size
+  line_width: GLfloat,
+  line_width_range: [GLfloat; 2],
+  point_size: Option<GLfloat>, // none means the size is set by shaders
+  point_size_range: [GLfloat; 2],
+
+  // patches
+  patch_vertices: GLint,
+
   // texture
   current_texture_unit: GLenum,
   bound_textures: Vec<(GLenum, GLuint)>,
+  max_texture_units: u32,
 
   // uniform buffer
   bound_uniform_buffers: Vec<GLuint>,
+  uniform_buffer_offset_alignment: usize,
+  max_uniform_block_size: usize,
+
+  // shader storage buffer
+  bound_storage_buffers: Vec<GLuint>,
 
   // array buffer
   bound_array_buffer: GLuint,
 
+  // element array buffer; part of the vertex array state, so unknown after switching vertex arrays
+  bound_element_array_buffer: GLuint,
+
+  // draw indirect buffer
+  bound_draw_indirect_buffer: GLuint,
+
   // framebuffer
   bound_draw_framebuffer: GLuint,
+  bound_read_framebuffer: GLuint,
+  clamp_read_color: ClampColor,
+  srgb_encoding: SRGBEncoding,
+  clear_depth: GLfloat,
+  clear_stencil: GLint,
+  viewport: [GLint; 4],
+  scissor: Option<[GLint; 4]>, // none means the scissor test is disabled
 
   // vertex array
   bound_vertex_array: GLuint,
 
   // shader program
-  current_program: GLuint
+  current_program: GLuint,
+  current_program_tessellation: bool, // whether the current program can render patches
+
+  // capabilities, queried lazily
+  capabilities: Option<Capabilities>,
+
+  // debug output callback; boxed twice so that OpenGL gets a thin pointer to it
+  debug_callback: Option<Box<DebugCallback>>,
+
+  // bind calls issued and skipped so far
+  #[cfg(debug_assertions)]
+  bind_stats: BindStats
+}
+
+/// Number of bind calls issued and skipped by a `GraphicsState`.
+///
+/// Only available in debug builds, to check that redundant binds are skipped – see
+/// `GraphicsState::bind_stats`.
+#[cfg(debug_assertions)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BindStats {
+  /// Binds that reached OpenGL.
+  pub issued: usize,
+  /// Binds skipped because the object was already bound.
+  pub skipped: usize
 }
 
 impl GraphicsState {
@@ -81,34 +171,98 @@ impl GraphicsState {
       let blending_state = get_ctx_blending_state()?;
       let blending_equation = get_ctx_blending_equation()?;
       let blending_func = get_ctx_blending_factors()?;
+      let blending_constant = get_ctx_blending_constant();
+      let color_mask = get_ctx_color_mask()?;
       let depth_test = get_ctx_depth_test()?;
+      let depth_comparison = get_ctx_depth_comparison()?;
+      let depth_write = get_ctx_depth_write()?;
+      let depth_bias = get_ctx_depth_bias()?;
+      let stencil_test_state = get_ctx_stencil_test_state()?;
+      let stencil_func = get_ctx_stencil_func()?;
+      let stencil_operations = get_ctx_stencil_operations()?;
+      let stencil_write_mask = get_ctx_integer(gl::STENCIL_WRITEMASK) as GLuint;
       let face_culling_state = get_ctx_face_culling_state()?;
       let face_culling_order = get_ctx_face_culling_order()?;
       let face_culling_mode = get_ctx_face_culling_mode()?;
+      let provoking_vertex = get_ctx_provoking_vertex()?;
+      let polygon_mode = get_ctx_polygon_mode()?;
+      let line_width = get_ctx_float(gl::LINE_WIDTH);
+      let line_width_range = get_ctx_float_range(gl::ALIASED_LINE_WIDTH_RANGE);
+      let point_size = get_ctx_point_size()?;
+      let point_size_range = get_ctx_float_range(gl::POINT_SIZE_RANGE);
+      let patch_vertices = get_ctx_patch_vertices();
       let current_texture_unit = get_ctx_current_texture_unit()?;
       let bound_textures = vec![(gl::TEXTURE_2D, 0); 48]; // 48 is the platform minimal requirement
+      let max_texture_units = get_ctx_integer(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS) as u32;
       let bound_uniform_buffers = vec![0; 36]; // 36 is the platform minimal requirement
-      let bound_array_buffer = 0;
+      let uniform_buffer_offset_alignment = get_ctx_integer(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT) as usize;
+      let max_uniform_block_size = get_ctx_integer(gl::MAX_UNIFORM_BLOCK_SIZE) as usize;
+      let bound_storage_buffers = vec![0; 8]; // 8 is the platform minimal requirement
+      let bound_array_buffer = get_ctx_bound_array_buffer()?;
+      let bound_element_array_buffer = get_ctx_bound_element_array_buffer()?;
+      let bound_draw_indirect_buffer = get_ctx_bound_draw_indirect_buffer()?;
       let bound_draw_framebuffer = get_ctx_bound_draw_framebuffer()?;
+      let bound_read_framebuffer = get_ctx_bound_read_framebuffer()?;
+      let clamp_read_color = get_ctx_clamp_read_color()?;
+      let srgb_encoding = get_ctx_srgb_encoding()?;
+      let clear_depth = get_ctx_float(gl::DEPTH_CLEAR_VALUE);
+      let clear_stencil = get_ctx_integer(gl::STENCIL_CLEAR_VALUE);
+      let viewport = get_ctx_viewport()?;
+      let scissor = get_ctx_scissor()?;
       let bound_vertex_array = get_ctx_bound_vertex_array()?;
       let current_program = get_ctx_current_program()?;
+      let current_program_tessellation = has_tessellation_stages(current_program);
 
       Ok(GraphicsState {
         _a: PhantomData,
         blending_state,
         blending_equation,
         blending_func,
+        blending_constant,
+        color_mask,
         depth_test,
+        depth_comparison,
+        depth_write,
+        depth_bias,
+        stencil_test_state,
+        stencil_func,
+        stencil_operations,
+        stencil_write_mask,
         face_culling_state,
         face_culling_order,
         face_culling_mode,
+        provoking_vertex,
+        polygon_mode,
+        line_width,
+        line_width_range,
+        point_size,
+        point_size_range,
+        patch_vertices,
         current_texture_unit,
         bound_textures,
+        max_texture_units,
         bound_uniform_buffers,
+        uniform_buffer_offset_alignment,
+        max_uniform_block_size,
+        bound_storage_buffers,
         bound_array_buffer,
+        bound_element_array_buffer,
+        bound_draw_indirect_buffer,
         bound_draw_framebuffer,
+        bound_read_framebuffer,
+        clamp_read_color,
+        srgb_encoding,
+        clear_depth,
+        clear_stencil,
+        viewport,
+        scissor,
         bound_vertex_array,
         current_program,
+        current_program_tessellation,
+        capabilities: None,
+        debug_callback: None,
+        #[cfg(debug_assertions)]
+        bind_stats: BindStats::default()
       })
     }
   }
@@ -124,10 +278,10 @@ impl GraphicsState {
     }
   }
 
-  pub(crate) unsafe fn set_blending_equation(&mut self, equation: Equation) {
-    if self.blending_equation != equation {
-      gl::BlendEquation(from_blending_equation(equation));
-      self.blending_equation = equation;
+  pub(crate) unsafe fn set_blending_equation(&mut self, equation: Equation, alpha_equation: Equation) {
+    if self.blending_equation != (equation, alpha_equation) {
+      gl::BlendEquationSeparate(from_blending_equation(equation), from_blending_equation(alpha_equation));
+      self.blending_equation = (equation, alpha_equation);
     }
   }
 
@@ -135,10 +289,38 @@ impl GraphicsState {
     &mut self,
     src: Factor,
     dest: Factor,
+    alpha_src: Factor,
+    alpha_dest: Factor
   ) {
-    if self.blending_func != (src, dest) {
-      gl::BlendFunc(from_blending_factor(src), from_blending_factor(dest));
-      self.blending_func = (src, dest);
+    if self.blending_func != (src, dest, alpha_src, alpha_dest) {
+      gl::BlendFuncSeparate(
+        from_blending_factor(src),
+        from_blending_factor(dest),
+        from_blending_factor(alpha_src),
+        from_blending_factor(alpha_dest)
+      );
+
+      self.blending_func = (src, dest, alpha_src, alpha_dest);
+    }
+  }
+
+  pub(crate) unsafe fn set_blending_constant(&mut self, constant: [GLfloat; 4]) {
+    if self.blending_constant != constant {
+      gl::BlendColor(constant[0], constant[1], constant[2], constant[3]);
+      self.blending_constant = constant;
+    }
+  }
+
+  pub(crate) unsafe fn set_color_mask(&mut self, color_mask: [bool; 4]) {
+    if self.color_mask != color_mask {
+      gl::ColorMask(
+        color_mask[0] as GLboolean,
+        color_mask[1] as GLboolean,
+        color_mask[2] as GLboolean,
+        color_mask[3] as GLboolean
+      );
+
+      self.color_mask = color_mask;
     }
   }
 
@@ -153,6 +335,95 @@ impl GraphicsState {
     }
   }
 
+  pub(crate) unsafe fn set_depth_comparison(&mut self, depth_comparison: DepthComparison) {
+    if self.depth_comparison != depth_comparison {
+      gl::DepthFunc(from_depth_comparison(depth_comparison));
+      self.depth_comparison = depth_comparison;
+    }
+  }
+
+  pub(crate) unsafe fn set_depth_write(&mut self, depth_write: DepthWrite) {
+    if self.depth_write != depth_write {
+      match depth_write {
+        DepthWrite::Enabled => gl::DepthMask(gl::TRUE),
+        DepthWrite::Disabled => gl::DepthMask(gl::FALSE)
+      }
+
+      self.depth_write = depth_write;
+    }
+  }
+
+  pub(crate) unsafe fn set_depth_bias(&mut self, depth_bias: Option<DepthBias>) {
+    if self.depth_bias != depth_bias {
+      match depth_bias {
+        Some(bias) => {
+          if self.depth_bias.is_none() {
+            gl::Enable(gl::POLYGON_OFFSET_FILL);
+          }
+
+          // glPolygonOffset is the same as glPolygonOffsetClamp with a zero clamp, so it also
+          // resets a previous clamp
+          if bias.clamp != 0. && self.capabilities().polygon_offset_clamp {
+            polygon_offset_clamp(bias.factor, bias.units, bias.clamp);
+          } else {
+            gl::PolygonOffset(bias.factor, bias.units);
+          }
+        }
+
+        None => gl::Disable(gl::POLYGON_OFFSET_FILL)
+      }
+
+      self.depth_bias = depth_bias;
+    }
+  }
+
+  pub(crate) unsafe fn set_stencil_test_state(&mut self, state: StencilTestState) {
+    if self.stencil_test_state != state {
+      match state {
+        StencilTestState::Enabled => gl::Enable(gl::STENCIL_TEST),
+        StencilTestState::Disabled => gl::Disable(gl::STENCIL_TEST)
+      }
+
+      self.stencil_test_state = state;
+    }
+  }
+
+  pub(crate) unsafe fn set_stencil_func(
+    &mut self,
+    comparison: StencilComparison,
+    reference: GLint,
+    read_mask: GLuint
+  ) {
+    if self.stencil_func != (comparison, reference, read_mask) {
+      gl::StencilFunc(from_stencil_comparison(comparison), reference, read_mask);
+      self.stencil_func = (comparison, reference, read_mask);
+    }
+  }
+
+  pub(crate) unsafe fn set_stencil_operations(
+    &mut self,
+    on_stencil_fail: StencilOperation,
+    on_depth_fail: StencilOperation,
+    on_pass: StencilOperation
+  ) {
+    if self.stencil_operations != (on_stencil_fail, on_depth_fail, on_pass) {
+      gl::StencilOp(
+        from_stencil_operation(on_stencil_fail),
+        from_stencil_operation(on_depth_fail),
+        from_stencil_operation(on_pass)
+      );
+
+      self.stencil_operations = (on_stencil_fail, on_depth_fail, on_pass);
+    }
+  }
+
+  pub(crate) unsafe fn set_stencil_write_mask(&mut self, write_mask: GLuint) {
+    if self.stencil_write_mask != write_mask {
+      gl::StencilMask(write_mask);
+      self.stencil_write_mask = write_mask;
+    }
+  }
+
   pub(crate) unsafe fn set_face_culling_state(&mut self, state: FaceCullingState) {
     if self.face_culling_state != state {
       match state {
@@ -187,6 +458,67 @@ impl GraphicsState {
     }
   }
 
+  pub(crate) unsafe fn set_provoking_vertex(&mut self, provoking_vertex: ProvokingVertex) {
+    if self.provoking_vertex != provoking_vertex {
+      match provoking_vertex {
+        ProvokingVertex::First => gl::ProvokingVertex(gl::FIRST_VERTEX_CONVENTION),
+        ProvokingVertex::Last => gl::ProvokingVertex(gl::LAST_VERTEX_CONVENTION)
+      }
+
+      self.provoking_vertex = provoking_vertex;
+    }
+  }
+
+  pub(crate) unsafe fn set_polygon_mode(&mut self, polygon_mode: PolygonMode) {
+    if self.polygon_mode != polygon_mode {
+      match polygon_mode {
+        PolygonMode::Fill => gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL),
+        PolygonMode::Line => gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE),
+        PolygonMode::Point => gl::PolygonMode(gl::FRONT_AND_BACK, gl::POINT)
+      }
+
+      self.polygon_mode = polygon_mode;
+    }
+  }
+
+  // The width is clamped to the range supported by the implementation.
+  pub(crate) unsafe fn set_line_width(&mut self, line_width: GLfloat) {
+    let line_width = line_width.max(self.line_width_range[0]).min(self.line_width_range[1]);
+
+    if self.line_width != line_width {
+      gl::LineWidth(line_width);
+      self.line_width = line_width;
+    }
+  }
+
+  // The size is clamped to the range supported by the implementation.
+  pub(crate) unsafe fn set_point_size(&mut self, point_size: Option<GLfloat>) {
+    let point_size = point_size.map(|size| size.max(self.point_size_range[0]).min(self.point_size_range[1]));
+
+    if self.point_size != point_size {
+      match point_size {
+        Some(size) => {
+          if self.point_size.is_none() {
+            gl::Disable(gl::PROGRAM_POINT_SIZE);
+          }
+
+          gl::PointSize(size);
+        }
+
+        None => gl::Enable(gl::PROGRAM_POINT_SIZE)
+      }
+
+      self.point_size = point_size;
+    }
+  }
+
+  pub(crate) unsafe fn set_patch_vertices(&mut self, patch_vertices: GLint) {
+    if self.patch_vertices != patch_vertices {
+      gl::PatchParameteri(gl::PATCH_VERTICES, patch_vertices);
+      self.patch_vertices = patch_vertices;
+    }
+  }
+
   pub(crate) unsafe fn set_texture_unit(&mut self, unit: u32) {
     if self.current_texture_unit != unit {
       gl::ActiveTexture(gl::TEXTURE0 + unit as GLenum);
@@ -201,6 +533,7 @@ impl GraphicsState {
       Some((target_, handle_)) if target != target_ || handle != handle_ => {
         gl::BindTexture(target, handle);
         self.bound_textures[unit] = (target, handle);
+        self.record_bind(true);
       }
 
       None => {
@@ -209,9 +542,10 @@ impl GraphicsState {
         // not enough registered texture units; let’s grow a bit more
         self.bound_textures.resize(unit + 1, (gl::TEXTURE_2D, 0));
         self.bound_textures[unit] = (target, handle);
+        self.record_bind(true);
       }
 
-      _ => () // cached
+      _ => self.record_bind(false) // cached
     }
   }
 
@@ -222,6 +556,7 @@ impl GraphicsState {
       Some(handle_) if handle != handle_ => {
         gl::BindBufferBase(gl::UNIFORM_BUFFER, binding as GLuint, handle);
         self.bound_uniform_buffers[binding_] = handle;
+        self.record_bind(true);
       }
 
       None => {
@@ -230,16 +565,118 @@ impl GraphicsState {
         // not enough registered buffer bindings; let’s grow a bit more
         self.bound_uniform_buffers.resize(binding_ + 1, 0);
         self.bound_uniform_buffers[binding_] = handle;
+        self.record_bind(true);
+      }
+
+      _ => self.record_bind(false) // cached
+    }
+  }
+
+  pub(crate) unsafe fn bind_buffer_range(&mut self, handle: GLuint, binding: u32, offset: usize, size: usize) {
+    let binding_ = binding as usize;
+
+    // ranges are not cached; always bind and invalidate the cached handle so that a subsequent
+    // bind_buffer_base with the same handle is not skipped
+    gl::BindBufferRange(gl::UNIFORM_BUFFER, binding as GLuint, handle, offset as GLintptr, size as GLsizeiptr);
+
+    if binding_ >= self.bound_uniform_buffers.len() {
+      self.bound_uniform_buffers.resize(binding_ + 1, 0);
+    }
+
+    self.bound_uniform_buffers[binding_] = 0;
+  }
+
+  pub(crate) unsafe fn bind_storage_buffer_base(&mut self, handle: GLuint, binding: u32) {
+    let binding_ = binding as usize;
+
+    match self.bound_storage_buffers.get(binding_).cloned() {
+      Some(handle_) if handle != handle_ => {
+        gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding as GLuint, handle);
+        self.bound_storage_buffers[binding_] = handle;
+        self.record_bind(true);
       }
 
-      _ => () // cached
+      None => {
+        gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding as GLuint, handle);
+
+        // not enough registered buffer bindings; let’s grow a bit more
+        self.bound_storage_buffers.resize(binding_ + 1, 0);
+        self.bound_storage_buffers[binding_] = handle;
+        self.record_bind(true);
+      }
+
+      _ => self.record_bind(false) // cached
+    }
+  }
+
+  pub(crate) unsafe fn set_debug_callback(&mut self, callback: Option<DebugCallback>) {
+    match callback {
+      Some(callback) => {
+        let mut callback = Box::new(callback);
+        // the heap address of the callback doesn’t change when moving the box into the state; the
+        // pointer is derived from a unique borrow since the callback is called through it
+        let user_param = &mut *callback as *mut DebugCallback as *const c_void;
+
+        gl::Enable(gl::DEBUG_OUTPUT);
+        // deliver the messages on this thread, from within the faulty call
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(debug_message_callback, user_param);
+
+        self.debug_callback = Some(callback);
+      }
+
+      None => {
+        gl::Disable(gl::DEBUG_OUTPUT);
+        gl::DebugMessageCallback(debug_message_callback, ptr::null());
+
+        self.debug_callback = None;
+      }
     }
   }
 
+  pub(crate) fn capabilities(&mut self) -> &Capabilities {
+    self.capabilities.get_or_insert_with(Capabilities::from_context)
+  }
+
+  pub(crate) fn max_texture_units(&self) -> u32 {
+    self.max_texture_units
+  }
+
+  pub(crate) fn uniform_buffer_offset_alignment(&self) -> usize {
+    self.uniform_buffer_offset_alignment
+  }
+
+  pub(crate) fn max_uniform_block_size(&self) -> usize {
+    self.max_uniform_block_size
+  }
+
   pub(crate) unsafe fn bind_array_buffer(&mut self, handle: GLuint) {
     if self.bound_array_buffer != handle {
       gl::BindBuffer(gl::ARRAY_BUFFER, handle);
       self.bound_array_buffer = handle;
+      self.record_bind(true);
+    } else {
+      self.record_bind(false);
+    }
+  }
+
+  pub(crate) unsafe fn bind_element_array_buffer(&mut self, handle: GLuint) {
+    if self.bound_element_array_buffer != handle {
+      gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, handle);
+      self.bound_element_array_buffer = handle;
+      self.record_bind(true);
+    } else {
+      self.record_bind(false);
+    }
+  }
+
+  pub(crate) unsafe fn bind_draw_indirect_buffer(&mut self, handle: GLuint) {
+    if self.bound_draw_indirect_buffer != handle {
+      gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, handle);
+      self.bound_draw_indirect_buffer = handle;
+      self.record_bind(true);
+    } else {
+      self.record_bind(false);
     }
   }
 
@@ -247,20 +684,306 @@ impl GraphicsState {
     if self.bound_draw_framebuffer != handle {
       gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, handle);
       self.bound_draw_framebuffer = handle;
+      self.record_bind(true);
+    } else {
+      self.record_bind(false);
+    }
+  }
+
+  pub(crate) unsafe fn bind_read_framebuffer(&mut self, handle: GLuint) {
+    if self.bound_read_framebuffer != handle {
+      gl::BindFramebuffer(gl::READ_FRAMEBUFFER, handle);
+      self.bound_read_framebuffer = handle;
+      self.record_bind(true);
+    } else {
+      self.record_bind(false);
     }
   }
 
+  pub(crate) unsafe fn set_clamp_read_color(&mut self, clamp: ClampColor) {
+    if self.clamp_read_color != clamp {
+      match clamp {
+        ClampColor::Enabled => gl::ClampColor(gl::CLAMP_READ_COLOR, gl::TRUE as GLenum),
+        ClampColor::Disabled => gl::ClampColor(gl::CLAMP_READ_COLOR, gl::FALSE as GLenum),
+        ClampColor::FixedOnly => gl::ClampColor(gl::CLAMP_READ_COLOR, gl::FIXED_ONLY)
+      }
+
+      self.clamp_read_color = clamp;
+    }
+  }
+
+  pub(crate) unsafe fn set_srgb_encoding(&mut self, encoding: SRGBEncoding) {
+    if self.srgb_encoding != encoding {
+      match encoding {
+        SRGBEncoding::Enabled => gl::Enable(gl::FRAMEBUFFER_SRGB),
+        SRGBEncoding::Disabled => gl::Disable(gl::FRAMEBUFFER_SRGB)
+      }
+
+      self.srgb_encoding = encoding;
+    }
+  }
+
+  pub(crate) unsafe fn set_clear_depth(&mut self, value: GLfloat) {
+    if self.clear_depth != value {
+      gl::ClearDepth(value as GLdouble);
+      self.clear_depth = value;
+    }
+  }
+
+  pub(crate) fn clear_depth(&self) -> GLfloat {
+    self.clear_depth
+  }
+
+  pub(crate) fn clear_stencil(&self) -> GLint {
+    self.clear_stencil
+  }
+
+  pub(crate) unsafe fn set_clear_stencil(&mut self, value: GLint) {
+    if self.clear_stencil != value {
+      gl::ClearStencil(value);
+      self.clear_stencil = value;
+    }
+  }
+
+  pub(crate) unsafe fn set_viewport(&mut self, viewport: [GLint; 4]) {
+    if self.viewport != viewport {
+      gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+      self.viewport = viewport;
+    }
+  }
+
+  pub(crate) fn viewport(&self) -> [GLint; 4] {
+    self.viewport
+  }
+
+  pub(crate) unsafe fn set_scissor(&mut self, scissor: Option<[GLint; 4]>) {
+    if self.scissor != scissor {
+      match scissor {
+        Some(b) => {
+          if self.scissor.is_none() {
+            gl::Enable(gl::SCISSOR_TEST);
+          }
+
+          gl::Scissor(b[0], b[1], b[2], b[3]);
+        }
+
+        None => gl::Disable(gl::SCISSOR_TEST)
+      }
+
+      self.scissor = scissor;
+    }
+  }
+
+  pub(crate) fn scissor(&self) -> Option<[GLint; 4]> {
+    self.scissor
+  }
+
+  pub(crate) fn bound_draw_framebuffer(&self) -> GLuint {
+    self.bound_draw_framebuffer
+  }
+
+  pub(crate) fn bound_read_framebuffer(&self) -> GLuint {
+    self.bound_read_framebuffer
+  }
+
+  pub(crate) fn current_program(&self) -> GLuint {
+    self.current_program
+  }
+
   pub(crate) unsafe fn bind_vertex_array(&mut self, handle: GLuint) {
     if self.bound_vertex_array != handle {
       gl::BindVertexArray(handle);
       self.bound_vertex_array = handle;
+      // the element array buffer binding comes with the vertex array, which we don’t track
+      self.bound_element_array_buffer = GLuint::MAX;
+      self.record_bind(true);
+    } else {
+      self.record_bind(false);
     }
   }
 
-  pub(crate) unsafe fn use_program(&mut self, handle: GLuint) {
+  pub(crate) fn current_program_tessellation(&self) -> bool {
+    self.current_program_tessellation
+  }
+
+  // Use a program; `tessellation` tells whether it has tessellation stages.
+  pub(crate) unsafe fn use_program(&mut self, handle: GLuint, tessellation: bool) {
     if self.current_program != handle {
       gl::UseProgram(handle);
       self.current_program = handle;
+      self.current_program_tessellation = tessellation;
+    }
+  }
+
+  /// Number of bind calls issued and skipped so far.
+  ///
+  /// Only available in debug builds.
+  #[cfg(debug_assertions)]
+  pub fn bind_stats(&self) -> BindStats {
+    self.bind_stats
+  }
+
+  // Count a bind call, issued or skipped, in debug builds.
+  #[inline]
+  fn record_bind(&mut self, issued: bool) {
+    #[cfg(debug_assertions)]
+    {
+      if issued {
+        self.bind_stats.issued += 1;
+      } else {
+        self.bind_stats.skipped += 1;
+      }
+    }
+
+    #[cfg(not(debug_assertions))]
+    let _ = issued;
+  }
+
+  // Forget about the bound textures and uniform and storage buffers, so that the next binds are not
+  // skipped.
+  fn invalidate_bindings(&mut self) {
+    for bound in &mut self.bound_textures {
+      *bound = (gl::NONE, 0);
+    }
+
+    for bound in &mut self.bound_uniform_buffers {
+      *bound = GLuint::MAX;
+    }
+
+    for bound in &mut self.bound_storage_buffers {
+      *bound = GLuint::MAX;
+    }
+  }
+
+  // Restore the state captured in `saved`, issuing only the required calls.
+  unsafe fn restore(&mut self, saved: &GraphicsState) {
+    self.set_blending_state(saved.blending_state);
+    self.set_blending_equation(saved.blending_equation.0, saved.blending_equation.1);
+
+    let (src, dest, alpha_src, alpha_dest) = saved.blending_func;
+    self.set_blending_func(src, dest, alpha_src, alpha_dest);
+    self.set_blending_constant(saved.blending_constant);
+    self.set_color_mask(saved.color_mask);
+    self.set_depth_test(saved.depth_test);
+    self.set_depth_comparison(saved.depth_comparison);
+    self.set_depth_write(saved.depth_write);
+    self.set_depth_bias(saved.depth_bias);
+    self.set_stencil_test_state(saved.stencil_test_state);
+    self.set_stencil_func(saved.stencil_func.0, saved.stencil_func.1, saved.stencil_func.2);
+
+    let (on_stencil_fail, on_depth_fail, on_pass) = saved.stencil_operations;
+    self.set_stencil_operations(on_stencil_fail, on_depth_fail, on_pass);
+    self.set_stencil_write_mask(saved.stencil_write_mask);
+    self.set_face_culling_state(saved.face_culling_state);
+    self.set_face_culling_order(saved.face_culling_order);
+    self.set_face_culling_mode(saved.face_culling_mode);
+    self.set_provoking_vertex(saved.provoking_vertex);
+    self.set_polygon_mode(saved.polygon_mode);
+    self.set_line_width(saved.line_width);
+    self.set_point_size(saved.point_size);
+    self.set_patch_vertices(saved.patch_vertices);
+    self.set_texture_unit(saved.current_texture_unit);
+    self.bind_array_buffer(saved.bound_array_buffer);
+    self.bind_draw_indirect_buffer(saved.bound_draw_indirect_buffer);
+    self.bind_draw_framebuffer(saved.bound_draw_framebuffer);
+    self.bind_read_framebuffer(saved.bound_read_framebuffer);
+    self.set_clamp_read_color(saved.clamp_read_color);
+    self.set_srgb_encoding(saved.srgb_encoding);
+    self.set_clear_depth(saved.clear_depth);
+    self.set_clear_stencil(saved.clear_stencil);
+    self.set_viewport(saved.viewport);
+    self.set_scissor(saved.scissor);
+    self.bind_vertex_array(saved.bound_vertex_array);
+    self.use_program(saved.current_program, saved.current_program_tessellation);
+    self.invalidate_bindings();
+  }
+}
+
+/// A guard protecting the OpenGL state when interleaving luminance with foreign OpenGL code.
+///
+/// When created – with `GraphicsContext::state_guard` – the guard captures the current OpenGL
+/// state and synchronizes luminance’s cache with it, so that luminance doesn’t skip calls based on
+/// a stale cache. When dropped, the guard restores the captured state, so that the foreign code
+/// finds it as it left it.
+///
+/// The following state is saved and restored:
+///
+///   - Blending state, equations and factors, for both RGB and alpha, and blending constant.
+///   - Color write mask.
+///   - Depth test, comparison and write mask.
+///   - Depth bias (polygon offset).
+///   - Stencil test state, function, operations and write mask.
+///   - Face culling state, order and mode.
+///   - Provoking vertex.
+///   - Polygon mode.
+///   - Line width and point size.
+///   - Number of vertices per patch.
+///   - Active texture unit.
+///   - Bound array and draw indirect buffers.
+///   - Bound draw and read framebuffers.
+///   - Read color clamping.
+///   - sRGB encoding of the framebuffer writes.
+///   - Depth and stencil clear values.
+///   - Bound vertex array – and with it, the element array buffer bound to it.
+///   - Current shader program.
+///   - Viewport.
+///   - Scissor test and box.
+///   - Clear color.
+///
+/// The textures bound to the texture units and the buffers bound to the uniform and shader storage
+/// buffer binding points are **not** restored. luminance forgets about them on both ends of the
+/// guard, so it’ll bind them again when needed.
+pub struct StateGuard {
+  state: Rc<RefCell<GraphicsState>>,
+  saved: GraphicsState,
+  clear_color: [GLfloat; 4]
+}
+
+impl StateGuard {
+  pub(crate) fn new(state: Rc<RefCell<GraphicsState>>) -> Result<Self, StateQueryError> {
+    let saved = GraphicsState::get_from_context()?;
+    let mut clear_color = [0.; 4];
+
+    unsafe {
+      gl::GetFloatv(gl::COLOR_CLEAR_VALUE, clear_color.as_mut_ptr());
+    }
+
+    // synchronize the cache with the actual state
+    {
+      let mut current = GraphicsState::get_from_context()?;
+      current.invalidate_bindings();
+
+      let mut state = state.borrow_mut();
+      // the debug callback is still registered; keep it alive
+      current.debug_callback = state.debug_callback.take();
+
+      #[cfg(debug_assertions)]
+      {
+        current.bind_stats = state.bind_stats;
+      }
+
+      *state = current;
+    }
+
+    Ok(StateGuard { state, saved, clear_color })
+  }
+}
+
+impl Drop for GraphicsState {
+  fn drop(&mut self) {
+    // OpenGL must not call the callback once it’s dropped
+    if self.debug_callback.is_some() {
+      unsafe { gl::DebugMessageCallback(debug_message_callback, ptr::null()) };
+    }
+  }
+}
+
+impl Drop for StateGuard {
+  fn drop(&mut self) {
+    unsafe {
+      self.state.borrow_mut().restore(&self.saved);
+
+      gl::ClearColor(self.clear_color[0], self.clear_color[1], self.clear_color[2], self.clear_color[3]);
     }
   }
 }
@@ -276,6 +999,48 @@ fn from_blending_equation(equation: Equation) -> GLenum {
   }
 }
 
+#[inline]
+fn from_depth_comparison(comparison: DepthComparison) -> GLenum {
+  match comparison {
+    DepthComparison::Never => gl::NEVER,
+    DepthComparison::Always => gl::ALWAYS,
+    DepthComparison::Equal => gl::EQUAL,
+    DepthComparison::NotEqual => gl::NOTEQUAL,
+    DepthComparison::Less => gl::LESS,
+    DepthComparison::LessOrEqual => gl::LEQUAL,
+    DepthComparison::Greater => gl::GREATER,
+    DepthComparison::GreaterOrEqual => gl::GEQUAL
+  }
+}
+
+#[inline]
+fn from_stencil_comparison(comparison: StencilComparison) -> GLenum {
+  match comparison {
+    StencilComparison::Never => gl::NEVER,
+    StencilComparison::Always => gl::ALWAYS,
+    StencilComparison::Equal => gl::EQUAL,
+    StencilComparison::NotEqual => gl::NOTEQUAL,
+    StencilComparison::Less => gl::LESS,
+    StencilComparison::LessOrEqual => gl::LEQUAL,
+    StencilComparison::Greater => gl::GREATER,
+    StencilComparison::GreaterOrEqual => gl::GEQUAL
+  }
+}
+
+#[inline]
+fn from_stencil_operation(operation: StencilOperation) -> GLenum {
+  match operation {
+    StencilOperation::Keep => gl::KEEP,
+    StencilOperation::Zero => gl::ZERO,
+    StencilOperation::Replace => gl::REPLACE,
+    StencilOperation::Increment => gl::INCR,
+    StencilOperation::IncrementWrap => gl::INCR_WRAP,
+    StencilOperation::Decrement => gl::DECR,
+    StencilOperation::DecrementWrap => gl::DECR_WRAP,
+    StencilOperation::Invert => gl::INVERT
+  }
+}
+
 #[inline]
 fn from_blending_factor(factor: Factor) -> GLenum {
   match factor {
@@ -289,7 +1054,11 @@ fn from_blending_factor(factor: Factor) -> GLenum {
     Factor::SrcAlphaComplement => gl::ONE_MINUS_SRC_ALPHA,
     Factor::DstAlpha => gl::DST_ALPHA,
     Factor::DstAlphaComplement => gl::ONE_MINUS_DST_ALPHA,
-    Factor::SrcAlphaSaturate => gl::SRC_ALPHA_SATURATE
+    Factor::SrcAlphaSaturate => gl::SRC_ALPHA_SATURATE,
+    Factor::ConstantColor => gl::CONSTANT_COLOR,
+    Factor::ConstantColorComplement => gl::ONE_MINUS_CONSTANT_COLOR,
+    Factor::ConstantAlpha => gl::CONSTANT_ALPHA,
+    Factor::ConstantAlphaComplement => gl::ONE_MINUS_CONSTANT_ALPHA
   }
 }
 
@@ -302,9 +1071,18 @@ pub enum StateQueryError {
   UnknownBlendingSrcFactor(GLenum),
   UnknownBlendingDstFactor(GLenum),
   UnknownDepthTestState(GLboolean),
+  UnknownDepthComparison(GLenum),
+  UnknownDepthBiasState(GLboolean),
+  UnknownStencilTestState(GLboolean),
+  UnknownStencilComparison(GLenum),
+  UnknownStencilOperation(GLenum),
   UnknownFaceCullingState(GLboolean),
   UnknownFaceCullingOrder(GLenum),
   UnknownFaceCullingMode(GLenum),
+  UnknownProvokingVertex(GLenum),
+  UnknownPolygonMode(GLenum),
+  UnknownClampReadColor(GLenum),
+  UnknownSRGBEncoding(GLboolean),
 }
 
 impl fmt::Display for StateQueryError {
@@ -316,9 +1094,18 @@ impl fmt::Display for StateQueryError {
       StateQueryError::UnknownBlendingSrcFactor(ref k) => write!(f, "unknown blending source factor: {}", k),
       StateQueryError::UnknownBlendingDstFactor(ref k) => write!(f, "unknown blending destination factor: {}", k),
       StateQueryError::UnknownDepthTestState(ref s) => write!(f, "unknown depth test state: {}", s),
+      StateQueryError::UnknownDepthComparison(ref c) => write!(f, "unknown depth comparison: {}", c),
+      StateQueryError::UnknownDepthBiasState(ref s) => write!(f, "unknown depth bias state: {}", s),
+      StateQueryError::UnknownStencilTestState(ref s) => write!(f, "unknown stencil test state: {}", s),
+      StateQueryError::UnknownStencilComparison(ref c) => write!(f, "unknown stencil comparison: {}", c),
+      StateQueryError::UnknownStencilOperation(ref o) => write!(f, "unknown stencil operation: {}", o),
       StateQueryError::UnknownFaceCullingState(ref s) => write!(f, "unknown face culling state: {}", s),
       StateQueryError::UnknownFaceCullingOrder(ref o) => write!(f, "unknown face culling order: {}", o),
-      StateQueryError::UnknownFaceCullingMode(ref m) => write!(f, "unknown face culling mode: {}", m)
+      StateQueryError::UnknownFaceCullingMode(ref m) => write!(f, "unknown face culling mode: {}", m),
+      StateQueryError::UnknownProvokingVertex(ref v) => write!(f, "unknown provoking vertex: {}", v),
+      StateQueryError::UnknownPolygonMode(ref m) => write!(f, "unknown polygon mode: {}", m),
+      StateQueryError::UnknownClampReadColor(ref c) => write!(f, "unknown clamp read color: {}", c),
+      StateQueryError::UnknownSRGBEncoding(ref e) => write!(f, "unknown sRGB encoding: {}", e)
     }
   }
 }
@@ -335,9 +1122,16 @@ unsafe fn get_ctx_blending_state() -> Result<BlendingState, StateQueryError> {
   }
 }
 
-unsafe fn get_ctx_blending_equation() -> Result<Equation, StateQueryError> {
+unsafe fn get_ctx_blending_equation() -> Result<(Equation, Equation), StateQueryError> {
+  let rgb = get_ctx_blending_equation_of(gl::BLEND_EQUATION_RGB)?;
+  let alpha = get_ctx_blending_equation_of(gl::BLEND_EQUATION_ALPHA)?;
+
+  Ok((rgb, alpha))
+}
+
+unsafe fn get_ctx_blending_equation_of(pname: GLenum) -> Result<Equation, StateQueryError> {
   let mut data = gl::FUNC_ADD as GLint;
-  gl::GetIntegerv(gl::BLEND_EQUATION_RGB, &mut data);
+  gl::GetIntegerv(pname, &mut data);
 
   let data = data as GLenum;
   match data {
@@ -350,12 +1144,19 @@ unsafe fn get_ctx_blending_equation() -> Result<Equation, StateQueryError> {
   }
 }
 
-unsafe fn get_ctx_blending_factors() -> Result<(Factor, Factor), StateQueryError> {
+unsafe fn get_ctx_blending_factors() -> Result<(Factor, Factor, Factor, Factor), StateQueryError> {
+  let (src_k, dst_k) = get_ctx_blending_factors_of(gl::BLEND_SRC_RGB, gl::BLEND_DST_RGB)?;
+  let (alpha_src_k, alpha_dst_k) = get_ctx_blending_factors_of(gl::BLEND_SRC_ALPHA, gl::BLEND_DST_ALPHA)?;
+
+  Ok((src_k, dst_k, alpha_src_k, alpha_dst_k))
+}
+
+unsafe fn get_ctx_blending_factors_of(src_pname: GLenum, dst_pname: GLenum) -> Result<(Factor, Factor), StateQueryError> {
   let mut src = gl::ONE as GLint;
   let mut dst = gl::ZERO as GLint;
 
-  gl::GetIntegerv(gl::BLEND_SRC_RGB, &mut src);
-  gl::GetIntegerv(gl::BLEND_DST_RGB, &mut dst);
+  gl::GetIntegerv(src_pname, &mut src);
+  gl::GetIntegerv(dst_pname, &mut dst);
 
   let src_k = from_gl_blending_factor(src as GLenum).map_err(StateQueryError::UnknownBlendingSrcFactor)?;
   let dst_k = from_gl_blending_factor(dst as GLenum).map_err(StateQueryError::UnknownBlendingDstFactor)?;
@@ -377,10 +1178,27 @@ fn from_gl_blending_factor(factor: GLenum) -> Result<Factor, GLenum> {
     gl::DST_ALPHA => Ok(Factor::DstAlpha),
     gl::ONE_MINUS_DST_ALPHA => Ok(Factor::DstAlphaComplement),
     gl::SRC_ALPHA_SATURATE => Ok(Factor::SrcAlphaSaturate),
+    gl::CONSTANT_COLOR => Ok(Factor::ConstantColor),
+    gl::ONE_MINUS_CONSTANT_COLOR => Ok(Factor::ConstantColorComplement),
+    gl::CONSTANT_ALPHA => Ok(Factor::ConstantAlpha),
+    gl::ONE_MINUS_CONSTANT_ALPHA => Ok(Factor::ConstantAlphaComplement),
     _ => Err(factor)
   }
 }
 
+unsafe fn get_ctx_blending_constant() -> [GLfloat; 4] {
+  let mut constant = [0.; 4];
+  gl::GetFloatv(gl::BLEND_COLOR, constant.as_mut_ptr());
+  constant
+}
+
+unsafe fn get_ctx_color_mask() -> Result<[bool; 4], StateQueryError> {
+  let mut mask = [gl::TRUE; 4];
+  gl::GetBooleanv(gl::COLOR_WRITEMASK, mask.as_mut_ptr());
+
+  Ok([mask[0] != gl::FALSE, mask[1] != gl::FALSE, mask[2] != gl::FALSE, mask[3] != gl::FALSE])
+}
+
 unsafe fn get_ctx_depth_test() -> Result<DepthTest, StateQueryError> {
   let state = gl::IsEnabled(gl::DEPTH_TEST);
 
@@ -391,6 +1209,119 @@ unsafe fn get_ctx_depth_test() -> Result<DepthTest, StateQueryError> {
   }
 }
 
+unsafe fn get_ctx_depth_comparison() -> Result<DepthComparison, StateQueryError> {
+  let comparison = get_ctx_integer(gl::DEPTH_FUNC) as GLenum;
+
+  match comparison {
+    gl::NEVER => Ok(DepthComparison::Never),
+    gl::ALWAYS => Ok(DepthComparison::Always),
+    gl::EQUAL => Ok(DepthComparison::Equal),
+    gl::NOTEQUAL => Ok(DepthComparison::NotEqual),
+    gl::LESS => Ok(DepthComparison::Less),
+    gl::LEQUAL => Ok(DepthComparison::LessOrEqual),
+    gl::GREATER => Ok(DepthComparison::Greater),
+    gl::GEQUAL => Ok(DepthComparison::GreaterOrEqual),
+    _ => Err(StateQueryError::UnknownDepthComparison(comparison))
+  }
+}
+
+unsafe fn get_ctx_depth_write() -> Result<DepthWrite, StateQueryError> {
+  let mut mask = gl::FALSE;
+  gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut mask);
+
+  if mask == gl::FALSE {
+    Ok(DepthWrite::Disabled)
+  } else {
+    Ok(DepthWrite::Enabled)
+  }
+}
+
+unsafe fn get_ctx_depth_bias() -> Result<Option<DepthBias>, StateQueryError> {
+  let state = gl::IsEnabled(gl::POLYGON_OFFSET_FILL);
+
+  match state {
+    gl::TRUE => {
+      let mut factor = 0.;
+      let mut units = 0.;
+      gl::GetFloatv(gl::POLYGON_OFFSET_FACTOR, &mut factor);
+      gl::GetFloatv(gl::POLYGON_OFFSET_UNITS, &mut units);
+
+      let mut clamp = 0.;
+
+      if polygon_offset_clamp_available() {
+        gl::GetFloatv(POLYGON_OFFSET_CLAMP, &mut clamp);
+      }
+
+      Ok(Some(DepthBias::new(factor, units).set_clamp(clamp)))
+    }
+    gl::FALSE => Ok(None),
+    _ => Err(StateQueryError::UnknownDepthBiasState(state))
+  }
+}
+
+unsafe fn get_ctx_patch_vertices() -> GLint {
+  // patches require OpenGL 4.0; querying them on older contexts leaves a GL_INVALID_ENUM error
+  if gl_version() >= (4, 0) || has_extension(&[b"GL_ARB_tessellation_shader"]) {
+    get_ctx_integer(gl::PATCH_VERTICES)
+  } else {
+    3 // default number of vertices per patch
+  }
+}
+
+unsafe fn get_ctx_stencil_test_state() -> Result<StencilTestState, StateQueryError> {
+  let state = gl::IsEnabled(gl::STENCIL_TEST);
+
+  match state {
+    gl::TRUE => Ok(StencilTestState::Enabled),
+    gl::FALSE => Ok(StencilTestState::Disabled),
+    _ => Err(StateQueryError::UnknownStencilTestState(state))
+  }
+}
+
+unsafe fn get_ctx_stencil_func() -> Result<(StencilComparison, GLint, GLuint), StateQueryError> {
+  let comparison = get_ctx_integer(gl::STENCIL_FUNC) as GLenum;
+  let reference = get_ctx_integer(gl::STENCIL_REF);
+  let read_mask = get_ctx_integer(gl::STENCIL_VALUE_MASK) as GLuint;
+
+  let comparison = match comparison {
+    gl::NEVER => StencilComparison::Never,
+    gl::ALWAYS => StencilComparison::Always,
+    gl::EQUAL => StencilComparison::Equal,
+    gl::NOTEQUAL => StencilComparison::NotEqual,
+    gl::LESS => StencilComparison::Less,
+    gl::LEQUAL => StencilComparison::LessOrEqual,
+    gl::GREATER => StencilComparison::Greater,
+    gl::GEQUAL => StencilComparison::GreaterOrEqual,
+    _ => return Err(StateQueryError::UnknownStencilComparison(comparison))
+  };
+
+  Ok((comparison, reference, read_mask))
+}
+
+unsafe fn get_ctx_stencil_operations() -> Result<(StencilOperation, StencilOperation, StencilOperation), StateQueryError> {
+  let on_stencil_fail = get_ctx_stencil_operation(gl::STENCIL_FAIL)?;
+  let on_depth_fail = get_ctx_stencil_operation(gl::STENCIL_PASS_DEPTH_FAIL)?;
+  let on_pass = get_ctx_stencil_operation(gl::STENCIL_PASS_DEPTH_PASS)?;
+
+  Ok((on_stencil_fail, on_depth_fail, on_pass))
+}
+
+unsafe fn get_ctx_stencil_operation(pname: GLenum) -> Result<StencilOperation, StateQueryError> {
+  let operation = get_ctx_integer(pname) as GLenum;
+
+  match operation {
+    gl::KEEP => Ok(StencilOperation::Keep),
+    gl::ZERO => Ok(StencilOperation::Zero),
+    gl::REPLACE => Ok(StencilOperation::Replace),
+    gl::INCR => Ok(StencilOperation::Increment),
+    gl::INCR_WRAP => Ok(StencilOperation::IncrementWrap),
+    gl::DECR => Ok(StencilOperation::Decrement),
+    gl::DECR_WRAP => Ok(StencilOperation::DecrementWrap),
+    gl::INVERT => Ok(StencilOperation::Invert),
+    _ => Err(StateQueryError::UnknownStencilOperation(operation))
+  }
+}
+
 unsafe fn get_ctx_face_culling_state() -> Result<FaceCullingState, StateQueryError> {
   let state = gl::IsEnabled(gl::CULL_FACE);
 
@@ -426,10 +1357,59 @@ unsafe fn get_ctx_face_culling_mode() -> Result<FaceCullingMode, StateQueryError
   }
 }
 
+unsafe fn get_ctx_provoking_vertex() -> Result<ProvokingVertex, StateQueryError> {
+  let mut provoking_vertex = gl::LAST_VERTEX_CONVENTION as GLint;
+  gl::GetIntegerv(gl::PROVOKING_VERTEX, &mut provoking_vertex);
+
+  let provoking_vertex = provoking_vertex as GLenum;
+  match provoking_vertex {
+    gl::FIRST_VERTEX_CONVENTION => Ok(ProvokingVertex::First),
+    gl::LAST_VERTEX_CONVENTION => Ok(ProvokingVertex::Last),
+    _ => Err(StateQueryError::UnknownProvokingVertex(provoking_vertex))
+  }
+}
+
+unsafe fn get_ctx_polygon_mode() -> Result<PolygonMode, StateQueryError> {
+  // some implementations return both the front and back modes; they’re always the same in core
+  let mut polygon_mode = [gl::FILL as GLint; 2];
+  gl::GetIntegerv(gl::POLYGON_MODE, polygon_mode.as_mut_ptr());
+
+  let polygon_mode = polygon_mode[0] as GLenum;
+  match polygon_mode {
+    gl::FILL => Ok(PolygonMode::Fill),
+    gl::LINE => Ok(PolygonMode::Line),
+    gl::POINT => Ok(PolygonMode::Point),
+    _ => Err(StateQueryError::UnknownPolygonMode(polygon_mode))
+  }
+}
+
 unsafe fn get_ctx_current_texture_unit() -> Result<GLenum, StateQueryError> {
   let mut active_texture = gl::TEXTURE0 as GLint;
   gl::GetIntegerv(gl::ACTIVE_TEXTURE, &mut active_texture);
-  Ok(active_texture as GLenum)
+  Ok(active_texture as GLenum - gl::TEXTURE0)
+}
+
+unsafe fn get_ctx_bound_array_buffer() -> Result<GLuint, StateQueryError> {
+  let mut bound = 0 as GLint;
+  gl::GetIntegerv(gl::ARRAY_BUFFER_BINDING, &mut bound);
+  Ok(bound as GLuint)
+}
+
+unsafe fn get_ctx_bound_element_array_buffer() -> Result<GLuint, StateQueryError> {
+  let mut bound = 0 as GLint;
+  gl::GetIntegerv(gl::ELEMENT_ARRAY_BUFFER_BINDING, &mut bound);
+  Ok(bound as GLuint)
+}
+
+unsafe fn get_ctx_bound_draw_indirect_buffer() -> Result<GLuint, StateQueryError> {
+  // indirect draws require OpenGL 4.0; nothing can be bound there on older contexts
+  if gl_version() < (4, 0) && !has_extension(&[b"GL_ARB_draw_indirect"]) {
+    return Ok(0);
+  }
+
+  let mut bound = 0 as GLint;
+  gl::GetIntegerv(gl::DRAW_INDIRECT_BUFFER_BINDING, &mut bound);
+  Ok(bound as GLuint)
 }
 
 unsafe fn get_ctx_bound_draw_framebuffer() -> Result<GLuint, StateQueryError> {
@@ -438,6 +1418,94 @@ unsafe fn get_ctx_bound_draw_framebuffer() -> Result<GLuint, StateQueryError> {
   Ok(bound as GLuint)
 }
 
+unsafe fn get_ctx_bound_read_framebuffer() -> Result<GLuint, StateQueryError> {
+  let mut bound = 0 as GLint;
+  gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut bound);
+  Ok(bound as GLuint)
+}
+
+unsafe fn get_ctx_clamp_read_color() -> Result<ClampColor, StateQueryError> {
+  let mut clamp = gl::FIXED_ONLY as GLint;
+  gl::GetIntegerv(gl::CLAMP_READ_COLOR, &mut clamp);
+
+  let clamp = clamp as GLenum;
+  match clamp {
+    gl::FIXED_ONLY => Ok(ClampColor::FixedOnly),
+    c if c == gl::TRUE as GLenum => Ok(ClampColor::Enabled),
+    c if c == gl::FALSE as GLenum => Ok(ClampColor::Disabled),
+    _ => Err(StateQueryError::UnknownClampReadColor(clamp))
+  }
+}
+
+unsafe fn get_ctx_integer(name: GLenum) -> GLint {
+  let mut value = 0;
+  gl::GetIntegerv(name, &mut value);
+  value
+}
+
+unsafe fn get_ctx_float(name: GLenum) -> GLfloat {
+  let mut value = 0.;
+  gl::GetFloatv(name, &mut value);
+  value
+}
+
+unsafe fn get_ctx_float_range(name: GLenum) -> [GLfloat; 2] {
+  let mut range = [1.; 2];
+  gl::GetFloatv(name, range.as_mut_ptr());
+  range
+}
+
+// Get the OpenGL version of the current context, as (major, minor).
+pub(crate) fn gl_version() -> (GLint, GLint) {
+  unsafe { (get_ctx_integer(gl::MAJOR_VERSION), get_ctx_integer(gl::MINOR_VERSION)) }
+}
+
+// Check whether the current context supports any of the given extensions.
+pub(crate) fn has_extension(names: &[&[u8]]) -> bool {
+  unsafe {
+    let ext_nb = get_ctx_integer(gl::NUM_EXTENSIONS);
+
+    (0..ext_nb as GLuint).any(|i| {
+      let ext = CStr::from_ptr(gl::GetStringi(gl::EXTENSIONS, i) as *const c_char);
+      names.contains(&ext.to_bytes())
+    })
+  }
+}
+
+unsafe fn get_ctx_viewport() -> Result<[GLint; 4], StateQueryError> {
+  let mut viewport = [0; 4];
+  gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+  Ok(viewport)
+}
+
+unsafe fn get_ctx_srgb_encoding() -> Result<SRGBEncoding, StateQueryError> {
+  let state = gl::IsEnabled(gl::FRAMEBUFFER_SRGB);
+
+  match state {
+    gl::TRUE => Ok(SRGBEncoding::Enabled),
+    gl::FALSE => Ok(SRGBEncoding::Disabled),
+    _ => Err(StateQueryError::UnknownSRGBEncoding(state))
+  }
+}
+
+unsafe fn get_ctx_scissor() -> Result<Option<[GLint; 4]>, StateQueryError> {
+  if gl::IsEnabled(gl::SCISSOR_TEST) == gl::FALSE {
+    return Ok(None);
+  }
+
+  let mut scissor = [0; 4];
+  gl::GetIntegerv(gl::SCISSOR_BOX, scissor.as_mut_ptr());
+  Ok(Some(scissor))
+}
+
+unsafe fn get_ctx_point_size() -> Result<Option<GLfloat>, StateQueryError> {
+  if gl::IsEnabled(gl::PROGRAM_POINT_SIZE) == gl::TRUE {
+    return Ok(None);
+  }
+
+  Ok(Some(get_ctx_float(gl::POINT_SIZE)))
+}
+
 unsafe fn get_ctx_bound_vertex_array() -> Result<GLuint, StateQueryError> {
   let mut bound = 0 as GLint;
   gl::GetIntegerv(gl::VERTEX_ARRAY_BINDING, &mut bound);
@@ -449,3 +1517,145 @@ unsafe fn get_ctx_current_program() -> Result<GLuint, StateQueryError> {
   gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut used);
   Ok(used as GLuint)
 }
+
+#[cfg(test)]
+impl GraphicsState {
+  // The state of a freshly created context, without querying one.
+  pub(crate) fn stub() -> Self {
+    GraphicsState {
+      _a: PhantomData,
+      blending_state: BlendingState::Disabled,
+      blending_equation: (Equation::Additive, Equation::Additive),
+      blending_func: (Factor::One, Factor::Zero, Factor::One, Factor::Zero),
+      blending_constant: [0.; 4],
+      color_mask: [true; 4],
+      depth_test: DepthTest::Disabled,
+      depth_comparison: DepthComparison::Less,
+      depth_write: DepthWrite::Enabled,
+      depth_bias: None,
+      stencil_test_state: StencilTestState::Disabled,
+      stencil_func: (StencilComparison::Always, 0, GLuint::MAX),
+      stencil_operations: (StencilOperation::Keep, StencilOperation::Keep, StencilOperation::Keep),
+      stencil_write_mask: GLuint::MAX,
+      face_culling_state: FaceCullingState::Disabled,
+      face_culling_order: FaceCullingOrder::CCW,
+      face_culling_mode: FaceCullingMode::Back,
+      provoking_vertex: ProvokingVertex::Last,
+      polygon_mode: PolygonMode::Fill,
+      line_width: 1.,
+      line_width_range: [1., 1.],
+      point_size: Some(1.),
+      point_size_range: [1., 1.],
+      patch_vertices: 3,
+      current_texture_unit: 0,
+      bound_textures: vec![(gl::TEXTURE_2D, 0); 48],
+      max_texture_units: 48,
+      bound_uniform_buffers: vec![0; 36],
+      uniform_buffer_offset_alignment: 256,
+      max_uniform_block_size: 16384,
+      bound_storage_buffers: vec![0; 8],
+      bound_array_buffer: 0,
+      bound_element_array_buffer: 0,
+      bound_draw_indirect_buffer: 0,
+      bound_draw_framebuffer: 0,
+      bound_read_framebuffer: 0,
+      clamp_read_color: ClampColor::FixedOnly,
+      srgb_encoding: SRGBEncoding::Disabled,
+      clear_depth: 1.,
+      clear_stencil: 0,
+      viewport: [0, 0, 800, 600],
+      scissor: None,
+      bound_vertex_array: 0,
+      current_program: 0,
+      current_program_tessellation: false,
+      capabilities: None,
+      debug_callback: None,
+      #[cfg(debug_assertions)]
+      bind_stats: BindStats::default()
+    }
+  }
+}
+
+// the bind counters only exist in debug builds
+#[cfg(all(test, debug_assertions))]
+mod tests {
+  use super::*;
+  use gl_stubs;
+
+  #[test]
+  fn patch_vertices_not_queried_before_gl_4() {
+    gl_stubs::load();
+
+    assert_eq!(unsafe { get_ctx_patch_vertices() }, 3);
+    assert!(!gl_stubs::queries().contains(&gl::PATCH_VERTICES));
+  }
+
+  #[test]
+  fn draw_indirect_buffer_not_queried_before_gl_4() {
+    gl_stubs::load();
+
+    assert_eq!(unsafe { get_ctx_bound_draw_indirect_buffer() }.unwrap(), 0);
+    assert!(!gl_stubs::queries().contains(&gl::DRAW_INDIRECT_BUFFER_BINDING));
+  }
+
+  // The binds issued by an indexed draw reading a texture and a uniform buffer.
+  unsafe fn draw(state: &mut GraphicsState, vao: GLuint, ibo: GLuint) {
+    state.bind_vertex_array(vao);
+    state.bind_element_array_buffer(ibo);
+    state.set_texture_unit(2);
+    state.bind_texture(gl::TEXTURE_2D, 7);
+    state.bind_buffer_base(9, 1);
+  }
+
+  #[test]
+  fn second_identical_draw_binds_nothing() {
+    gl_stubs::load();
+    let mut state = GraphicsState::stub();
+
+    unsafe { draw(&mut state, 1, 2) };
+    let first = state.bind_stats();
+    assert_eq!(first, BindStats { issued: 4, skipped: 0 });
+
+    unsafe { draw(&mut state, 1, 2) };
+    let second = state.bind_stats();
+    assert_eq!(second.issued, first.issued);
+    assert_eq!(second.skipped, first.skipped + 4);
+  }
+
+  #[test]
+  fn vertex_array_switch_invalidates_element_array_buffer() {
+    gl_stubs::load();
+    let mut state = GraphicsState::stub();
+
+    unsafe {
+      state.bind_vertex_array(1);
+      state.bind_element_array_buffer(2);
+      state.bind_vertex_array(3);
+      state.bind_vertex_array(1);
+      state.bind_element_array_buffer(2);
+    }
+
+    assert_eq!(state.bind_stats(), BindStats { issued: 5, skipped: 0 });
+  }
+
+  #[test]
+  fn texture_and_uniform_buffer_binds_cached_per_unit() {
+    gl_stubs::load();
+    let mut state = GraphicsState::stub();
+
+    unsafe {
+      state.set_texture_unit(0);
+      state.bind_texture(gl::TEXTURE_2D, 7);
+      state.set_texture_unit(1);
+      state.bind_texture(gl::TEXTURE_2D, 7);
+      state.set_texture_unit(0);
+      state.bind_texture(gl::TEXTURE_2D, 7);
+
+      state.bind_buffer_base(9, 0);
+      state.bind_buffer_base(9, 1);
+      state.bind_buffer_base(9, 0);
+    }
+
+    assert_eq!(state.bind_stats(), BindStats { issued: 4, skipped: 2 });
+  }
+}
@@ -0,0 +1,361 @@
+//! std140 uniform block layout.
+//!
+//! Uniform blocks declared with `layout (std140)` in GLSL follow a fixed set of alignment and size
+//! rules, which differ from the ones Rust uses for `#[repr(C)]` structures. The most surprising
+//! ones are:
+//!
+//!   - Scalars (`float`, `int`, `uint`) are aligned to 4 bytes.
+//!   - `vec2` is aligned to 8 bytes, `vec3` and `vec4` are aligned to 16 bytes. A `vec3` is only
+//!     12 bytes long, though, so a scalar can be packed right after it.
+//!   - Every element of an array is aligned to 16 bytes: a `float[4]` takes 64 bytes, not 16.
+//!   - Matrices are stored as arrays of column vectors: a `mat4` is 64 bytes but a `mat3` is 48
+//!     bytes (three `vec3` columns with a 16-byte stride).
+//!   - Structures are aligned to 16 bytes and their size is rounded up to a multiple of 16 bytes.
+//!
+//! A type which layout doesn’t follow these rules still uploads fine but renders garbage. The
+//! `uniform_block!` macro declares a `#[repr(C)]` structure and checks *at compile-time* that
+//! every field sits at the offset required by std140:
+//!
+//! ```ignore
+//! uniform_block! {
+//!   struct Light {
+//!     position: [f32; 3], // vec3, offset 0
+//!     intensity: f32,     // float, offset 12 – packed after the vec3
+//!     color: [f32; 4]     // vec4, offset 16
+//!   }
+//! }
+//!
+//! assert_eq!(Light::STD140_SIZE, 32);
+//! ```
+//!
+//! If a field is misplaced, compilation fails and tells you which field needs padding before it.
+//! In that case, add explicit padding fields – `_pad: [f32; N]` – until the layouts match.
+//!
+//! Fields can be of any type implementing `Std140`: scalars, vectors, `M44`, `Std140Array` and
+//! other structures declared with `uniform_block!`. `M22` and `M33` are not supported because
+//! their Rust representation lacks the padding of the std140 columns; use an `M44` or a
+//! `Std140Array` of vectors instead.
+
+use std::ops::{Index, IndexMut};
+
+use buffer::UniformBlock;
+use linear::M44;
+
+/// Types with a known std140 layout.
+///
+/// # Safety
+///
+/// `ALIGN` and `SIZE` must be the std140 base alignment and size of the type, and the size of the
+/// Rust type must be equal to `SIZE`. They must also agree with `UniformBlock::std140_align` and
+/// `UniformBlock::std140_size`.
+pub unsafe trait Std140: UniformBlock {
+  /// Base alignment, in bytes.
+  const ALIGN: usize;
+  /// Size, in bytes.
+  const SIZE: usize;
+}
+
+macro_rules! impl_std140 {
+  ($t:ty, $align:expr, $size:expr) => {
+    unsafe impl Std140 for $t {
+      const ALIGN: usize = $align;
+      const SIZE: usize = $size;
+    }
+  }
+}
+
+macro_rules! impl_std140_vectors {
+  ($t:ty, $n:expr) => {
+    impl_std140!($t, $n, $n);
+    impl_std140!([$t; 2], 2 * $n, 2 * $n);
+    impl_std140!([$t; 3], 4 * $n, 3 * $n);
+    impl_std140!([$t; 4], 4 * $n, 4 * $n);
+  }
+}
+
+impl_std140_vectors!(i32, 4);
+impl_std140_vectors!(u32, 4);
+impl_std140_vectors!(f32, 4);
+impl_std140_vectors!(f64, 8);
+
+impl_std140!(M44, 16, 64);
+
+/// Round `offset` up to the next multiple of `align`.
+#[doc(hidden)]
+pub const fn align_up(offset: usize, align: usize) -> usize {
+  (offset + align - 1) / align * align
+}
+
+/// Alignment of a structure or array holding elements of alignment `align`.
+#[doc(hidden)]
+pub const fn aggregate_align(align: usize) -> usize {
+  if align < 16 { 16 } else { align_up(align, 16) }
+}
+
+/// An element of a `Std140Array`, padded to 16 bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C, align(16))]
+pub struct Std140Padded<T>(pub T);
+
+/// An array following the std140 layout, in which every element is aligned to 16 bytes.
+///
+/// This is the Rust counterpart of a GLSL array inside a std140 uniform block.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Std140Array<T, const N: usize>(pub [Std140Padded<T>; N]);
+
+impl<T, const N: usize> Std140Array<T, N> where T: Copy {
+  /// Create an array from its elements.
+  pub fn new(values: [T; N]) -> Self {
+    Std140Array(values.map(Std140Padded))
+  }
+}
+
+impl<T, const N: usize> Default for Std140Array<T, N> where T: Copy + Default {
+  fn default() -> Self {
+    Std140Array([Std140Padded::default(); N])
+  }
+}
+
+impl<T, const N: usize> Index<usize> for Std140Array<T, N> {
+  type Output = T;
+
+  fn index(&self, i: usize) -> &Self::Output {
+    &self.0[i].0
+  }
+}
+
+impl<T, const N: usize> IndexMut<usize> for Std140Array<T, N> {
+  fn index_mut(&mut self, i: usize) -> &mut Self::Output {
+    &mut self.0[i].0
+  }
+}
+
+unsafe impl<T, const N: usize> UniformBlock for Std140Array<T, N> where T: Std140 {
+  fn std140_align() -> usize {
+    <Self as Std140>::ALIGN
+  }
+
+  fn std140_size() -> usize {
+    <Self as Std140>::SIZE
+  }
+}
+
+unsafe impl<T, const N: usize> Std140 for Std140Array<T, N> where T: Std140 {
+  const ALIGN: usize = aggregate_align(T::ALIGN);
+  const SIZE: usize = N * align_up(T::SIZE, aggregate_align(T::ALIGN));
+}
+
+/// Offset of a field in a structure, in bytes, usable in constants.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! std140_offset_of {
+  ($name:ident, $field:ident) => {{
+    // only the address of the field is computed; the uninitialized memory is never read
+    let uninit = ::std::mem::MaybeUninit::<$name>::uninit();
+    let base = uninit.as_ptr();
+
+    unsafe {
+      let field = ::std::ptr::addr_of!((*base).$field) as *const u8;
+      field.offset_from(base as *const u8) as usize
+    }
+  }}
+}
+
+/// Declare a `#[repr(C)]` structure usable in a std140 uniform block.
+///
+/// The layout of the structure is checked at compile-time against the std140 rules and the
+/// structure gets a `STD140_SIZE` associated constant holding its std140 size, in bytes. See the
+/// documentation of the `std140` module for further details.
+#[macro_export]
+macro_rules! uniform_block {
+  (
+    $(#[$attr:meta])*
+    $vis:vis struct $name:ident {
+      $( $(#[$field_attr:meta])* $field_vis:vis $field:ident : $t:ty ),* $(,)*
+    }
+  ) => {
+    $(#[$attr])*
+    #[repr(C)]
+    $vis struct $name {
+      $( $(#[$field_attr])* $field_vis $field: $t ),*
+    }
+
+    impl $name {
+      /// Size of the structure in a std140 uniform block, in bytes.
+      pub const STD140_SIZE: usize = <$name as $crate::std140::Std140>::SIZE;
+    }
+
+    unsafe impl $crate::buffer::UniformBlock for $name {
+      fn std140_align() -> usize {
+        <$name as $crate::std140::Std140>::ALIGN
+      }
+
+      fn std140_size() -> usize {
+        <$name as $crate::std140::Std140>::SIZE
+      }
+    }
+
+    unsafe impl $crate::std140::Std140 for $name {
+      const ALIGN: usize = {
+        let mut align = 0;
+        $(
+          if <$t as $crate::std140::Std140>::ALIGN > align {
+            align = <$t as $crate::std140::Std140>::ALIGN;
+          }
+        )*
+        $crate::std140::aggregate_align(align)
+      };
+
+      const SIZE: usize = {
+        let mut offset = 0;
+        $(
+          offset = $crate::std140::align_up(offset, <$t as $crate::std140::Std140>::ALIGN);
+          offset += <$t as $crate::std140::Std140>::SIZE;
+        )*
+        $crate::std140::align_up(offset, <$name as $crate::std140::Std140>::ALIGN)
+      };
+    }
+
+    const _: () = {
+      let mut offset = 0;
+      $(
+        offset = $crate::std140::align_up(offset, <$t as $crate::std140::Std140>::ALIGN);
+        assert!(
+          $crate::std140_offset_of!($name, $field) == offset,
+          concat!(
+            "field `", stringify!($field), "` of `", stringify!($name),
+            "` doesn’t follow the std140 layout; add padding fields before it"
+          )
+        );
+        offset += <$t as $crate::std140::Std140>::SIZE;
+      )*
+      let _ = offset;
+      assert!(
+        ::std::mem::size_of::<$name>() == <$name as $crate::std140::Std140>::SIZE,
+        concat!(
+          "the size of `", stringify!($name),
+          "` doesn’t follow the std140 layout; add trailing padding fields"
+        )
+      );
+    };
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::mem;
+
+  use buffer::UniformBlock;
+  use linear::{M22, M33, M44};
+  use super::*;
+
+  uniform_block! {
+    struct Light {
+      position: [f32; 3],
+      intensity: f32,
+      color: [f32; 4]
+    }
+  }
+
+  uniform_block! {
+    struct Scene {
+      light: Light,
+      exposure: f32,
+      _pad0: f32,
+      _pad1: f32,
+      _pad2: f32,
+      transform: M44
+    }
+  }
+
+  fn layout<T>() -> (usize, usize, usize) where T: Std140 {
+    assert_eq!(T::std140_align(), T::ALIGN);
+    assert_eq!(T::std140_size(), T::SIZE);
+    (T::ALIGN, T::SIZE, mem::size_of::<T>())
+  }
+
+  #[test]
+  fn align_up_rounds_to_multiples() {
+    assert_eq!(align_up(0, 16), 0);
+    assert_eq!(align_up(1, 16), 16);
+    assert_eq!(align_up(16, 16), 16);
+    assert_eq!(align_up(17, 4), 20);
+    assert_eq!(aggregate_align(4), 16);
+    assert_eq!(aggregate_align(32), 32);
+  }
+
+  #[test]
+  fn scalars() {
+    assert_eq!(layout::<f32>(), (4, 4, 4));
+    assert_eq!(layout::<i32>(), (4, 4, 4));
+    assert_eq!(layout::<u32>(), (4, 4, 4));
+    assert_eq!(layout::<f64>(), (8, 8, 8));
+  }
+
+  #[test]
+  fn vectors() {
+    assert_eq!(layout::<[f32; 2]>(), (8, 8, 8));
+    assert_eq!(layout::<[f32; 3]>(), (16, 12, 12));
+    assert_eq!(layout::<[f32; 4]>(), (16, 16, 16));
+    assert_eq!(layout::<[f64; 3]>(), (32, 24, 24));
+    assert_eq!(layout::<[f64; 4]>(), (32, 32, 32));
+  }
+
+  #[test]
+  fn matrices() {
+    assert_eq!(layout::<M44>(), (16, 64, 64));
+
+    // the Rust representation of these lacks the column padding, hence no Std140 implementation
+    assert_eq!((M22::std140_align(), M22::std140_size()), (16, 32));
+    assert_eq!((M33::std140_align(), M33::std140_size()), (16, 48));
+  }
+
+  #[test]
+  fn arrays() {
+    assert_eq!(layout::<Std140Array<f32, 4>>(), (16, 64, 64));
+    assert_eq!(layout::<Std140Array<[f32; 3], 2>>(), (16, 32, 32));
+    assert_eq!(layout::<Std140Array<[f64; 3], 2>>(), (32, 64, 64));
+    assert_eq!(layout::<Std140Array<M44, 2>>(), (16, 128, 128));
+    assert_eq!(layout::<Std140Array<Light, 3>>(), (16, 96, 96));
+  }
+
+  #[test]
+  fn array_elements() {
+    let mut a = Std140Array::new([1., 2., 3.]);
+    a[1] = 4.;
+
+    assert_eq!((a[0], a[1], a[2]), (1., 4., 3.));
+    assert_eq!(Std140Array::<f32, 3>::default()[2], 0.);
+  }
+
+  #[test]
+  fn structures() {
+    assert_eq!(layout::<Light>(), (16, 32, 32));
+    assert_eq!(Light::STD140_SIZE, 32);
+
+    assert_eq!(layout::<Scene>(), (16, 112, 112));
+    assert_eq!(Scene::STD140_SIZE, 112);
+    assert_eq!(std140_offset_of!(Scene, exposure), 32);
+    assert_eq!(std140_offset_of!(Scene, transform), 48);
+  }
+
+  #[test]
+  fn small_scalars() {
+    // no GLSL counterpart: laid out as 32-bit scalars so that they never match the Rust size
+    assert_eq!((u8::std140_align(), u8::std140_size()), (4, 4));
+    assert_eq!((<[i16; 3]>::std140_align(), <[i16; 3]>::std140_size()), (16, 12));
+    assert_eq!((bool::std140_align(), bool::std140_size()), (4, 4));
+  }
+
+  #[test]
+  fn tuples() {
+    assert_eq!(<(f32, [f32; 3])>::std140_align(), 16);
+    assert_eq!(<(f32, [f32; 3])>::std140_size(), 32);
+    assert_eq!(<([f32; 3], f32)>::std140_size(), 16);
+    assert_eq!(<(f32, f32, f64)>::std140_size(), 16);
+    assert_eq!(<((f32, f32), f32)>::std140_size(), 32);
+    assert_eq!(<([f64; 3], f32)>::std140_align(), 32);
+    assert_eq!(<([f64; 3], f32)>::std140_size(), 32);
+  }
+}
@@ -0,0 +1,128 @@
+//! Stencil test related features.
+//!
+//! The stencil test compares, for every fragment, a reference value with the value stored in the
+//! stencil buffer, and discards the fragment if the comparison fails. Whatever the outcome, the
+//! stencil buffer can then be updated. That is typically used to mask regions of the screen: a
+//! first pass writes a shape into the stencil buffer and a second pass only renders where the shape
+//! was written – mirrors, portals, outlines, etc.
+//!
+//! > Note: the stencil test requires the framebuffer to have a stencil buffer. Without one, the
+//! > test always passes.
+
+/// Stencil test setup.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct StencilTest {
+  /// Comparison performed between the reference value and the stored value.
+  pub comparison: StencilComparison,
+  /// Reference value.
+  pub reference: u8,
+  /// Mask applied to both the reference and the stored value before comparing them.
+  pub read_mask: u8,
+  /// Mask of the bits of the stencil buffer that can be written.
+  pub write_mask: u8,
+  /// Operation to perform when the stencil test fails.
+  pub on_stencil_fail: StencilOperation,
+  /// Operation to perform when the stencil test passes but the depth test fails.
+  pub on_depth_fail: StencilOperation,
+  /// Operation to perform when both the stencil and the depth tests pass.
+  pub on_pass: StencilOperation
+}
+
+impl StencilTest {
+  /// Create a stencil test that compares `reference` with the stored value, all bits included,
+  /// and leaves the stencil buffer untouched.
+  pub fn new(comparison: StencilComparison, reference: u8) -> Self {
+    StencilTest {
+      comparison,
+      reference,
+      read_mask: 0xff,
+      write_mask: 0xff,
+      on_stencil_fail: StencilOperation::Keep,
+      on_depth_fail: StencilOperation::Keep,
+      on_pass: StencilOperation::Keep
+    }
+  }
+
+  pub fn set_read_mask(self, read_mask: u8) -> Self {
+    StencilTest {
+      read_mask,
+      .. self
+    }
+  }
+
+  pub fn set_write_mask(self, write_mask: u8) -> Self {
+    StencilTest {
+      write_mask,
+      .. self
+    }
+  }
+
+  /// Set the operations to perform when the stencil test fails, when the stencil test passes but
+  /// the depth test fails and when both tests pass, in that order.
+  pub fn set_operations(
+    self,
+    on_stencil_fail: StencilOperation,
+    on_depth_fail: StencilOperation,
+    on_pass: StencilOperation
+  ) -> Self {
+    StencilTest {
+      on_stencil_fail,
+      on_depth_fail,
+      on_pass,
+      .. self
+    }
+  }
+}
+
+/// Comparison performed by the stencil test between the reference value and the value stored in
+/// the stencil buffer, both masked with the read mask.
+///
+/// The fragment passes the test if the comparison holds.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StencilComparison {
+  /// The test never passes.
+  Never,
+  /// The test always passes.
+  Always,
+  /// The test passes if the values are equal.
+  Equal,
+  /// The test passes if the values are not equal.
+  NotEqual,
+  /// The test passes if the reference value is less than the stored one.
+  Less,
+  /// The test passes if the reference value is less than or equal to the stored one.
+  LessOrEqual,
+  /// The test passes if the reference value is greater than the stored one.
+  Greater,
+  /// The test passes if the reference value is greater than or equal to the stored one.
+  GreaterOrEqual
+}
+
+/// Operation to perform on the value stored in the stencil buffer.
+///
+/// Only the bits set in the write mask are affected.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StencilOperation {
+  /// Keep the stored value.
+  Keep,
+  /// Set the stored value to `0`.
+  Zero,
+  /// Replace the stored value with the reference value.
+  Replace,
+  /// Increment the stored value, clamping at the maximum value.
+  Increment,
+  /// Increment the stored value, wrapping to `0` past the maximum value.
+  IncrementWrap,
+  /// Decrement the stored value, clamping at `0`.
+  Decrement,
+  /// Decrement the stored value, wrapping to the maximum value below `0`.
+  DecrementWrap,
+  /// Invert the bits of the stored value.
+  Invert
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum StencilTestState {
+  Enabled,
+  Disabled
+}
@@ -9,7 +9,8 @@
 //! - *line strips*;
 //! - *triangles*;
 //! - *triangle fans*;
-//! - *triangle strips*.
+//! - *triangle strips*;
+//! - *patches*, for tessellation shaders.
 //!
 //! Those kinds of tessellation are designated by the `Mode` type.
 //!
@@ -18,6 +19,19 @@
 //! Creation is done via the `Tess::new` function. This function is polymorphing in the type of
 //! vertices you send. See the `TessVertices` type for further details.
 //!
+//! If you have a lot of tessellations sharing the same vertex type, you can make them share a
+//! single vertex array with a `VertexArrayFormat` and `Tess::with_format`.
+//!
+//! If you need to build up the vertices incrementally – for debug geometry, for instance – use a
+//! `GrowableTess`, which vertices can be pushed like in a `Vec`.
+//!
+//! If the layout of your vertices is only known at runtime, you can use `Tess::from_raw` with a
+//! `VertexLayout` instead.
+//!
+//! If your attributes are stored in separate arrays – positions, normals and texture coordinates,
+//! for instance – you don’t have to interleave them: a `TessBuilder` creates a tessellation
+//! reading each array from its own vertex buffer.
+//!
 //! # Tessellation vertices mapping
 //!
 //! It’s possible to map `Tess`’ vertices into your code. You’re provided with two types to do so:
@@ -25,26 +39,49 @@
 //! - `BufferSlice`, which gives you an immutable access to the vertices
 //! - `BufferSliceMut`, which gives you a mutable access to the vertices
 //!
-//! You can retrieve those slices with the `Tess::as_slice` and `Tess::as_slice_mut` methods. 
+//! You can retrieve those slices with the `Tess::as_slice` and `Tess::as_slice_mut` methods. The
+//! latter is the way to go to update the vertices of a tessellation every frame without
+//! reallocating its vertex buffer:
+//!
+//! ```ignore
+//! {
+//!   let mut vertices = tess.as_slice_mut()?;
+//!   vertices[1].0[0] += 0.1; // shift the second vertex along the X axis
+//! } // the vertex buffer is unmapped here; the next render uses the new vertices
+//! ```
 //!
 //! # Tessellation render
 //!
 //! In order to render a `Tess`, you have to use a `TessSlice` object. You’ll be able to use that
 //! object in *pipelines*. See the `pipeline` module for further details.
+//!
+//! # Indirect render
+//!
+//! When the render parameters are computed on the GPU – for instance by a compute shader culling
+//! instances – you don’t want to read them back on the CPU. You can instead render a `TessSlice`
+//! *indirectly*:
+//!
+//! - `TessSlice::render_indirect` reads the whole render command from a `Buffer<IndirectDraw>`.
+//! - `TessSlice::render_counted` renders the slice with as many instances as the current value of
+//!   an `AtomicCounter`.
 
 use gl;
 use gl::types::*;
+use std::cell::Cell;
 use std::error::Error;
 use std::fmt;
 use std::marker::PhantomData;
-use std::mem::size_of;
-use std::ops::{Range, RangeFull, RangeFrom, RangeTo};
+use std::mem::{size_of, size_of_val};
+use std::ops::{Bound, Range, RangeBounds, RangeFull, RangeFrom, RangeInclusive, RangeTo,
+               RangeToInclusive};
 use std::os::raw::c_void;
 use std::ptr;
+use std::rc::Rc;
 
-use buffer::{Buffer, BufferError, BufferSlice, BufferSliceMut, RawBuffer};
+use buffer::{AtomicCounter, Buffer, GrowableBuffer, BufferError, BufferSlice, BufferSliceMut, Pod, RawBuffer};
 use context::GraphicsContext;
-use vertex::{Dim, Type, Vertex, VertexComponentFormat};
+use state::gl_version;
+use vertex::{Dim, Type, Vertex, VertexComponentFormat, VertexLayout};
 
 /// Vertices can be connected via several modes.
 #[derive(Copy, Clone, Debug)]
@@ -60,14 +97,20 @@ pub enum Mode {
   /// A triangle fan, defined by at least three points and zero or many other ones.
   TriangleFan,
   /// A triangle strip, defined by at least three points and zero or many other ones.
-  TriangleStrip
+  TriangleStrip,
+  /// A patch, defined by the given number of points, processed by tessellation shaders.
+  ///
+  /// Patches can only be rendered with a program that has tessellation stages. Rendering a patch
+  /// tessellation with another program, or with `0` points per patch, panics.
+  Patch(u32)
 }
 
 /// Error that can occur while trying to map GPU tessellation to host code.
 #[derive(Debug, Eq, PartialEq)]
 pub enum TessMapError {
   VertexBufferMapFailed(BufferError),
-  ForbiddenAttributelessMapping
+  ForbiddenAttributelessMapping,
+  MissingIndexBuffer
 }
 
 impl fmt::Display for TessMapError {
@@ -80,12 +123,16 @@ impl fmt::Display for TessMapError {
       TessMapError::ForbiddenAttributelessMapping => {
         f.write_str("cannot map an attributeless buffer")
       }
+
+      TessMapError::MissingIndexBuffer => {
+        f.write_str("cannot read the indices of a non-indexed tessellation")
+      }
     }
   }
 }
 
 impl Error for TessMapError {
-  fn cause(&self) -> Option<&Error> {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
     match *self {
       TessMapError::VertexBufferMapFailed(ref e) => Some(e),
       _ => None
@@ -93,6 +140,31 @@ impl Error for TessMapError {
   }
 }
 
+/// Error that can occur while building a tessellation with a `TessBuilder`.
+#[derive(Debug, Eq, PartialEq)]
+pub enum TessError {
+  /// No vertex buffer was added to the builder.
+  NoVertices,
+  /// The vertex buffers don’t hold the same number of vertices. The first number is the number of
+  /// vertices of the first buffer and the second one the number of vertices of the mismatching
+  /// buffer.
+  MismatchedVertexCounts(usize, usize)
+}
+
+impl fmt::Display for TessError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match *self {
+      TessError::NoVertices => f.write_str("cannot build a tessellation without vertices"),
+
+      TessError::MismatchedVertexCounts(expected, found) => {
+        write!(f, "mismatched vertex counts: expected {} vertices, found {}", expected, found)
+      }
+    }
+  }
+}
+
+impl Error for TessError {}
+
 /// Accepted vertices for building tessellations.
 ///
 /// This type enables you to pass in a slice of vertices or ask for the GPU to only reserve enough
@@ -113,13 +185,69 @@ impl<'a, T> From<&'a [T]> for TessVertices<'a, T> where T: 'a + Vertex {
 
 /// GPU typed tessellation.
 ///
+/// Accepted indices for building tessellations.
+///
+/// Indices can be either 16-bit or 32-bit wide. 16-bit indices halve the size of the index buffer
+/// but can only address the 65536 first vertices. You don’t have to build this type yourself: a
+/// slice of `u16`, a slice of `u32` or `None` are all converted to it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TessIndices<'a> {
+  /// No indices: the vertices are used as-is.
+  None,
+  /// 16-bit indices.
+  U16(&'a [u16]),
+  /// 32-bit indices.
+  U32(&'a [u32])
+}
+
+impl<'a> From<&'a [u16]> for TessIndices<'a> {
+  fn from(indices: &'a [u16]) -> Self {
+    TessIndices::U16(indices)
+  }
+}
+
+impl<'a> From<&'a [u32]> for TessIndices<'a> {
+  fn from(indices: &'a [u32]) -> Self {
+    TessIndices::U32(indices)
+  }
+}
+
+impl<'a> From<Option<&'a [u32]>> for TessIndices<'a> {
+  fn from(indices: Option<&'a [u32]>) -> Self {
+    indices.map_or(TessIndices::None, TessIndices::U32)
+  }
+}
+
+// Create the index buffer of a tessellation, if any. Return the buffer, the number of indices and
+// the OpenGL type of the indices.
+fn create_index_buffer<C>(ctx: &mut C, indices: TessIndices) -> Option<(RawBuffer, usize, GLenum)>
+where C: GraphicsContext {
+  match indices {
+    TessIndices::None => None,
+
+    TessIndices::U16(indices) => {
+      let index_buffer = Buffer::from_slice(ctx, indices);
+      Some((index_buffer.to_raw(), indices.len(), gl::UNSIGNED_SHORT))
+    }
+
+    TessIndices::U32(indices) => {
+      let index_buffer = Buffer::from_slice(ctx, indices);
+      Some((index_buffer.to_raw(), indices.len(), gl::UNSIGNED_INT))
+    }
+  }
+}
+
 /// The tessellation is typed with the vertex type.
 pub struct Tess<V> {
-  mode: GLenum,
+  mode: Mode,
   vert_nb: usize,
   vao: GLenum,
   vbo: Option<RawBuffer>, // no vbo means attributeless render
+  _extra_vbos: Vec<RawBuffer>, // other vertex buffers of a deinterleaved tessellation
   ibo: Option<RawBuffer>,
+  index_type: GLenum, // type of the indices, if any
+  instance_buffer: Option<RawBuffer>, // per-instance attributes, if any
+  shared_vao: Option<Rc<SharedVertexArray>>, // vertex array shared with other tessellations
   _v: PhantomData<V>
 }
 
@@ -128,12 +256,12 @@ impl<V> Tess<V> where V: Vertex {
   ///
   /// The `mode` argument gives the type of the primitives and how to interpret the `vertices` and
   /// `indices` slices. If `indices` is set to `None`, the tessellation will use the `vertices`
-  /// as-is.
+  /// as-is. Indices can be passed as a slice of either `u16` or `u32`; see `TessIndices`.
   pub fn new<'a, C, W, I>(ctx: &mut C, mode: Mode, vertices: W, indices: I) -> Self
       where C: GraphicsContext,
             TessVertices<'a, V>: From<W>,
             V: 'a + Vertex,
-            I: Into<Option<&'a[u32]>> {
+            I: Into<TessIndices<'a>> {
     let vertices = vertices.into();
 
     let mut vao: GLuint = 0;
@@ -160,38 +288,29 @@ impl<V> Tess<V> where V: Vertex {
       ctx.state().borrow_mut().bind_array_buffer(raw_vbo.handle()); // FIXME: issue the call whatever the caching result
       set_vertex_pointers(&V::vertex_format());
 
-      // TODO: refactor this schiesse
       // in case of indexed render, create an index buffer
-      if let Some(indices) = indices.into() {
-        let ind_nb = indices.len();
-        let index_buffer = Buffer::new(ctx, ind_nb);
-        index_buffer.fill(indices).unwrap();
-
-        let raw_ibo = index_buffer.to_raw();
+      let (vert_nb, ibo, index_type) = match create_index_buffer(ctx, indices.into()) {
+        Some((raw_ibo, ind_nb, index_type)) => {
+          ctx.state().borrow_mut().bind_element_array_buffer(raw_ibo.handle());
+          (ind_nb, Some(raw_ibo), index_type)
+        }
 
-        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, raw_ibo.handle());
+        None => (vert_nb, None, gl::NONE)
+      };
 
-        ctx.state().borrow_mut().bind_vertex_array(vao);
+      ctx.state().borrow_mut().bind_vertex_array(vao);
 
-        Tess {
-          mode: opengl_mode(mode),
-          vert_nb: ind_nb,
-          vao: vao,
-          vbo: Some(raw_vbo),
-          ibo: Some(raw_ibo),
-          _v: PhantomData
-        }
-      } else {
-        ctx.state().borrow_mut().bind_vertex_array(vao);
-
-        Tess {
-          mode: opengl_mode(mode),
-          vert_nb: vert_nb,
-          vao: vao,
-          vbo: Some(raw_vbo),
-          ibo: None,
-          _v: PhantomData
-        }
+      Tess {
+        mode,
+        vert_nb,
+        vao,
+        vbo: Some(raw_vbo),
+        ibo,
+        index_type,
+        _extra_vbos: Vec::new(),
+        instance_buffer: None,
+        shared_vao: None,
+        _v: PhantomData
       }
     }
   }
@@ -210,15 +329,16 @@ impl<V> Tess<V> where V: Vertex {
     let inst_nb = inst_nb as GLsizei;
 
     unsafe {
-      ctx.state().borrow_mut().bind_vertex_array(self.vao);
+      self.bind_vertex_array(ctx);
+      let mode = prepare_mode(ctx, self.mode);
 
       if self.ibo.is_some() { // indexed render
-        let first = (size_of::<u32>() * start_index) as *const c_void;
+        let first = (index_size(self.index_type) * start_index) as *const c_void;
 
         if inst_nb == 1 {
-          gl::DrawElements(self.mode, vert_nb, gl::UNSIGNED_INT, first);
+          gl::DrawElements(mode, vert_nb, self.index_type, first);
         } else if inst_nb > 1 {
-          gl::DrawElementsInstanced(self.mode, vert_nb, gl::UNSIGNED_INT, first, inst_nb);
+          gl::DrawElementsInstanced(mode, vert_nb, self.index_type, first, inst_nb);
         } else {
           panic!("cannot index-render 0 instance");
         }
@@ -226,9 +346,9 @@ impl<V> Tess<V> where V: Vertex {
         let first = start_index as GLint;
 
         if inst_nb == 1 {
-          gl::DrawArrays(self.mode, first, vert_nb);
+          gl::DrawArrays(mode, first, vert_nb);
         } else if inst_nb > 1 {
-          gl::DrawArraysInstanced(self.mode, first, vert_nb, inst_nb);
+          gl::DrawArraysInstanced(mode, first, vert_nb, inst_nb);
         } else {
           panic!("cannot render 0 instance");
         }
@@ -236,19 +356,170 @@ impl<V> Tess<V> where V: Vertex {
     }
   }
 
+  // Bind the vertex array of the tessellation. If the vertex array is shared, the vertex and index
+  // buffers of the tessellation are bound to it as well.
+  unsafe fn bind_vertex_array<C>(&self, ctx: &mut C) where C: GraphicsContext {
+    ctx.state().borrow_mut().bind_vertex_array(self.vao);
+
+    if let Some(ref shared) = self.shared_vao {
+      let vbo = self.vbo.as_ref().map_or(0, |vbo| vbo.handle());
+      let ibo = self.ibo.as_ref().map_or(0, |ibo| ibo.handle());
+
+      // the buffers are part of the vertex array state; skip the binds if they’re already attached
+      if shared.attached.get() != (vbo, ibo) {
+        gl::BindVertexBuffer(0, vbo, 0, shared.stride);
+        ctx.state().borrow_mut().bind_element_array_buffer(ibo);
+        shared.attached.set((vbo, ibo));
+      }
+    }
+  }
+
+  // Render the tessellation with the indirect draw command found at `offset` bytes in the buffer
+  // which handle is passed as argument.
+  fn render_indirect<C>(&self, ctx: &mut C, buffer: GLuint, offset: usize) where C: GraphicsContext {
+    let indirect = offset as *const c_void;
+
+    unsafe {
+      self.bind_vertex_array(ctx);
+      let mode = prepare_mode(ctx, self.mode);
+      ctx.state().borrow_mut().bind_draw_indirect_buffer(buffer);
+
+      if self.ibo.is_some() {
+        gl::DrawElementsIndirect(mode, self.index_type, indirect);
+      } else {
+        gl::DrawArraysIndirect(mode, indirect);
+      }
+    }
+  }
+
+  // Render the vertices captured by the transform feedback object which handle is passed as
+  // argument.
+  pub(crate) fn render_transform_feedback<C>(
+    &self,
+    ctx: &mut C,
+    mode: Mode,
+    transform_feedback: GLuint
+  ) where C: GraphicsContext {
+    unsafe {
+      self.bind_vertex_array(ctx);
+      let mode = prepare_mode(ctx, mode);
+      gl::DrawTransformFeedback(mode, transform_feedback);
+    }
+  }
+
+  // Get the handle of the vertex buffer, if any.
+  pub(crate) fn vertex_buffer_handle(&self) -> Option<GLuint> {
+    self.vbo.as_ref().map(|vbo| vbo.handle())
+  }
+
   /// Get an immutable slice over the vertices stored on GPU.
-  pub fn as_slice(&self) -> Result<BufferSlice<V>, TessMapError> {
+  pub fn as_slice(&self) -> Result<BufferSlice<V>, TessMapError> where V: Pod {
     self.vbo.as_ref()
       .ok_or(TessMapError::ForbiddenAttributelessMapping)
       .and_then(|raw| RawBuffer::as_slice(raw).map_err(TessMapError::VertexBufferMapFailed))
   }
 
   /// Get a mutable slice over the vertices stored on GPU.
-  pub fn as_slice_mut<C>(&mut self) -> Result<BufferSliceMut<V>, TessMapError> {
+  ///
+  /// Use it to update the vertices in place – to animate them on the CPU, for instance – instead
+  /// of recreating the tessellation. The changes are visible to the next render, once the slice is
+  /// dropped.
+  ///
+  /// > Note: the slice is typed with the vertex type of the tessellation. Tessellations created with
+  /// > `Tess::from_raw` or a `TessBuilder` are typed `Tess<()>` and cannot be mapped that way.
+  pub fn as_slice_mut(&mut self) -> Result<BufferSliceMut<V>, TessMapError> where V: Pod {
     self.vbo.as_mut()
       .ok_or(TessMapError::ForbiddenAttributelessMapping)
       .and_then(|raw| RawBuffer::as_slice_mut(raw).map_err(TessMapError::VertexBufferMapFailed))
   }
+
+  /// Copy the vertices stored on GPU back into host memory.
+  ///
+  /// This is mostly useful to inspect vertices written on the GPU side – by a transform feedback,
+  /// for instance.
+  pub fn read_vertices(&self) -> Result<Vec<V>, TessMapError> where V: Copy + Pod {
+    let vbo = self.vbo.as_ref().ok_or(TessMapError::ForbiddenAttributelessMapping)?;
+    vbo.read(0, vbo.len()).map_err(TessMapError::VertexBufferMapFailed)
+  }
+
+  /// Copy a range of the vertices stored on GPU back into host memory.
+  pub fn read_vertices_range(&self, range: Range<usize>) -> Result<Vec<V>, TessMapError> where V: Copy + Pod {
+    let vbo = self.vbo.as_ref().ok_or(TessMapError::ForbiddenAttributelessMapping)?;
+    let nb = range.end.saturating_sub(range.start);
+
+    vbo.read(range.start, nb).map_err(TessMapError::VertexBufferMapFailed)
+  }
+
+  /// Copy the indices stored on GPU back into host memory.
+  ///
+  /// 16-bit indices are widened to `u32`.
+  pub fn read_indices(&self) -> Result<Vec<u32>, TessMapError> {
+    let ibo = self.ibo.as_ref().ok_or(TessMapError::MissingIndexBuffer)?;
+
+    if self.index_type == gl::UNSIGNED_SHORT {
+      let indices: Vec<u16> = ibo.read(0, ibo.len()).map_err(TessMapError::VertexBufferMapFailed)?;
+      Ok(indices.into_iter().map(u32::from).collect())
+    } else {
+      ibo.read(0, ibo.len()).map_err(TessMapError::VertexBufferMapFailed)
+    }
+  }
+
+  /// Set the per-instance attributes of the tessellation.
+  ///
+  /// Each value of `instances` is read by a single instance when rendering several instances of
+  /// the tessellation – see `TessSlice::render_instanced`. The attributes of `I` are assigned the
+  /// locations following the ones of `V`: if `V` has two attributes, at locations `0` and `1`, the
+  /// first attribute of `I` is at location `2`, and so on. Declare them that way in your vertex
+  /// shader:
+  ///
+  /// ```ignore
+  /// layout (location = 0) in vec2 co;
+  /// layout (location = 1) in vec3 color;
+  /// layout (location = 2) in vec2 offset; // per-instance
+  /// ```
+  ///
+  /// Calling this function again replaces the previous per-instance attributes. If they fit in the
+  /// buffer of the previous ones, that buffer is reused: its storage is orphaned and the new values
+  /// are uploaded without mapping it, so that updating the instances every frame doesn’t wait for
+  /// the draws of the previous frame.
+  ///
+  /// > Note: tessellations created with `Tess::from_raw` or a `TessBuilder` are typed `Tess<()>`, so
+  /// > their per-instance attributes start at location `0`. Make sure their vertex attributes don’t
+  /// > use the same locations.
+  ///
+  /// # Panic
+  ///
+  /// Panic if the tessellation shares its vertex array – see `Tess::with_format`.
+  pub fn set_instances<C, I>(&mut self, ctx: &mut C, instances: &[I]) where C: GraphicsContext, I: Vertex {
+    if self.shared_vao.is_some() {
+      panic!("cannot set the per-instance attributes of a tessellation sharing its vertex array");
+    }
+
+    let first = V::vertex_format().len() as u32;
+    let formats = I::vertex_format();
+    let buffer = match self.instance_buffer.take() {
+      Some(buffer) if buffer.bytes() >= size_of_val(instances) => {
+        buffer.stream(instances);
+        buffer
+      }
+
+      _ => Buffer::from_slice(ctx, instances).to_raw()
+    };
+
+    unsafe {
+      let mut state = ctx.state().borrow_mut();
+      state.bind_vertex_array(self.vao);
+      state.bind_array_buffer(buffer.handle());
+    }
+
+    set_vertex_pointers_at(first, &formats);
+
+    for i in 0..formats.len() as u32 {
+      unsafe { gl::VertexAttribDivisor(first + i, 1) };
+    }
+
+    self.instance_buffer = Some(buffer);
+  }
 }
 
 impl Tess<()> {
@@ -273,33 +544,376 @@ impl Tess<()> {
       gfx_state.bind_vertex_array(0);
 
       Tess {
-        mode: opengl_mode(mode),
+        mode,
         vert_nb: vert_nb,
         vao: vao,
         vbo: None,
         ibo: None,
+        index_type: gl::NONE,
+        _extra_vbos: Vec::new(),
+        instance_buffer: None,
+        shared_vao: None,
+        _v: PhantomData
+      }
+    }
+  }
+
+  /// Create a tessellation from raw vertex data and a runtime vertex layout.
+  ///
+  /// `data` holds `vert_nb` vertices, laid out as described by `layout`. As with `Tess::new`, if
+  /// `indices` is set to `None`, the tessellation will use the vertices as-is.
+  ///
+  /// Because the vertex type is not known statically, the tessellation is typed `Tess<()>`.
+  ///
+  /// # Panic
+  ///
+  /// Panic if `data` is too small to hold `vert_nb` vertices.
+  pub fn from_raw<'a, C, I>(
+    ctx: &mut C,
+    mode: Mode,
+    data: &[u8],
+    layout: &VertexLayout,
+    vert_nb: usize,
+    indices: I
+  ) -> Self
+  where C: GraphicsContext,
+        I: Into<TessIndices<'a>> {
+    let bytes = vert_nb * layout.stride;
+
+    if data.len() < bytes {
+      panic!("cannot create a tessellation of {} vertices ({} bytes) out of {} bytes", vert_nb, bytes, data.len());
+    }
+
+    let mut vao: GLuint = 0;
+
+    unsafe {
+      gl::GenVertexArrays(1, &mut vao);
+
+      ctx.state().borrow_mut().bind_vertex_array(vao);
+
+      // vertex buffer
+      let vertex_buffer = Buffer::new(ctx, data.len());
+      vertex_buffer.fill(data).unwrap();
+
+      let raw_vbo = vertex_buffer.to_raw();
+
+      ctx.state().borrow_mut().bind_array_buffer(raw_vbo.handle());
+
+      for attribute in &layout.attributes {
+        set_component_format(attribute.location, layout.stride as GLsizei, attribute.offset, &attribute.format);
+      }
+
+      // in case of indexed render, create an index buffer
+      let (vert_nb, raw_ibo, index_type) = match create_index_buffer(ctx, indices.into()) {
+        Some((raw_ibo, ind_nb, index_type)) => {
+          ctx.state().borrow_mut().bind_element_array_buffer(raw_ibo.handle());
+          (ind_nb, Some(raw_ibo), index_type)
+        }
+
+        None => (vert_nb, None, gl::NONE)
+      };
+
+      ctx.state().borrow_mut().bind_vertex_array(vao);
+
+      Tess {
+        mode,
+        vert_nb,
+        vao,
+        vbo: Some(raw_vbo),
+        ibo: raw_ibo,
+        index_type,
+        _extra_vbos: Vec::new(),
+        instance_buffer: None,
+        shared_vao: None,
         _v: PhantomData
       }
     }
   }
 }
 
+/// A builder of deinterleaved tessellations.
+///
+/// Each call to `TessBuilder::add_vertices` adds a vertex buffer holding a set of attributes. The
+/// attributes are assigned consecutive locations, in the order the buffers are added: if the first
+/// buffer holds positions and the second one normals and texture coordinates, they are
+/// respectively at locations `0`, `1` and `2`.
+///
+/// ```ignore
+/// let tess = TessBuilder::new(&mut ctx, Mode::Triangle)
+///   .add_vertices(&positions[..]) // [[f32; 3]], location 0
+///   .add_vertices(&normals[..])   // [[f32; 3]], location 1
+///   .add_vertices(&uvs[..])       // [[f32; 2]], location 2
+///   .set_indices(&indices[..])
+///   .build()?;
+/// ```
+///
+/// All the vertex buffers must hold the same number of vertices. A builder with a single vertex
+/// buffer creates the same tessellation as `Tess::new`, but typed `Tess<()>` because the vertex
+/// type is not known statically.
+pub struct TessBuilder<'a, C> where C: 'a {
+  ctx: &'a mut C,
+  mode: Mode,
+  vertex_buffers: Vec<(RawBuffer, usize, Vec<VertexComponentFormat>)>,
+  indices: TessIndices<'a>
+}
+
+impl<'a, C> TessBuilder<'a, C> where C: GraphicsContext {
+  /// Create a new builder of tessellations of the given `Mode`.
+  pub fn new(ctx: &'a mut C, mode: Mode) -> Self {
+    TessBuilder {
+      ctx,
+      mode,
+      vertex_buffers: Vec::new(),
+      indices: TessIndices::None
+    }
+  }
+
+  /// Add a vertex buffer holding a set of attributes.
+  ///
+  /// The buffer is created right away.
+  pub fn add_vertices<V>(mut self, vertices: &[V]) -> Self where V: Vertex {
+    let buffer = Buffer::from_slice(self.ctx, vertices).to_raw();
+    self.vertex_buffers.push((buffer, vertices.len(), V::vertex_format()));
+    self
+  }
+
+  /// Set the indices of the tessellation.
+  ///
+  /// See `Tess::new` for further details about indexed tessellations.
+  pub fn set_indices<I>(mut self, indices: I) -> Self where I: Into<TessIndices<'a>> {
+    self.indices = indices.into();
+    self
+  }
+
+  /// Build the tessellation.
+  ///
+  /// Fail if no vertex buffer was added or if the vertex buffers don’t hold the same number of
+  /// vertices.
+  pub fn build(self) -> Result<Tess<()>, TessError> {
+    let vert_nb = match self.vertex_buffers.first() {
+      Some(&(_, vert_nb, _)) => vert_nb,
+      None => return Err(TessError::NoVertices)
+    };
+
+    if let Some(&(_, nb, _)) = self.vertex_buffers.iter().find(|&&(_, nb, _)| nb != vert_nb) {
+      return Err(TessError::MismatchedVertexCounts(vert_nb, nb));
+    }
+
+    let mut vao: GLuint = 0;
+
+    unsafe {
+      gl::GenVertexArrays(1, &mut vao);
+
+      self.ctx.state().borrow_mut().bind_vertex_array(vao);
+
+      // each buffer sources the attributes following the ones of the previous buffer
+      let mut location = 0;
+
+      for (buffer, _, formats) in &self.vertex_buffers {
+        self.ctx.state().borrow_mut().bind_array_buffer(buffer.handle());
+        set_vertex_pointers_at(location, formats);
+        location += formats.len() as u32;
+      }
+
+      // in case of indexed render, create an index buffer
+      let (vert_nb, ibo, index_type) = match create_index_buffer(self.ctx, self.indices) {
+        Some((raw_ibo, ind_nb, index_type)) => {
+          self.ctx.state().borrow_mut().bind_element_array_buffer(raw_ibo.handle());
+          (ind_nb, Some(raw_ibo), index_type)
+        }
+
+        None => (vert_nb, None, gl::NONE)
+      };
+
+      self.ctx.state().borrow_mut().bind_vertex_array(vao);
+
+      let mut buffers = self.vertex_buffers.into_iter().map(|(buffer, _, _)| buffer);
+      let vbo = buffers.next();
+
+      Ok(Tess {
+        mode: self.mode,
+        vert_nb,
+        vao,
+        vbo,
+        _extra_vbos: buffers.collect(),
+        ibo,
+        index_type,
+        instance_buffer: None,
+        shared_vao: None,
+        _v: PhantomData
+      })
+    }
+  }
+}
+
 impl<V> Drop for Tess<V> {
   fn drop(&mut self) {
+    // shared vertex arrays are deleted with their format
+    match self.shared_vao {
+      // the names of the buffers about to be deleted can be reused by new buffers; forget about them
+      Some(ref shared) => shared.attached.set(DETACHED),
+      None => unsafe { gl::DeleteVertexArrays(1, &self.vao) }
+    }
+  }
+}
+
+/// A vertex array format, shareable between tessellations.
+///
+/// By default, each `Tess` creates its own vertex array, describing how to fetch its vertices.
+/// When many tessellations share the same vertex type, you can instead describe the vertex format
+/// once with a `VertexArrayFormat` and create the tessellations with `Tess::with_format`. Such
+/// tessellations share a single vertex array and only swap their vertex and index buffers when
+/// rendered.
+///
+/// > Note: sharing a vertex array requires OpenGL 4.3 (separate attribute formats). On older
+/// > implementations, `Tess::with_format` falls back to creating a vertex array per tessellation.
+pub struct VertexArrayFormat<V> {
+  shared: Option<Rc<SharedVertexArray>>,
+  _v: PhantomData<V>
+}
+
+// A vertex array which vertex format is specified once.
+struct SharedVertexArray {
+  handle: GLuint,
+  stride: GLsizei,
+  // vertex and index buffers currently attached to the vertex array
+  attached: Cell<(GLuint, GLuint)>
+}
+
+// Attached buffers that never match actual buffers.
+const DETACHED: (GLuint, GLuint) = (GLuint::MAX, GLuint::MAX);
+
+impl Drop for SharedVertexArray {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteVertexArrays(1, &self.handle);
+    }
+  }
+}
+
+impl<V> VertexArrayFormat<V> where V: Vertex {
+  /// Create a new vertex array format for the vertex type `V`.
+  pub fn new<C>(ctx: &mut C) -> Self where C: GraphicsContext {
+    // separate attribute formats require OpenGL 4.3
+    if gl_version() < (4, 3) {
+      return VertexArrayFormat {
+        shared: None,
+        _v: PhantomData
+      };
+    }
+
+    let formats = V::vertex_format();
+    let offsets = aligned_offsets(&formats);
+    let stride = offset_based_vertex_weight(&formats, &offsets) as GLsizei;
+    let mut vao: GLuint = 0;
+
     unsafe {
-      gl::DeleteVertexArrays(1, &self.vao);
+      gl::GenVertexArrays(1, &mut vao);
+
+      ctx.state().borrow_mut().bind_vertex_array(vao);
+
+      for (i, (format, off)) in formats.iter().zip(offsets).enumerate() {
+        set_component_attrib_format(i as u32, off, format);
+      }
+    }
+
+    VertexArrayFormat {
+      shared: Some(Rc::new(SharedVertexArray { handle: vao, stride, attached: Cell::new(DETACHED) })),
+      _v: PhantomData
+    }
+  }
+
+  /// Whether the format is actually shared by the tessellations created with it.
+  ///
+  /// This is `false` if the implementation doesn’t support separate attribute formats.
+  pub fn is_shared(&self) -> bool {
+    self.shared.is_some()
+  }
+}
+
+impl<V> Tess<V> where V: Vertex {
+  /// Create a new tessellation sharing the vertex array of a `VertexArrayFormat`.
+  ///
+  /// The arguments are the same as in `Tess::new`. If the format is not shared, this is the same
+  /// as calling `Tess::new`.
+  pub fn with_format<'a, C, W, I>(
+    ctx: &mut C,
+    format: &VertexArrayFormat<V>,
+    mode: Mode,
+    vertices: W,
+    indices: I
+  ) -> Self
+      where C: GraphicsContext,
+            TessVertices<'a, V>: From<W>,
+            V: 'a + Vertex,
+            I: Into<TessIndices<'a>> {
+    let shared = match format.shared {
+      Some(ref shared) => shared.clone(),
+      None => return Tess::new(ctx, mode, vertices, indices)
+    };
+
+    let vertices = vertices.into();
+    let vert_nb = match vertices {
+      TessVertices::Fill(slice) => slice.len(),
+      TessVertices::Reserve(nb) => nb
+    };
+
+    let vertex_buffer = Buffer::new(ctx, vert_nb);
+
+    if let TessVertices::Fill(verts) = vertices {
+      vertex_buffer.fill(verts).unwrap();
+    }
+
+    let (vert_nb, ibo, index_type) = match create_index_buffer(ctx, indices.into()) {
+      Some((raw_ibo, ind_nb, index_type)) => (ind_nb, Some(raw_ibo), index_type),
+      None => (vert_nb, None, gl::NONE)
+    };
+
+    Tess {
+      mode,
+      vert_nb,
+      vao: shared.handle,
+      vbo: Some(vertex_buffer.to_raw()),
+      ibo,
+      index_type,
+      _extra_vbos: Vec::new(),
+      instance_buffer: None,
+      shared_vao: Some(shared),
+      _v: PhantomData
+    }
+  }
+}
+
+// Set the format of a vertex attribute with a relative offset, sourcing from the binding 0.
+fn set_component_attrib_format(i: u32, off: usize, f: &VertexComponentFormat) {
+  unsafe {
+    match f.comp_type {
+      Type::Floating => {
+        gl::VertexAttribFormat(i as GLuint, dim_as_size(&f.dim), opengl_sized_type(f), gl::FALSE, off as GLuint);
+      },
+      Type::Integral | Type::Unsigned | Type::Boolean => {
+        gl::VertexAttribIFormat(i as GLuint, dim_as_size(&f.dim), opengl_sized_type(f), off as GLuint);
+      }
     }
+
+    gl::VertexAttribBinding(i as GLuint, 0);
+    gl::EnableVertexAttribArray(i as GLuint);
   }
 }
 
 // Give OpenGL types information on the content of the VBO by setting vertex formats and pointers
 // to buffer memory.
 fn set_vertex_pointers(formats: &[VertexComponentFormat]) {
+  set_vertex_pointers_at(0, formats);
+}
+
+// Same as set_vertex_pointers, but with the first component at location `first`.
+fn set_vertex_pointers_at(first: u32, formats: &[VertexComponentFormat]) {
   let offsets = aligned_offsets(formats);
   let vertex_weight = offset_based_vertex_weight(formats, &offsets) as GLsizei;
 
   for (i, (format, off)) in formats.iter().zip(offsets).enumerate() {
-    set_component_format(i as u32, vertex_weight, off, format);
+    set_component_format(first + i as u32, vertex_weight, off, format);
   }
 }
 
@@ -382,6 +996,14 @@ fn opengl_sized_type(f: &VertexComponentFormat) -> GLenum {
   }
 }
 
+// Size in bytes of an index of the given OpenGL type.
+fn index_size(index_type: GLenum) -> usize {
+  match index_type {
+    gl::UNSIGNED_SHORT => size_of::<u16>(),
+    _ => size_of::<u32>()
+  }
+}
+
 fn opengl_mode(mode: Mode) -> GLenum {
   match mode {
     Mode::Point => gl::POINTS,
@@ -389,10 +1011,150 @@ fn opengl_mode(mode: Mode) -> GLenum {
     Mode::LineStrip => gl::LINE_STRIP,
     Mode::Triangle => gl::TRIANGLES,
     Mode::TriangleFan => gl::TRIANGLE_FAN,
-    Mode::TriangleStrip => gl::TRIANGLE_STRIP
+    Mode::TriangleStrip => gl::TRIANGLE_STRIP,
+    Mode::Patch(_) => gl::PATCHES
+  }
+}
+
+// Get the OpenGL primitive of a mode. In the case of patches, check that they can be rendered with
+// the current program and set the number of vertices per patch.
+unsafe fn prepare_mode<C>(ctx: &mut C, mode: Mode) -> GLenum where C: GraphicsContext {
+  if let Mode::Patch(vert_nb) = mode {
+    if vert_nb == 0 {
+      panic!("cannot render patches of 0 vertex");
+    }
+
+    if !ctx.state().borrow().current_program_tessellation() {
+      panic!("cannot render patches with a program that has no tessellation stages");
+    }
+
+    ctx.state().borrow_mut().set_patch_vertices(vert_nb as GLint);
+  }
+
+  opengl_mode(mode)
+}
+
+/// A non-indexed tessellation which vertices can be pushed dynamically.
+///
+/// The vertices are stored in a `GrowableBuffer`, which grows when needed. Use
+/// `GrowableTess::slice` to render the vertices pushed so far.
+pub struct GrowableTess<V> {
+  tess: Tess<V>,
+  buffer: GrowableBuffer<V>,
+  // handle of the buffer the vertex array currently points to
+  bound_handle: GLuint
+}
+
+impl<V> GrowableTess<V> where V: Vertex {
+  /// Create a new, empty tessellation able to hold `capacity` vertices before growing.
+  pub fn new<C>(ctx: &mut C, mode: Mode, capacity: usize) -> Self where C: GraphicsContext {
+    let buffer = GrowableBuffer::new(ctx, capacity);
+    let mut vao: GLuint = 0;
+
+    unsafe {
+      gl::GenVertexArrays(1, &mut vao);
+    }
+
+    let mut tess = GrowableTess {
+      tess: Tess {
+        mode,
+        vert_nb: buffer.capacity(),
+        vao,
+        vbo: None, // the vertices are owned by the growable buffer
+        ibo: None,
+        index_type: gl::NONE,
+        _extra_vbos: Vec::new(),
+        instance_buffer: None,
+        shared_vao: None,
+        _v: PhantomData
+      },
+      buffer,
+      bound_handle: 0
+    };
+
+    tess.bind_buffer();
+    tess
+  }
+
+  /// Number of vertices in the tessellation.
+  pub fn len(&self) -> usize {
+    self.buffer.len()
+  }
+
+  /// Whether the tessellation is empty.
+  pub fn is_empty(&self) -> bool {
+    self.buffer.is_empty()
   }
+
+  /// Number of vertices the tessellation can hold without growing.
+  pub fn capacity(&self) -> usize {
+    self.buffer.capacity()
+  }
+
+  /// Push a vertex at the end of the tessellation.
+  pub fn push(&mut self, vertex: V) where V: Copy {
+    self.extend(&[vertex]);
+  }
+
+  /// Push several vertices at the end of the tessellation.
+  pub fn extend(&mut self, vertices: &[V]) {
+    self.buffer.extend(vertices);
+    self.bind_buffer();
+  }
+
+  /// Remove all the vertices from the tessellation, keeping the GPU memory around.
+  pub fn clear(&mut self) {
+    self.buffer.clear();
+  }
+
+  /// Get a slice over all the vertices pushed so far.
+  pub fn slice<'a>(&'a self) -> TessSlice<'a, V> {
+    TessSlice::one_sub(&self.tess, self.buffer.len())
+  }
+
+  // Make the vertex array point to the current buffer if it has changed since the last call.
+  fn bind_buffer(&mut self) {
+    let raw = self.buffer.raw();
+
+    if raw.handle() != self.bound_handle {
+      unsafe {
+        let mut gfx_state = raw.state().borrow_mut();
+        gfx_state.bind_vertex_array(self.tess.vao);
+        gfx_state.bind_array_buffer(raw.handle());
+        set_vertex_pointers(&V::vertex_format());
+      }
+
+      self.bound_handle = raw.handle();
+      self.tess.vert_nb = self.buffer.capacity();
+    }
+  }
+}
+
+/// Indirect draw command.
+///
+/// This is the layout the GPU expects to find in a buffer when rendering indirectly. Its fields
+/// are the same as the ones of a `TessSlice`, and are typically written by a shader.
+///
+/// > Note: non-indexed tessellations don’t have a base vertex; in that case, the GPU reads
+/// > `base_vertex` as the base instance and ignores `base_instance`. Leave both to `0` unless you
+/// > know what you’re doing.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct IndirectDraw {
+  /// Number of vertices to render.
+  pub vert_nb: u32,
+  /// Number of instances to render.
+  pub inst_nb: u32,
+  /// Start index (vertex) in the tessellation.
+  pub start_index: u32,
+  /// Value added to the indices before fetching vertices.
+  pub base_vertex: i32,
+  /// First instance to render.
+  pub base_instance: u32
 }
 
+unsafe impl Pod for IndirectDraw {}
+
 /// Tessellation slice.
 ///
 /// This type enables slicing a tessellation on the fly so that we can render patches of it.
@@ -447,22 +1209,18 @@ impl<'a, V> TessSlice<'a, V> {
   /// Create a tessellation render for a slice of the tessellation starting anywhere in its buffer
   /// with only one instance.
   ///
-  /// The part is selected by giving the start vertex and the number of vertices to render. This
+  /// The part is selected by giving the start vertex and the number of vertices to render. For an
+  /// indexed tessellation, both are expressed in indices rather than in vertices.
   ///
   /// # Panic
   ///
-  /// Panic if the start vertex is higher to the capacity of the tessellation’s vertex buffer.
-  ///
-  /// Panic if the number of vertices is higher to the capacity of the tessellation’s vertex buffer.
+  /// Panic if the selected part – `[start, start + nb)` – doesn’t fit in the capacity of the
+  /// tessellation’s vertex buffer.
   pub fn one_slice(tess: &'a Tess<V>, start: usize, nb: usize) -> Self {
-    if start > tess.vert_nb {
+    if start > tess.vert_nb || nb > tess.vert_nb - start {
       panic!("cannot render {} vertices starting at vertex {} for a tessellation which vertex capacity is {}", nb, start, tess.vert_nb);
     }
 
-    if nb > tess.vert_nb {
-      panic!("cannot render {} vertices for a tessellation which vertex capacity is {}", nb, tess.vert_nb);
-    }
-
     TessSlice {
       tess: tess,
       start_index: start,
@@ -475,6 +1233,53 @@ impl<'a, V> TessSlice<'a, V> {
   pub fn render<C>(&self, ctx: &mut C) where C: GraphicsContext, V: Vertex {
     self.tess.render(ctx, self.start_index, self.vert_nb, self.inst_nb);
   }
+
+  /// Render `inst_nb` instances of a tessellation.
+  ///
+  /// Instances can be told apart in shaders with `gl_InstanceID` or with per-instance attributes –
+  /// see `Tess::set_instances`.
+  ///
+  /// # Panic
+  ///
+  /// Panic if `inst_nb` is `0`.
+  pub fn render_instanced<C>(&self, ctx: &mut C, inst_nb: usize) where C: GraphicsContext, V: Vertex {
+    self.tess.render(ctx, self.start_index, self.vert_nb, inst_nb);
+  }
+
+  /// Render a tessellation with the indirect draw command at index `i` in `commands`.
+  ///
+  /// The vertices and instances selected by the slice are ignored: they’re read from the command.
+  ///
+  /// # Panic
+  ///
+  /// Panic if `i` is out of the bounds of `commands`.
+  pub fn render_indirect<C>(
+    &self,
+    ctx: &mut C,
+    commands: &Buffer<IndirectDraw>,
+    i: usize
+  ) where C: GraphicsContext, V: Vertex {
+    if i >= commands.len() {
+      panic!("cannot render indirect command {} out of a buffer of {} commands", i, commands.len());
+    }
+
+    self.tess.render_indirect(ctx, commands.handle(), i * size_of::<IndirectDraw>());
+  }
+
+  /// Render a tessellation with as many instances as the value of an atomic counter.
+  ///
+  /// The number of instances selected by the slice is ignored. The counter is never read back on
+  /// the CPU.
+  pub fn render_counted<C>(&self, ctx: &mut C, counter: &AtomicCounter) where C: GraphicsContext, V: Vertex {
+    counter.set_draw_range(self.start_index as u32, self.vert_nb as u32);
+
+    unsafe {
+      // make sure the writes to the counter made by shaders are visible to the draw command
+      gl::MemoryBarrier(gl::COMMAND_BARRIER_BIT);
+    }
+
+    self.tess.render_indirect(ctx, counter.handle(), 0);
+  }
 }
 
 impl<'a, V> From<&'a Tess<V>> for TessSlice<'a, V> {
@@ -483,6 +1288,23 @@ impl<'a, V> From<&'a Tess<V>> for TessSlice<'a, V> {
   }
 }
 
+/// Slice a tessellation with a range of vertices.
+///
+/// `tess.slice(3..6)` renders the vertices `[3, 6)` – or the indices, for an indexed tessellation –
+/// and is the same as `TessSlice::one_slice(&tess, 3, 3)`. All the range types from `std::ops` are
+/// supported:
+///
+/// ```ignore
+/// tess.slice(..);     // all the vertices
+/// tess.slice(..3);    // vertices [0, 3)
+/// tess.slice(3..);    // vertices [3, vertex capacity)
+/// tess.slice(3..6);   // vertices [3, 6)
+/// tess.slice(3..=5);  // vertices [3, 6)
+/// ```
+///
+/// # Panic
+///
+/// Slicing panics if the range is reversed or goes past the capacity of the tessellation.
 pub trait TessSliceIndex<Idx, V> {
   fn slice<'a>(&'a self, idx: Idx) -> TessSlice<'a, V>;
 }
@@ -495,18 +1317,150 @@ impl<V> TessSliceIndex<RangeFull, V> for Tess<V> {
 
 impl<V> TessSliceIndex<RangeTo<usize>, V> for Tess<V> {
   fn slice<'a>(&'a self, to: RangeTo<usize>) -> TessSlice<'a, V> {
-    TessSlice::one_sub(self, to.end)
+    let (start, nb) = vertex_range(to, self.vert_nb);
+    TessSlice::one_slice(self, start, nb)
   }
 }
 
 impl<V> TessSliceIndex<RangeFrom<usize>, V> for Tess<V> {
   fn slice<'a>(&'a self, from: RangeFrom<usize>) -> TessSlice<'a, V> {
-    TessSlice::one_slice(self, from.start, self.vert_nb)
+    let (start, nb) = vertex_range(from, self.vert_nb);
+    TessSlice::one_slice(self, start, nb)
   }
 }
 
 impl<V> TessSliceIndex<Range<usize>, V> for Tess<V> {
   fn slice<'a>(&'a self, range: Range<usize>) -> TessSlice<'a, V> {
-    TessSlice::one_slice(self, range.start, range.end)
+    let (start, nb) = vertex_range(range, self.vert_nb);
+    TessSlice::one_slice(self, start, nb)
+  }
+}
+
+impl<V> TessSliceIndex<RangeInclusive<usize>, V> for Tess<V> {
+  fn slice<'a>(&'a self, range: RangeInclusive<usize>) -> TessSlice<'a, V> {
+    let (start, nb) = vertex_range(range, self.vert_nb);
+    TessSlice::one_slice(self, start, nb)
+  }
+}
+
+impl<V> TessSliceIndex<RangeToInclusive<usize>, V> for Tess<V> {
+  fn slice<'a>(&'a self, to: RangeToInclusive<usize>) -> TessSlice<'a, V> {
+    let (start, nb) = vertex_range(to, self.vert_nb);
+    TessSlice::one_slice(self, start, nb)
+  }
+}
+
+// Start and number of vertices selected by `range` in a tessellation holding `vert_nb` vertices.
+//
+// Panic if the range is reversed or doesn’t fit in the tessellation.
+fn vertex_range<R>(range: R, vert_nb: usize) -> (usize, usize) where R: RangeBounds<usize> {
+  let start = match range.start_bound() {
+    Bound::Included(&start) => start,
+    Bound::Excluded(&start) => start + 1,
+    Bound::Unbounded => 0
+  };
+
+  let end = match range.end_bound() {
+    Bound::Included(&end) => end + 1,
+    Bound::Excluded(&end) => end,
+    Bound::Unbounded => vert_nb
+  };
+
+  if start > vert_nb {
+    panic!("cannot render vertices starting at vertex {} for a tessellation which vertex capacity is {}", start, vert_nb);
+  }
+
+  if start > end {
+    panic!("cannot render the reversed vertex range {}..{}", start, end);
+  }
+
+  if end > vert_nb {
+    panic!("cannot render {} vertices for a tessellation which vertex capacity is {}", end, vert_nb);
+  }
+
+  (start, end - start)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Mode, prepare_mode, vertex_range};
+  use context::GraphicsContext;
+  use gl;
+  use gl_stubs::Context;
+
+  #[test]
+  fn full_range() {
+    assert_eq!(vertex_range(.., 10), (0, 10));
+    assert_eq!(vertex_range(.., 0), (0, 0));
+  }
+
+  #[test]
+  fn range_from() {
+    assert_eq!(vertex_range(3.., 10), (3, 7));
+    assert_eq!(vertex_range(10.., 10), (10, 0));
+  }
+
+  #[test]
+  fn range_to() {
+    assert_eq!(vertex_range(..4, 10), (0, 4));
+    assert_eq!(vertex_range(..10, 10), (0, 10));
+    assert_eq!(vertex_range(..=4, 10), (0, 5));
+  }
+
+  #[test]
+  fn range() {
+    assert_eq!(vertex_range(2..5, 10), (2, 3));
+    assert_eq!(vertex_range(5..5, 10), (5, 0));
+    assert_eq!(vertex_range(2..=5, 10), (2, 4));
+    assert_eq!(vertex_range(0..10, 10), (0, 10));
+  }
+
+  #[test]
+  #[should_panic(expected = "starting at vertex 11")]
+  fn range_from_out_of_bounds() {
+    vertex_range(11.., 10);
+  }
+
+  #[test]
+  #[should_panic(expected = "cannot render 11 vertices")]
+  fn range_to_out_of_bounds() {
+    vertex_range(..11, 10);
+  }
+
+  #[test]
+  #[should_panic(expected = "cannot render 11 vertices")]
+  fn range_to_inclusive_out_of_bounds() {
+    vertex_range(..=10, 10);
+  }
+
+  #[test]
+  #[should_panic(expected = "cannot render 12 vertices")]
+  fn range_out_of_bounds() {
+    vertex_range(8..12, 10);
+  }
+
+  #[test]
+  #[should_panic(expected = "reversed vertex range 5..2")]
+  fn reversed_range() {
+    let (start, end) = (5, 2);
+    vertex_range(start..end, 10);
+  }
+
+  // the stubs don’t provide the program queries, so these tests also check none is issued
+  #[test]
+  fn patches_rendered_with_tessellation_program() {
+    let mut ctx = Context::new();
+    unsafe { ctx.state().borrow_mut().use_program(1, true) };
+
+    assert_eq!(unsafe { prepare_mode(&mut ctx, Mode::Patch(4)) }, gl::PATCHES);
+  }
+
+  #[test]
+  #[should_panic(expected = "no tessellation stages")]
+  fn patches_not_rendered_without_tessellation_program() {
+    let mut ctx = Context::new();
+    unsafe { ctx.state().borrow_mut().use_program(1, false) };
+
+    unsafe { prepare_mode(&mut ctx, Mode::Patch(4)) };
   }
 }
@@ -41,8 +41,8 @@
 //! ## Samplers
 //!
 //! Samplers gather filters – i.e. how a shader should interpolate texels while fetching them,
-//! wrap rules – i.e. how a shader should behave when leaving the normalized UV coordinates? and
-//! a depth comparison, for depth textures only. See the documentation of `Sampler` for further
+//! wrap rules – i.e. how a shader should behave when leaving the normalized UV coordinates?,
+//! channel swizzling and a depth comparison, for depth textures only. See the documentation of `Sampler` for further
 //! explanations.
 //!
 //! Samplers must be declared in the shader code according to the type of the texture used in the
@@ -75,6 +75,7 @@
 //! | `RGB32I`      | `isampler*D`      |
 //! | `RGB32UI`     | `usampler*D`      |
 //! | `RGB32F`      | `sampler*D`       |
+//! | `R11FG11FB10F`| `sampler*D`       |
 //! | `RGBA8I`      | `isampler*D`      |
 //! | `RGBA8UI`     | `usampler*D`      |
 //! | `RGBA16I`     | `isampler*D`      |
@@ -82,8 +83,14 @@
 //! | `RGBA32I`     | `isampler*D`      |
 //! | `RGBA32UI`    | `usampler*D`      |
 //! | `RGBA32F`     | `sampler*D`       |
+//! | `RGB10A2`     | `sampler*D`       |
+//! | `SRGB8`       | `sampler*D`       |
+//! | `SRGBA8`      | `sampler*D`       |
 //! | `Depth32F`    | `sampler1D`       |
 //!
+//! Cubemaps use the `Cube` suffix instead of the dimension: `samplerCube`, `isamplerCube` and
+//! `usamplerCube`.
+//!
 //! # Uploading data to textures
 //!
 //! One of the primary use of textures is to store images so that they can be used in your
@@ -100,6 +107,15 @@
 //!   data you have don’t take channels into account and are just *raw* data.
 //! - `Texture::upload_part_raw`: same thing as above, but with offset and size control.
 //!
+//! The texels must cover the uploaded region exactly – otherwise, those functions panic. They’re
+//! laid out row after row and, for 3D textures, slice after slice: a 3D texture of size
+//! `[w, h, d]` takes `w * h * d` texels, the texel at `(x, y, z)` being at index
+//! `x + w * (y + h * z)`.
+//!
+//! Cubemaps have six faces, which you can upload all at once with `Texture::upload_faces` and
+//! `Texture::upload_faces_raw`, or one by one with the functions above – the offset selecting the
+//! face.
+//!
 //! Alternatively, you can clear the texture with `Texture::clear` and `Texture::clear_part`.
 //!
 //! # Retrieving texels
@@ -119,9 +135,14 @@ use std::ops::{Deref, DerefMut};
 use std::ptr;
 use std::rc::Rc;
 
-use context::GraphicsContext;
-use pixel::{Pixel, PixelFormat, opengl_pixel_format, pixel_components};
-use state::GraphicsState;
+use context::{Capabilities, GraphicsContext};
+use pixel::{DepthPixel, Pixel, PixelFormat, SRGBPixel, is_depth_pixel, opengl_pixel_format, pixel_components,
+            srgb_to_linear};
+use state::{GraphicsState, gl_version, has_extension};
+
+// anisotropic filtering is core since OpenGL 4.6 only; the gl crate doesn’t expose its enums
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
 
 /// How to wrap texture coordinates while sampling textures?
 #[derive(Clone, Copy, Debug)]
@@ -170,6 +191,23 @@ pub enum MagFilter {
   Linear
 }
 
+/// Source of a channel when sampling a texture.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Swizzle {
+  /// The red channel of the texture.
+  Red,
+  /// The green channel of the texture.
+  Green,
+  /// The blue channel of the texture.
+  Blue,
+  /// The alpha channel of the texture.
+  Alpha,
+  /// Always `0`.
+  Zero,
+  /// Always `1`.
+  One
+}
+
 /// Depth comparison to perform while depth test. `a` is the incoming fragment’s depth and b is the
 /// fragment’s depth that is already stored.
 #[derive(Clone, Copy, Debug)]
@@ -208,9 +246,9 @@ pub trait Dimensionable {
   /// X offset.
   fn x_offset(offset: Self::Offset) -> u32;
   /// Y offset. If it doesn’t have one, set it to 0.
-  fn y_offset(_: Self::Offset) -> u32 { 1 }
+  fn y_offset(_: Self::Offset) -> u32 { 0 }
   /// Z offset. If it doesn’t have one, set it to 0.
-  fn z_offset(_: Self::Offset) -> u32 { 1 }
+  fn z_offset(_: Self::Offset) -> u32 { 0 }
   /// Zero offset.
   fn zero_offset() -> Self::Offset;
 }
@@ -220,6 +258,14 @@ fn dim_capacity<D>(size: D::Size) -> u32 where D: Dimensionable {
   D::width(size) * D::height(size) * D::depth(size)
 }
 
+// Number of texels covered by an upload of a given size; cubemaps are uploaded one face at a time.
+fn upload_capacity<D>(size: D::Size) -> usize where D: Dimensionable {
+  match D::dim() {
+    Dim::Cubemap => (D::width(size) * D::height(size)) as usize,
+    _ => dim_capacity::<D>(size) as usize
+  }
+}
+
 /// Dimension of a texture.
 #[derive(Clone, Copy, Debug)]
 pub enum Dim {
@@ -327,7 +373,7 @@ impl Dimensionable for Cubemap {
 }
 
 /// Faces of a cubemap.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum CubeFace {
   PositiveX,
   NegativeX,
@@ -337,6 +383,18 @@ pub enum CubeFace {
   NegativeZ
 }
 
+impl CubeFace {
+  /// All the faces, in the order OpenGL numbers them.
+  pub const ALL: [CubeFace; 6] = [
+    CubeFace::PositiveX,
+    CubeFace::NegativeX,
+    CubeFace::PositiveY,
+    CubeFace::NegativeY,
+    CubeFace::PositiveZ,
+    CubeFace::NegativeZ
+  ];
+}
+
 /// Trait used to reify a type into a `Layering`.
 pub trait Layerable {
   /// Reify to `Layering`.
@@ -433,6 +491,10 @@ impl<L, D, P> Texture<L, D, P>
     where L: Layerable,
           D: Dimensionable,
           P: Pixel {
+  /// Create a new texture.
+  ///
+  /// `TextureError::SizeTooLarge` is returned if the implementation doesn’t support textures that
+  /// large – see `GraphicsContext::capabilities`.
   pub fn new<C>(
     ctx: &mut C,
     size: D::Size,
@@ -441,6 +503,12 @@ impl<L, D, P> Texture<L, D, P>
   ) -> Result<Self, TextureError>
 
   where C: GraphicsContext {
+    if sampler.depth_comparison.is_some() && !is_depth_pixel(P::pixel_format()) {
+      return Err(TextureError::DepthComparisonOnColorTexture);
+    }
+
+    check_texture_size::<D>(ctx.state().borrow_mut().capabilities(), size)?;
+
     let mipmaps = mipmaps + 1; // + 1 prevent having 0 mipmaps
     let mut texture = 0;
     let target = opengl_target(L::layering(), D::dim());
@@ -489,6 +557,38 @@ impl<L, D, P> Texture<L, D, P>
     self.mipmaps
   }
 
+  /// Restrict the mipmap levels that can be sampled.
+  ///
+  /// Only the levels from `base_level` to `max_level` – both included – are used when sampling the
+  /// texture. This is useful when streaming textures: upload the coarse mipmaps first and lower
+  /// `base_level` as the detailed ones get uploaded, so that undefined mipmaps are never sampled.
+  ///
+  /// Both levels are clamped to the mipmaps of the texture. See `Sampler::base_level` and
+  /// `Sampler::max_level` to set the levels at creation.
+  pub fn set_mip_levels(&self, base_level: usize, max_level: usize) {
+    unsafe {
+      let mut gfx_state = self.state.borrow_mut();
+
+      gfx_state.bind_texture(self.target, self.handle);
+      set_texture_levels(self.target, base_level, Some(max_level), self.mipmaps);
+      gfx_state.bind_texture(self.target, 0);
+    }
+  }
+
+  /// Set the depth comparison performed when sampling the texture, or disable it with `None`.
+  ///
+  /// This is the way to enable the comparison on the depth slot of a framebuffer – a shadow map,
+  /// for instance – which is created with the default sampler. See `Sampler::depth_comparison`.
+  pub fn set_depth_comparison(&self, depth_comparison: Option<DepthComparison>) where P: DepthPixel {
+    unsafe {
+      let mut gfx_state = self.state.borrow_mut();
+
+      gfx_state.bind_texture(self.target, self.handle);
+      set_texture_depth_comparison(self.target, depth_comparison);
+      gfx_state.bind_texture(self.target, 0);
+    }
+  }
+
   /// Clear a part of a texture.
   ///
   /// The part being cleared is defined by a rectangle in which the `offset` represents the
@@ -496,7 +596,7 @@ impl<L, D, P> Texture<L, D, P>
   /// by this rectangle will be cleared to the `pixel` value.
   pub fn clear_part(&self, gen_mipmaps: bool, offset: D::Offset, size: D::Size, pixel: P::Encoding)
       where P::Encoding: Copy {
-    self.upload_part(gen_mipmaps, offset, size, &vec![pixel; dim_capacity::<D>(size) as usize])
+    self.upload_part(gen_mipmaps, offset, size, &vec![pixel; upload_capacity::<D>(size)])
   }
 
   /// Clear a whole texture with a `pixel` value.
@@ -510,6 +610,14 @@ impl<L, D, P> Texture<L, D, P>
   /// The part being updated is defined by a rectangle in which the `offset` represents the
   /// left-upper corner and the `size` gives the dimension of the rectangle. All the covered texels
   /// by this rectangle will be updated by the `texels` slice.
+  ///
+  /// Only the base level is updated. If `gen_mipmaps` is `true`, the other mipmaps are then
+  /// regenerated from it; otherwise, they’re left untouched.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if the rectangle doesn’t fit in the texture or if `texels` doesn’t hold
+  /// exactly as many texels as the rectangle – i.e. `w * h * d` for a 3D texture.
   pub fn upload_part(
     &self,
     gen_mipmaps: bool, // TODO: proper typing instead of bool
@@ -517,6 +625,9 @@ impl<L, D, P> Texture<L, D, P>
     size: D::Size,
     texels: &[P::Encoding]
   ) {
+    check_upload_region::<D>(self.size, offset, size);
+    check_upload_len(texels.len(), upload_capacity::<D>(size));
+
     unsafe {
       let mut gfx_state = self.state.borrow_mut();
 
@@ -533,6 +644,10 @@ impl<L, D, P> Texture<L, D, P>
   }
 
   /// Upload `texels` to the whole texture.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if `texels` doesn’t hold exactly as many texels as the texture.
   pub fn upload(
     &self,
     gen_mipmaps: bool, // FIXME: bool typing
@@ -546,6 +661,12 @@ impl<L, D, P> Texture<L, D, P>
   /// This function is similar to `upload_part` but it works on `P::RawEncoding` instead of
   /// `P::Encoding`. This useful when the texels are represented as a contiguous array of raw
   /// components of the texels.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if the rectangle doesn’t fit in the texture or if `texels` doesn’t hold
+  /// exactly as many raw components as the rectangle – i.e. `w * h * d` times the number of
+  /// components of `P` for a 3D texture.
   pub fn upload_part_raw(
     &self,
     gen_mipmaps: bool,
@@ -553,6 +674,9 @@ impl<L, D, P> Texture<L, D, P>
     size: D::Size,
     texels: &[P::RawEncoding]
   ) {
+    check_upload_region::<D>(self.size, offset, size);
+    check_upload_len(texels.len(), upload_capacity::<D>(size) * pixel_components(P::pixel_format()));
+
     unsafe {
       let mut gfx_state = self.state.borrow_mut();
 
@@ -569,12 +693,19 @@ impl<L, D, P> Texture<L, D, P>
   }
 
   /// Upload raw `texels` to the whole texture.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if `texels` doesn’t hold exactly as many raw components as the texture.
   pub fn upload_raw(&self, gen_mipmaps: bool, texels: &[P::RawEncoding]) {
     self.upload_part_raw(gen_mipmaps, D::zero_offset(), self.size, texels)
   }
 
   // FIXME: cubemaps?
   /// Get the raw texels associated with this texture.
+  ///
+  /// The texels are returned as stored: texels of sRGB formats are sRGB-encoded. See
+  /// `Texture::get_linear_texels` to get them linear.
   pub fn get_raw_texels(&self) -> Vec<P::RawEncoding> where P: Pixel, P::RawEncoding: Copy {
     let mut texels = Vec::new();
     let pf = P::pixel_format();
@@ -602,11 +733,72 @@ impl<L, D, P> Texture<L, D, P>
     texels
   }
 
+  /// Get the texels associated with this texture, decoded from sRGB to linear.
+  ///
+  /// Every channel is normalized to *[0;1]*. The alpha channel, if any, is not sRGB-encoded and is
+  /// only normalized.
+  pub fn get_linear_texels(&self) -> Vec<f32> where P: SRGBPixel<RawEncoding = u8> {
+    let components = pixel_components(P::pixel_format());
+
+    self.get_raw_texels().into_iter().enumerate().map(|(i, x)| {
+      let x = x as f32 / 255.;
+
+      if i % components == 3 { x } else { srgb_to_linear(x) }
+    }).collect()
+  }
+
   pub fn size(&self) -> D::Size {
     self.size
   }
 }
 
+impl<P> Texture<Flat, Cubemap, P> where P: Pixel {
+  /// Upload `faces` to the six faces of the cubemap.
+  ///
+  /// Faces are given in the order of `CubeFace::ALL`. Each face must hold as many texels as a face
+  /// of the cubemap – `size * size`; otherwise, `TextureError::FaceSizeMismatch` is returned and
+  /// nothing is uploaded.
+  pub fn upload_faces(&self, gen_mipmaps: bool, faces: [&[P::Encoding]; 6]) -> Result<(), TextureError> {
+    let texel_nb = (self.size * self.size) as usize;
+    self.upload_face_texels(gen_mipmaps, faces, texel_nb)
+  }
+
+  /// Upload raw `faces` to the six faces of the cubemap.
+  ///
+  /// This function is similar to `upload_faces` but it works on `P::RawEncoding` instead of
+  /// `P::Encoding`: each face must hold `size * size` times the number of components of `P`.
+  pub fn upload_faces_raw(&self, gen_mipmaps: bool, faces: [&[P::RawEncoding]; 6]) -> Result<(), TextureError> {
+    let texel_nb = (self.size * self.size) as usize * pixel_components(P::pixel_format());
+    self.upload_face_texels(gen_mipmaps, faces, texel_nb)
+  }
+
+  fn upload_face_texels<T>(&self, gen_mipmaps: bool, faces: [&[T]; 6], face_len: usize) -> Result<(), TextureError> {
+    for (face, texels) in CubeFace::ALL.iter().zip(&faces) {
+      if texels.len() != face_len {
+        return Err(TextureError::FaceSizeMismatch(*face, face_len, texels.len()));
+      }
+    }
+
+    unsafe {
+      let mut gfx_state = self.state.borrow_mut();
+
+      gfx_state.bind_texture(self.target, self.handle);
+
+      for (face, texels) in CubeFace::ALL.iter().zip(&faces) {
+        upload_texels::<Flat, Cubemap, P, T>(self.target, ([0, 0], *face), self.size, texels);
+      }
+
+      if gen_mipmaps {
+        gl::GenerateMipmap(self.target);
+      }
+
+      gfx_state.bind_texture(self.target, 0);
+    }
+
+    Ok(())
+  }
+}
+
 pub(crate) fn opengl_target(l: Layering, d: Dim) -> GLenum {
   match l {
     Layering::Flat => match d {
@@ -633,7 +825,7 @@ pub(crate) unsafe fn create_texture<L, D>(
 ) -> Result<(), TextureError>
 where L: Layerable,
       D: Dimensionable {
-  set_texture_levels(target, mipmaps);
+  set_texture_levels(target, sampler.base_level, sampler.max_level, mipmaps);
   apply_sampler_to_texture(target, sampler);
   create_texture_storage::<L, D>(size, mipmaps, pf)
 }
@@ -710,14 +902,34 @@ fn create_cubemap_storage(format: GLenum, iformat: GLenum, encoding: GLenum, s:
   for level in 0..mipmaps {
     let s = s / 2u32.pow(level as u32);
 
-    unsafe { gl::TexImage2D(gl::TEXTURE_CUBE_MAP, level as GLint, iformat as GLint, s as GLsizei, s as GLsizei, 0, format, encoding, ptr::null()) };
+    // each face has its own storage
+    for face in 0..6 {
+      unsafe { gl::TexImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + face, level as GLint, iformat as GLint, s as GLsizei, s as GLsizei, 0, format, encoding, ptr::null()) };
+    }
+  }
+}
+
+pub(crate) fn opengl_cube_face(face: CubeFace) -> GLenum {
+  match face {
+    CubeFace::PositiveX => gl::TEXTURE_CUBE_MAP_POSITIVE_X,
+    CubeFace::NegativeX => gl::TEXTURE_CUBE_MAP_NEGATIVE_X,
+    CubeFace::PositiveY => gl::TEXTURE_CUBE_MAP_POSITIVE_Y,
+    CubeFace::NegativeY => gl::TEXTURE_CUBE_MAP_NEGATIVE_Y,
+    CubeFace::PositiveZ => gl::TEXTURE_CUBE_MAP_POSITIVE_Z,
+    CubeFace::NegativeZ => gl::TEXTURE_CUBE_MAP_NEGATIVE_Z
   }
 }
 
-fn set_texture_levels(target: GLenum, mipmaps: usize) {
+// Set the base and max levels of the currently bound texture. Both levels are clamped to the
+// available mipmaps; no max level means the last mipmap.
+fn set_texture_levels(target: GLenum, base_level: usize, max_level: Option<usize>, mipmaps: usize) {
+  let last_level = mipmaps - 1;
+  let max_level = max_level.map_or(last_level, |level| level.min(last_level));
+  let base_level = base_level.min(max_level);
+
   unsafe {
-    gl::TexParameteri(target, gl::TEXTURE_BASE_LEVEL, 0);
-    gl::TexParameteri(target, gl::TEXTURE_MAX_LEVEL, mipmaps as GLint - 1);
+    gl::TexParameteri(target, gl::TEXTURE_BASE_LEVEL, base_level as GLint);
+    gl::TexParameteri(target, gl::TEXTURE_MAX_LEVEL, max_level as GLint);
   }
 }
 
@@ -728,7 +940,25 @@ fn apply_sampler_to_texture(target: GLenum, sampler: &Sampler) {
     gl::TexParameteri(target, gl::TEXTURE_WRAP_T, opengl_wrap(sampler.wrap_t) as GLint);
     gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, opengl_min_filter(sampler.min_filter) as GLint);
     gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, opengl_mag_filter(sampler.mag_filter) as GLint);
-    match sampler.depth_comparison {
+    set_texture_depth_comparison(target, sampler.depth_comparison);
+
+    let swizzle = [gl::TEXTURE_SWIZZLE_R, gl::TEXTURE_SWIZZLE_G, gl::TEXTURE_SWIZZLE_B, gl::TEXTURE_SWIZZLE_A];
+    for (&param, &channel) in swizzle.iter().zip(&sampler.swizzle) {
+      gl::TexParameteri(target, param, opengl_swizzle(channel) as GLint);
+    }
+
+    // querying the support is not free; skip it for the default level
+    if sampler.anisotropy > 1. {
+      if let Some(max_anisotropy) = max_anisotropy() {
+        gl::TexParameterf(target, TEXTURE_MAX_ANISOTROPY, sampler.anisotropy.min(max_anisotropy));
+      }
+    }
+  }
+}
+
+fn set_texture_depth_comparison(target: GLenum, depth_comparison: Option<DepthComparison>) {
+  unsafe {
+    match depth_comparison {
       Some(fun) => {
         gl::TexParameteri(target, gl::TEXTURE_COMPARE_FUNC, opengl_depth_comparison(fun) as GLint);
         gl::TexParameteri(target, gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE as GLint);
@@ -740,6 +970,51 @@ fn apply_sampler_to_texture(target: GLenum, sampler: &Sampler) {
   }
 }
 
+// Check that the implementation supports textures of the given size.
+pub(crate) fn check_texture_size<D>(
+  capabilities: &Capabilities,
+  size: D::Size
+) -> Result<(), TextureError>
+where D: Dimensionable {
+  let max_size = match D::dim() {
+    Dim::Dim1 | Dim::Dim2 => capabilities.max_texture_size,
+    Dim::Dim3 => capabilities.max_3d_texture_size,
+    Dim::Cubemap => capabilities.max_cube_map_texture_size
+  };
+  let largest = D::width(size).max(D::height(size)).max(D::depth(size));
+
+  if largest > max_size {
+    Err(TextureError::SizeTooLarge(largest, max_size))
+  } else {
+    Ok(())
+  }
+}
+
+// Maximum supported degree of anisotropy, if anisotropic filtering is available.
+pub(crate) fn max_anisotropy() -> Option<f32> {
+  let available = gl_version() >= (4, 6) ||
+    has_extension(&[b"GL_ARB_texture_filter_anisotropic", b"GL_EXT_texture_filter_anisotropic"]);
+
+  if available {
+    let mut max = 1.;
+    unsafe { gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max) };
+    Some(max)
+  } else {
+    None
+  }
+}
+
+fn opengl_swizzle(swizzle: Swizzle) -> GLenum {
+  match swizzle {
+    Swizzle::Red => gl::RED,
+    Swizzle::Green => gl::GREEN,
+    Swizzle::Blue => gl::BLUE,
+    Swizzle::Alpha => gl::ALPHA,
+    Swizzle::Zero => gl::ZERO,
+    Swizzle::One => gl::ONE
+  }
+}
+
 fn opengl_wrap(wrap: Wrap) -> GLenum {
   match wrap {
     Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
@@ -779,6 +1054,34 @@ fn opengl_depth_comparison(fun: DepthComparison) -> GLenum {
   }
 }
 
+// Make sure an upload doesn’t write outside of the texture. The face of a cubemap is not part of
+// the region.
+fn check_upload_region<D>(tex_size: D::Size, offset: D::Offset, size: D::Size) where D: Dimensionable {
+  let fits = |off: u32, len: u32, tex_len: u32| off as u64 + len as u64 <= tex_len as u64;
+  let fits_depth = match D::dim() {
+    Dim::Cubemap => true,
+    _ => fits(D::z_offset(offset), D::depth(size), D::depth(tex_size))
+  };
+
+  if !fits(D::x_offset(offset), D::width(size), D::width(tex_size)) ||
+     !fits(D::y_offset(offset), D::height(size), D::height(tex_size)) ||
+     !fits_depth {
+    panic!(
+      "cannot upload a {}×{}×{} region at ({}, {}, {}) to a {}×{}×{} texture",
+      D::width(size), D::height(size), D::depth(size),
+      D::x_offset(offset), D::y_offset(offset), D::z_offset(offset),
+      D::width(tex_size), D::height(tex_size), D::depth(tex_size)
+    );
+  }
+}
+
+// Make sure an upload doesn’t read past the end of the texels.
+fn check_upload_len(len: usize, expected: usize) {
+  if len != expected {
+    panic!("cannot upload {} values to a texture region expecting {}", len, expected);
+  }
+}
+
 // Upload texels into the texture’s memory. Becareful of the type of texels you send down.
 fn upload_texels<L, D, P, T>(target: GLenum, off: D::Offset, size: D::Size, texels: &[T])
     where L: Layerable,
@@ -794,7 +1097,8 @@ fn upload_texels<L, D, P, T>(target: GLenum, off: D::Offset, size: D::Size, texe
             Dim::Dim1 => unsafe { gl::TexSubImage1D(target, 0, D::x_offset(off) as GLint, D::width(size) as GLsizei, format, encoding, texels.as_ptr() as *const c_void) },
             Dim::Dim2 => unsafe { gl::TexSubImage2D(target, 0, D::x_offset(off) as GLint, D::y_offset(off) as GLint, D::width(size) as GLsizei, D::height(size) as GLsizei, format, encoding, texels.as_ptr() as *const c_void) },
             Dim::Dim3 => unsafe { gl::TexSubImage3D(target, 0, D::x_offset(off) as GLint, D::y_offset(off) as GLint, D::z_offset(off) as GLint, D::width(size) as GLsizei, D::height(size) as GLsizei, D::depth(size) as GLsizei, format, encoding, texels.as_ptr() as *const c_void) },
-            Dim::Cubemap => unsafe { gl::TexSubImage3D(target, 0, D::x_offset(off) as GLint, D::y_offset(off) as GLint, (gl::TEXTURE_CUBE_MAP_POSITIVE_X + D::z_offset(off)) as GLint, D::width(size) as GLsizei, D::width(size) as GLsizei, 1, format, encoding, texels.as_ptr() as *const c_void) }
+            // faces are uploaded through their own target rather than the cubemap one
+            Dim::Cubemap => unsafe { gl::TexSubImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + D::z_offset(off), 0, D::x_offset(off) as GLint, D::y_offset(off) as GLint, D::width(size) as GLsizei, D::width(size) as GLsizei, format, encoding, texels.as_ptr() as *const c_void) }
           }
         },
         Layering::Layered => panic!("Layering::Layered not implemented yet")
@@ -818,7 +1122,33 @@ pub struct Sampler {
   /// Magnification filter.
   pub mag_filter: MagFilter,
   /// For depth textures, should we perform depth comparison and if so, how?
-  pub depth_comparison: Option<DepthComparison>
+  ///
+  /// With a depth comparison, the texture must be declared as a shadow sampler in GLSL –
+  /// `sampler2DShadow`, for instance. `texture` then takes the reference depth as the last
+  /// coordinate and returns the result of the comparison between it and the stored depth: `1` if
+  /// it passes, `0` otherwise. With a `Linear` filter, the results of the neighboring texels are
+  /// averaged, giving *percentage-closer filtering* for free.
+  ///
+  /// Only depth textures can be compared; `Texture::new` fails with
+  /// `TextureError::DepthComparisonOnColorTexture` otherwise.
+  pub depth_comparison: Option<DepthComparison>,
+  /// Lowest mipmap level – i.e. the most detailed – that can be sampled.
+  pub base_level: usize,
+  /// Highest mipmap level – i.e. the least detailed – that can be sampled. `None` means the last
+  /// mipmap of the texture.
+  pub max_level: Option<usize>,
+  /// Sources of the red, green, blue and alpha channels, in that order, when sampling.
+  ///
+  /// Use this to remap the channels of a texture without changing the shader – broadcasting the
+  /// red channel of a font atlas to all the channels with `[Red, Red, Red, Red]`, for instance.
+  pub swizzle: [Swizzle; 4],
+  /// Maximum degree of anisotropy used when sampling.
+  ///
+  /// Anisotropic filtering sharpens textures viewed at grazing angles – a ground seen from a
+  /// walking character, for instance – by taking up to that many samples along the direction of
+  /// the slope. `1` disables it. The level is clamped to the maximum supported one – see
+  /// `GraphicsContext::max_anisotropy` – and ignored if anisotropic filtering is not available.
+  pub anisotropy: f32
 }
 
 /// Default value is as following:
@@ -830,7 +1160,11 @@ impl Default for Sampler {
       wrap_t: Wrap::ClampToEdge,
       min_filter: MinFilter::NearestMipmapLinear,
       mag_filter: MagFilter::Linear,
-      depth_comparison: None
+      depth_comparison: None,
+      base_level: 0,
+      max_level: None,
+      swizzle: [Swizzle::Red, Swizzle::Green, Swizzle::Blue, Swizzle::Alpha],
+      anisotropy: 1.
     }
   }
 }
@@ -838,6 +1172,14 @@ impl Default for Sampler {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum TextureError {
   TextureStorageCreationFailed(String),
+  /// A cubemap face doesn’t have the expected number of texels: the face, the expected number and
+  /// the provided one.
+  FaceSizeMismatch(CubeFace, usize, usize),
+  /// A depth comparison was requested for a texture which pixel format is not a depth one.
+  DepthComparisonOnColorTexture,
+  /// The largest side of the texture, given first, exceeds the maximum size supported by the
+  /// implementation for its dimension, given second. See `Capabilities`.
+  SizeTooLarge(u32, u32),
 }
 
 impl fmt::Display for TextureError {
@@ -846,6 +1188,18 @@ impl fmt::Display for TextureError {
       TextureError::TextureStorageCreationFailed(ref e) => {
         write!(f, "texture storage creation failed: {}", e)
       }
+
+      TextureError::FaceSizeMismatch(face, expected, provided) => {
+        write!(f, "cubemap face {:?} has {} texels, expected {}", face, provided, expected)
+      }
+
+      TextureError::DepthComparisonOnColorTexture => {
+        write!(f, "depth comparison requested for a non-depth texture")
+      }
+
+      TextureError::SizeTooLarge(size, max_size) => {
+        write!(f, "texture size too large: {} (maximum is {})", size, max_size)
+      }
     }
   }
 }
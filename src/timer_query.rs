@@ -0,0 +1,104 @@
+//! GPU timer queries.
+//!
+//! Commands are executed by the GPU long after the CPU issued them, so measuring the time spent in
+//! a pipeline on the CPU tells you nothing about its GPU cost. A `TimerQuery` measures the time the
+//! GPU spends executing the commands issued in a closure:
+//!
+//! ```ignore
+//! let timer = TimerQuery::new();
+//!
+//! timer.measure(|| {
+//!   builder.pipeline(&back_buffer, [0., 0., 0., 1.], |pipeline, shd_gate| {
+//!     // …
+//!   });
+//! });
+//!
+//! // a few frames later
+//! if let Some(elapsed) = timer.elapsed_ns() {
+//!   println!("the pipeline took {}ns", elapsed);
+//! }
+//! ```
+//!
+//! The result is computed asynchronously: it’s usually available a frame or two after the measure.
+//! `TimerQuery::elapsed_ns` never blocks and returns `None` until then. A typical GPU profiler
+//! keeps a few queries per pass and cycles through them, reading the oldest one each frame.
+//!
+//! > Note: measures cannot be nested – only one timer query can be active at a time.
+
+use gl;
+use gl::types::*;
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+/// A query measuring the time spent by the GPU executing commands.
+pub struct TimerQuery {
+  handle: GLuint,
+  // a query object doesn’t hold any result until it’s used for the first time
+  measured: Cell<bool>,
+  _p: PhantomData<*const ()>
+}
+
+impl TimerQuery {
+  /// Create a new query.
+  pub fn new() -> Self {
+    let mut handle: GLuint = 0;
+
+    unsafe { gl::GenQueries(1, &mut handle) };
+
+    TimerQuery {
+      handle,
+      measured: Cell::new(false),
+      _p: PhantomData
+    }
+  }
+
+  /// Measure the time spent by the GPU executing the commands issued during `f`.
+  ///
+  /// The result of the previous measure, if not read yet, is lost.
+  pub fn measure<F, R>(&self, f: F) -> R where F: FnOnce() -> R {
+    unsafe { gl::BeginQuery(gl::TIME_ELAPSED, self.handle) };
+    let r = f();
+    unsafe { gl::EndQuery(gl::TIME_ELAPSED) };
+
+    self.measured.set(true);
+    r
+  }
+
+  /// Whether the result of the last measure is available.
+  ///
+  /// This is always `false` if nothing was measured yet.
+  pub fn is_ready(&self) -> bool {
+    if !self.measured.get() {
+      return false;
+    }
+
+    let mut ready: GLuint = gl::FALSE as GLuint;
+    unsafe { gl::GetQueryObjectuiv(self.handle, gl::QUERY_RESULT_AVAILABLE, &mut ready) };
+    ready == gl::TRUE as GLuint
+  }
+
+  /// Time spent by the GPU during the last measure, in nanoseconds.
+  ///
+  /// This function doesn’t block: it returns `None` if the result is not available yet.
+  pub fn elapsed_ns(&self) -> Option<u64> {
+    if !self.is_ready() {
+      return None;
+    }
+
+    let mut elapsed: GLuint64 = 0;
+    unsafe { gl::GetQueryObjectui64v(self.handle, gl::QUERY_RESULT, &mut elapsed) };
+    Some(elapsed)
+  }
+}
+
+impl Default for TimerQuery {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Drop for TimerQuery {
+  fn drop(&mut self) {
+    unsafe { gl::DeleteQueries(1, &self.handle) }
+  }
+}
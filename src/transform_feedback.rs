@@ -0,0 +1,226 @@
+//! Transform feedback.
+//!
+//! Transform feedback captures the vertices output by the vertex processing stages – vertex,
+//! tessellation and geometry shaders – into a buffer. You can then render the captured vertices
+//! back without any CPU round-trip, which is the basis of GPU-generated geometry.
+//!
+//! A `TransformFeedback<V>` owns a buffer able to hold a given number of vertices of type `V`. The
+//! outputs to capture are declared in the shader with the `xfb_buffer` and `xfb_offset` layout
+//! qualifiers (GLSL 4.40) and must match the layout of `V`:
+//!
+//! ```glsl
+//! layout (xfb_buffer = 0, xfb_offset = 0) out vec3 position;
+//! layout (xfb_buffer = 0, xfb_offset = 12) out vec3 color;
+//! ```
+//!
+//! With older GLSL versions, name the outputs when creating the program instead, with
+//! `Program::from_strings_with_feedback`:
+//!
+//! ```ignore
+//! let (program, _) = Program::<Vertex, (), ()>::from_strings_with_feedback(
+//!   None,
+//!   VS,
+//!   None,
+//!   FS,
+//!   &["position", "color"]
+//! )?;
+//! ```
+//!
+//! Capture vertices by issuing renders in `TransformFeedback::capture`, then draw them with
+//! `TessGate::render_transform_feedback`:
+//!
+//! ```ignore
+//! let feedback = TransformFeedback::<Vertex>::new(&mut ctx, 1024);
+//! let written = PrimitivesWritten::new();
+//!
+//! // generate the geometry
+//! written.measure(|| {
+//!   feedback.capture(Mode::Triangle, || tess_gate.render(&mut ctx, (&source).into()))
+//! });
+//!
+//! // draw exactly what was generated
+//! tess_gate.render_transform_feedback(&mut ctx, &feedback, Mode::Triangle);
+//!
+//! // if you need the number of primitives on the CPU
+//! let primitive_nb = written.result();
+//! ```
+//!
+//! Use `TransformFeedback::capture_only` if the captured vertices must not be rasterized. Because
+//! a transform feedback object can be rendered while capturing into another one, two of them can
+//! ping-pong state between frames – particles simulated entirely on the GPU, for instance.
+
+use gl;
+use gl::types::*;
+use std::marker::PhantomData;
+
+use context::GraphicsContext;
+use tess::{Mode, Tess, TessVertices};
+use vertex::Vertex;
+
+/// A transform feedback object capturing vertices of type `V`.
+pub struct TransformFeedback<V> {
+  handle: GLuint,
+  tess: Tess<V>,
+  capacity: usize
+}
+
+impl<V> TransformFeedback<V> where V: Vertex {
+  /// Create a new transform feedback object able to capture `capacity` vertices.
+  pub fn new<C>(ctx: &mut C, capacity: usize) -> Self where C: GraphicsContext {
+    let tess = Tess::new(ctx, Mode::Point, TessVertices::Reserve(capacity), None);
+    let buffer = tess.vertex_buffer_handle().unwrap_or(0);
+    let mut handle: GLuint = 0;
+
+    unsafe {
+      gl::GenTransformFeedbacks(1, &mut handle);
+      gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, handle);
+      gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, 0, buffer);
+      gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, 0);
+    }
+
+    TransformFeedback {
+      handle,
+      tess,
+      capacity
+    }
+  }
+
+  /// Number of vertices the transform feedback object can capture.
+  pub fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  /// Capture the vertices output by the renders issued in `f`.
+  ///
+  /// The renders must output primitives of the same kind as `mode`: points for `Mode::Point`,
+  /// lines for `Mode::Line` and `Mode::LineStrip` and triangles for the others. Any previously
+  /// captured vertices are overwritten. Vertices past the capacity are discarded.
+  ///
+  /// # Panic
+  ///
+  /// Patches cannot be captured: pass the kind of primitives output by the tessellation stages
+  /// instead of `Mode::Patch`.
+  pub fn capture<F, R>(&self, mode: Mode, f: F) -> R where F: FnOnce() -> R {
+    unsafe {
+      gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, self.handle);
+      gl::BeginTransformFeedback(opengl_primitive(mode));
+    }
+
+    let r = f();
+
+    unsafe {
+      gl::EndTransformFeedback();
+      gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, 0);
+    }
+
+    r
+  }
+
+  /// Capture the vertices output by the renders issued in `f`, without rasterizing them.
+  ///
+  /// This is the same as `TransformFeedback::capture`, but the primitives are discarded right after
+  /// being captured: the fragment shader doesn’t run and nothing is written to the framebuffer.
+  /// That’s what you want when the renders only compute vertices – updating particles, for
+  /// instance.
+  ///
+  /// # Panic
+  ///
+  /// Patches cannot be captured: pass the kind of primitives output by the tessellation stages
+  /// instead of `Mode::Patch`.
+  pub fn capture_only<F, R>(&self, mode: Mode, f: F) -> R where F: FnOnce() -> R {
+    unsafe { gl::Enable(gl::RASTERIZER_DISCARD) };
+    let r = self.capture(mode, f);
+    unsafe { gl::Disable(gl::RASTERIZER_DISCARD) };
+
+    r
+  }
+
+  /// Tessellation holding the captured vertices.
+  ///
+  /// Use it to read the captured vertices back with `Tess::as_slice`. Rendering it directly
+  /// renders all `capacity` vertices; prefer `TessGate::render_transform_feedback` to render only
+  /// the captured ones.
+  pub fn tess(&self) -> &Tess<V> {
+    &self.tess
+  }
+
+  // Render the captured vertices.
+  pub(crate) fn render<C>(&self, ctx: &mut C, mode: Mode) where C: GraphicsContext {
+    self.tess.render_transform_feedback(ctx, mode, self.handle);
+  }
+}
+
+impl<V> Drop for TransformFeedback<V> {
+  fn drop(&mut self) {
+    unsafe { gl::DeleteTransformFeedbacks(1, &self.handle) }
+  }
+}
+
+/// A query counting the primitives written by transform feedback.
+///
+/// Results are computed asynchronously by the GPU: use `is_ready` to know whether reading the
+/// result would block.
+pub struct PrimitivesWritten {
+  handle: GLuint,
+  _p: PhantomData<*const ()>
+}
+
+impl PrimitivesWritten {
+  /// Create a new query.
+  pub fn new() -> Self {
+    let mut handle: GLuint = 0;
+
+    unsafe { gl::GenQueries(1, &mut handle) };
+
+    PrimitivesWritten {
+      handle,
+      _p: PhantomData
+    }
+  }
+
+  /// Count the primitives written by transform feedback during `f`.
+  pub fn measure<F, R>(&self, f: F) -> R where F: FnOnce() -> R {
+    unsafe { gl::BeginQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN, self.handle) };
+    let r = f();
+    unsafe { gl::EndQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN) };
+
+    r
+  }
+
+  /// Whether the result of the last measure is available.
+  pub fn is_ready(&self) -> bool {
+    let mut ready: GLuint = gl::FALSE as GLuint;
+    unsafe { gl::GetQueryObjectuiv(self.handle, gl::QUERY_RESULT_AVAILABLE, &mut ready) };
+    ready == gl::TRUE as GLuint
+  }
+
+  /// Number of primitives written during the last measure.
+  ///
+  /// This function blocks until the result is available.
+  pub fn result(&self) -> u32 {
+    let mut written: GLuint = 0;
+    unsafe { gl::GetQueryObjectuiv(self.handle, gl::QUERY_RESULT, &mut written) };
+    written
+  }
+}
+
+impl Default for PrimitivesWritten {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Drop for PrimitivesWritten {
+  fn drop(&mut self) {
+    unsafe { gl::DeleteQueries(1, &self.handle) }
+  }
+}
+
+fn opengl_primitive(mode: Mode) -> GLenum {
+  match mode {
+    Mode::Point => gl::POINTS,
+    Mode::Line | Mode::LineStrip => gl::LINES,
+    Mode::Triangle | Mode::TriangleFan | Mode::TriangleStrip => gl::TRIANGLES,
+    Mode::Patch(_) => panic!("cannot capture patches; capture the primitives output by the tessellation stages instead")
+  }
+}
@@ -43,6 +43,12 @@
 //! If you absolutely want to use your own types – which is legit, you can implement `Vertex` by
 //! mapping your inner fields to a tuple or `GTup`, and call the right `Vertex` method on that
 //! tuple.
+//!
+//! # Runtime layouts
+//!
+//! Sometimes, the layout of your vertices is only known at runtime – typically when loading meshes
+//! from files. In that case, you can describe it with a `VertexLayout` and create a tessellation
+//! from raw bytes with `Tess::from_raw`.
 
 use std::vec::Vec;
 
@@ -67,6 +73,40 @@ pub struct VertexComponentFormat {
   pub align: usize
 }
 
+/// Runtime description of how vertices are laid out in memory.
+///
+/// All the attributes live in the same buffer, which holds a vertex every `stride` bytes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VertexLayout {
+  /// Attributes of the vertices.
+  pub attributes: Vec<VertexAttribute>,
+  /// Size in bytes between two consecutive vertices.
+  pub stride: usize
+}
+
+impl VertexLayout {
+  pub fn new(attributes: Vec<VertexAttribute>, stride: usize) -> Self {
+    VertexLayout { attributes, stride }
+  }
+}
+
+/// Runtime description of a single vertex attribute.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VertexAttribute {
+  /// Location of the attribute, as declared in the vertex shader.
+  pub location: u32,
+  /// Format of the attribute. Its `align` field is ignored, as `offset` is explicit.
+  pub format: VertexComponentFormat,
+  /// Offset in bytes of the attribute from the beginning of a vertex.
+  pub offset: usize
+}
+
+impl VertexAttribute {
+  pub fn new(location: u32, format: VertexComponentFormat, offset: usize) -> Self {
+    VertexAttribute { location, format, offset }
+  }
+}
+
 /// Possible type of vertex components.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Type {